@@ -0,0 +1,131 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Normal form conversion benchmarks.
+////////////////////////////////////////////////////////////////////////////////
+
+// External library imports
+use criterion::black_box;
+use criterion::BatchSize;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use simple_predicates::CnfHashSet;
+use simple_predicates::CnfVec;
+use simple_predicates::DnfVec;
+use simple_predicates::Eval;
+use simple_predicates::Expr;
+
+
+/// A benchmark variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Var(u32);
+
+impl Eval for Var {
+    type Context = ();
+    fn eval(&self, _: &()) -> bool { true }
+}
+
+/// Builds a disjunction of `terms` conjunctions of two variables each, with a
+/// negated conjunction mixed in every third term. The CNF of the expression
+/// has `2^terms` clauses.
+fn or_of_ands(terms: u32) -> Expr<Var> {
+    let mut expr: Option<Expr<Var>> = None;
+    for i in 0..terms {
+        let term = Expr::from(Var(2 * i)).and(Var(2 * i + 1));
+        let term = if i % 3 == 2 { !(!term) } else { term };
+        expr = Some(match expr {
+            Some(expr) => expr.or(term),
+            None       => term,
+        });
+    }
+    expr.expect("nonzero term count")
+}
+
+/// Builds a conjunction of `clauses` negated conjunctions of three variables,
+/// which is nearly in CNF already.
+fn and_of_nots(clauses: u32) -> Expr<Var> {
+    let exprs = (0..clauses).map(|i| !Expr::from(Var(3 * i))
+        .and(Var(3 * i + 1))
+        .and(Var(3 * i + 2)));
+    Expr::conjoin(exprs).expect("nonzero clause count")
+}
+
+/// Builds a conjunction of `clauses` disjunctions of three literals, which is
+/// already in CNF.
+fn and_of_ors(clauses: u32) -> Expr<Var> {
+    let exprs = (0..clauses).map(|i| Expr::from(Var(3 * i))
+        .or(!Expr::from(Var(3 * i + 1)))
+        .or(Var(3 * i + 2)));
+    Expr::conjoin(exprs).expect("nonzero clause count")
+}
+
+fn cnf_conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cnf_from_or_of_ands");
+    for terms in [4, 8, 10] {
+        let expr = or_of_ands(terms);
+        let _ = group.bench_with_input(
+            BenchmarkId::new("CnfVec", terms),
+            &expr,
+            |b, expr| b.iter_batched(
+                || expr.clone(),
+                |expr| CnfVec::from(black_box(expr)),
+                BatchSize::SmallInput));
+        let _ = group.bench_with_input(
+            BenchmarkId::new("CnfHashSet", terms),
+            &expr,
+            |b, expr| b.iter_batched(
+                || expr.clone(),
+                |expr| CnfHashSet::from(black_box(expr)),
+                BatchSize::SmallInput));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("cnf_from_and_of_ors");
+    for clauses in [16, 256] {
+        let expr = and_of_ors(clauses);
+        let _ = group.bench_with_input(
+            BenchmarkId::new("CnfVec", clauses),
+            &expr,
+            |b, expr| b.iter_batched(
+                || expr.clone(),
+                |expr| CnfVec::from(black_box(expr)),
+                BatchSize::SmallInput));
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("cnf_from_and_of_nots");
+    for clauses in [16, 256] {
+        let expr = and_of_nots(clauses);
+        let _ = group.bench_with_input(
+            BenchmarkId::new("CnfVec", clauses),
+            &expr,
+            |b, expr| b.iter_batched(
+                || expr.clone(),
+                |expr| CnfVec::from(black_box(expr)),
+                BatchSize::SmallInput));
+    }
+    group.finish();
+}
+
+fn dnf_conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dnf_from_and_of_nots");
+    for clauses in [4, 8] {
+        let expr = and_of_nots(clauses);
+        let _ = group.bench_with_input(
+            BenchmarkId::new("DnfVec", clauses),
+            &expr,
+            |b, expr| b.iter_batched(
+                || expr.clone(),
+                |expr| DnfVec::from(black_box(expr)),
+                BatchSize::SmallInput));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cnf_conversion, dnf_conversion);
+criterion_main!(benches);
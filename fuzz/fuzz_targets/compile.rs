@@ -0,0 +1,21 @@
+//! Compiles arbitrary text as a predicate, with limits keeping accepted
+//! expressions small enough for the recursive normalization steps.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_predicates::CompileOptions;
+use simple_predicates::ComplexityLimit;
+use simple_predicates::Predicate;
+use simple_predicates_fuzz::Bit;
+
+fuzz_target!(|data: &str| {
+    let options = CompileOptions {
+        limit: ComplexityLimit {
+            max_depth: Some(64),
+            max_nodes: Some(32),
+            max_vars: None,
+        },
+        cnf: true,
+    };
+    let _ = Predicate::compile::<Bit>(data, &options);
+});
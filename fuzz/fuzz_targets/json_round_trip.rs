@@ -0,0 +1,15 @@
+//! Parses arbitrary text as a JSON expression, and checks that any expression
+//! which parses survives a round-trip. The `serde_json` recursion limit bounds
+//! the depth of parsed expressions.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_predicates::Expr;
+
+fuzz_target!(|data: &str| {
+    if let Ok(expr) = Expr::from_json(data) {
+        let json = expr.to_json().expect("serialize parsed expression");
+        let parsed = Expr::from_json(&json).expect("parse serialized expression");
+        assert_eq!(parsed, expr);
+    }
+});
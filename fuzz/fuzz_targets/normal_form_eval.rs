@@ -0,0 +1,31 @@
+//! Checks that converting an arbitrary expression into each normal form
+//! preserves its value under an arbitrary assignment.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use simple_predicates::CnfHashSet;
+use simple_predicates::CnfVec;
+use simple_predicates::DnfHashSet;
+use simple_predicates::DnfVec;
+use simple_predicates::Eval;
+use simple_predicates::Expr;
+use simple_predicates::TraversalOrder;
+use simple_predicates_fuzz::Bit;
+
+/// The maximum number of nodes of a converted expression. Normal forms can be
+/// exponentially larger than the expression.
+const MAX_NODES: usize = 32;
+
+fuzz_target!(|input: (Expr<Bit>, u8)| {
+    let (expr, data) = input;
+    if expr.iter_subexprs(TraversalOrder::PreOrder).count() > MAX_NODES {
+        return;
+    }
+    let expected = expr.eval(&data);
+
+    assert_eq!(CnfVec::from(expr.clone()).eval(&data), expected);
+    assert_eq!(DnfVec::from(expr.clone()).eval(&data), expected);
+    assert_eq!(CnfHashSet::from(expr.clone()).eval(&data), expected);
+    assert_eq!(DnfHashSet::from(expr.clone()).eval(&data), expected);
+    assert_eq!(expr.simplify_traced().0.eval(&data), expected);
+});
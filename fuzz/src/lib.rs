@@ -0,0 +1,51 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Shared definitions for the fuzz targets.
+//!
+//! Run a target with `cargo +nightly fuzz run <target>` from the repository
+//! root.
+////////////////////////////////////////////////////////////////////////////////
+
+// External library imports
+use arbitrary::Arbitrary;
+use arbitrary::Result;
+use arbitrary::Unstructured;
+use simple_predicates::Eval;
+
+// Standard library imports
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Bit
+////////////////////////////////////////////////////////////////////////////////
+/// A variable which is true if its bit is set in the context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bit(pub u8);
+
+impl Eval for Bit {
+    type Context = u8;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        data & (1 << self.0) != 0
+    }
+}
+
+impl FromStr for Bit {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<u8>().map(|bit| Bit(bit % 8))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Bit {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Bit(u.int_in_range(0..=7)?))
+    }
+}
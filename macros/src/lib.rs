@@ -0,0 +1,593 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Procedural macros for `simple_predicates`.
+//!
+//! These macros are re-exported by `simple_predicates` when its "macros"
+//! feature is enabled, and should be used through that crate.
+////////////////////////////////////////////////////////////////////////////////
+#![warn(missing_docs)]
+#![warn(rust_2018_idioms)]
+#![warn(unused)]
+
+// Standard library imports
+use proc_macro::Delimiter;
+use proc_macro::Group;
+use proc_macro::Ident;
+use proc_macro::Literal;
+use proc_macro::Punct;
+use proc_macro::Spacing;
+use proc_macro::Span;
+use proc_macro::TokenStream;
+use proc_macro::TokenTree;
+
+
+/// The largest number of distinct variables for which `static_expr!` checks
+/// satisfiability.
+const MAX_CHECKED_VARS: usize = 16;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// static_expr
+////////////////////////////////////////////////////////////////////////////////
+/// Builds a `StaticExpr` from a boolean expression at compile time.
+///
+/// The expression is written with `!`, `&`, and `|` (in order of decreasing
+/// precedence) and parentheses. Any other sequence of tokens is a variable,
+/// and is emitted as an expression of the `StaticExpr`'s variable type:
+///
+/// ```rust,ignore
+/// static RULE: StaticExpr<Flag> = static_expr!{ (Flag::A & !Flag::B) | C };
+/// ```
+///
+/// The expression is converted into negation normal form, with repeated
+/// operands removed, and each distinct variable is stored once. Malformed
+/// expressions are reported as compile errors, as are expressions which can
+/// never be satisfied, provided they have at most 16 distinct variables.
+/// Variables are compared by their tokens.
+#[proc_macro]
+pub fn static_expr(input: TokenStream) -> TokenStream {
+    match expand_static_expr(input) {
+        Ok(tokens) => tokens,
+        Err(e)     => e.into_compile_error(),
+    }
+}
+
+/// Parses, normalizes, checks, and emits a `static_expr!` invocation.
+fn expand_static_expr(input: TokenStream) -> Result<TokenStream, Error> {
+    let mut parser = Parser::new(input, Span::call_site());
+    let ast = parser.parse_expr()?;
+    parser.finish()?;
+
+    let mut vars = Vec::new();
+    let norm = normalize(ast, false, &mut vars);
+    if vars.len() <= MAX_CHECKED_VARS && !satisfiable(&norm, vars.len()) {
+        return Err(Error::new(
+            Span::call_site(),
+            "expression can never be satisfied"));
+    }
+
+    let mut nodes = Vec::new();
+    let mut var_nodes = vec![None; vars.len()];
+    let _ = emit(&norm, &vars, &mut nodes, &mut var_nodes);
+
+    let mut items = TokenStream::new();
+    for node in nodes {
+        items.extend(node);
+        items.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+    }
+    let mut args = TokenStream::new();
+    args.extend(Some(TokenTree::from(Punct::new('&', Spacing::Alone))));
+    args.extend(Some(TokenTree::from(Group::new(Delimiter::Bracket, items))));
+
+    let mut out = path("::simple_predicates::StaticExpr::new");
+    out.extend(Some(TokenTree::from(Group::new(Delimiter::Parenthesis, args))));
+    Ok(out)
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Error
+////////////////////////////////////////////////////////////////////////////////
+/// An error to be reported at compile time.
+struct Error {
+    /// The location of the error.
+    span: Span,
+    /// The error message.
+    message: String,
+}
+
+impl Error {
+    /// Constructs a new `Error`.
+    fn new<S>(span: Span, message: S) -> Self where S: Into<String> {
+        Error { span, message: message.into() }
+    }
+
+    /// Returns a `compile_error!` invocation reporting the error.
+    fn into_compile_error(self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+
+        let tokens: Vec<TokenTree> = vec![
+            Ident::new("compile_error", self.span).into(),
+            bang.into(),
+            Group::new(Delimiter::Parenthesis, TokenTree::from(message).into())
+                .into(),
+        ];
+        tokens.into_iter().collect()
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsing
+////////////////////////////////////////////////////////////////////////////////
+/// A parsed boolean expression.
+enum Ast {
+    /// A variable, with its tokens.
+    Var(TokenStream),
+    /// A negated expression.
+    Not(Box<Ast>),
+    /// A disjunction of expressions.
+    Or(Box<Ast>, Box<Ast>),
+    /// A conjunction of expressions.
+    And(Box<Ast>, Box<Ast>),
+}
+
+/// A recursive descent parser over a token sequence.
+struct Parser {
+    /// The tokens to parse.
+    tokens: Vec<TokenTree>,
+    /// The index of the next token.
+    pos: usize,
+    /// The span to report for errors at the end of the input.
+    end: Span,
+}
+
+impl Parser {
+    /// Constructs a new `Parser` for the given tokens.
+    fn new(input: TokenStream, end: Span) -> Self {
+        Parser { tokens: input.into_iter().collect(), pos: 0, end }
+    }
+
+    /// Returns the operator character of the next token, if it is one.
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(TokenTree::Punct(p)) if matches!(p.as_char(), '!' | '&' | '|')
+                => Some(p.as_char()),
+            _   => None,
+        }
+    }
+
+    /// Consumes a binary operator, accepting `&&` and `||` as `&` and `|`.
+    fn bump_op(&mut self, op: char) {
+        let joint = matches!(&self.tokens[self.pos],
+            TokenTree::Punct(p) if p.spacing() == Spacing::Joint);
+        self.pos += 1;
+        if joint && self.peek_op() == Some(op) { self.pos += 1; }
+    }
+
+    /// Returns an error if any tokens remain.
+    fn finish(&self) -> Result<(), Error> {
+        match self.tokens.get(self.pos) {
+            Some(token) => Err(Error::new(token.span(), "unexpected token")),
+            None        => Ok(()),
+        }
+    }
+
+    /// Parses a disjunction.
+    fn parse_expr(&mut self) -> Result<Ast, Error> {
+        let mut expr = self.parse_and()?;
+        while self.peek_op() == Some('|') {
+            self.bump_op('|');
+            let rhs = self.parse_and()?;
+            expr = Ast::Or(Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    /// Parses a conjunction.
+    fn parse_and(&mut self) -> Result<Ast, Error> {
+        let mut expr = self.parse_unary()?;
+        while self.peek_op() == Some('&') {
+            self.bump_op('&');
+            let rhs = self.parse_unary()?;
+            expr = Ast::And(Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    /// Parses a negation, a parenthesized expression, or a variable.
+    fn parse_unary(&mut self) -> Result<Ast, Error> {
+        match self.tokens.get(self.pos) {
+            None => Err(Error::new(self.end, "expected an expression")),
+
+            Some(TokenTree::Punct(p)) if p.as_char() == '!' => {
+                self.pos += 1;
+                Ok(Ast::Not(Box::new(self.parse_unary()?)))
+            },
+
+            Some(TokenTree::Group(g))
+                if g.delimiter() == Delimiter::Parenthesis =>
+            {
+                let mut inner = Parser::new(g.stream(), g.span_close());
+                let expr = inner.parse_expr()?;
+                inner.finish()?;
+                self.pos += 1;
+                match self.tokens.get(self.pos) {
+                    Some(token) if self.peek_op().is_none() => Err(Error::new(
+                        token.span(),
+                        "expected `&` or `|`")),
+                    _ => Ok(expr),
+                }
+            },
+
+            Some(token) if self.peek_op().is_some() => Err(Error::new(
+                token.span(),
+                "expected an expression")),
+
+            Some(_) => {
+                let mut var = TokenStream::new();
+                while self.pos < self.tokens.len() && self.peek_op().is_none() {
+                    var.extend(Some(self.tokens[self.pos].clone()));
+                    self.pos += 1;
+                }
+                Ok(Ast::Var(var))
+            },
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Normalization
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression in negation normal form.
+#[derive(Clone, PartialEq)]
+enum Norm {
+    /// A variable, by index, and whether it is unnegated.
+    Lit(usize, bool),
+    /// A disjunction of at least two distinct expressions.
+    Or(Vec<Norm>),
+    /// A conjunction of at least two distinct expressions.
+    And(Vec<Norm>),
+}
+
+/// Converts the expression into negation normal form, negating it if `negate`
+/// is true. Distinct variables are collected into `vars`.
+fn normalize(ast: Ast, negate: bool, vars: &mut Vec<(String, TokenStream)>)
+    -> Norm
+{
+    match ast {
+        Ast::Var(tokens) => {
+            let key = tokens.to_string();
+            let idx = match vars.iter().position(|(k, _)| *k == key) {
+                Some(idx) => idx,
+                None      => { vars.push((key, tokens)); vars.len() - 1 },
+            };
+            Norm::Lit(idx, !negate)
+        },
+        Ast::Not(p)    => normalize(*p, !negate, vars),
+        Ast::Or(a, b)  => join(
+            normalize(*a, negate, vars),
+            normalize(*b, negate, vars),
+            negate),
+        Ast::And(a, b) => join(
+            normalize(*a, negate, vars),
+            normalize(*b, negate, vars),
+            !negate),
+    }
+}
+
+/// Joins the expressions into a flattened conjunction or disjunction, omitting
+/// repeated operands.
+fn join(a: Norm, b: Norm, conjunction: bool) -> Norm {
+    let mut operands: Vec<Norm> = Vec::new();
+    for expr in [a, b] {
+        match (expr, conjunction) {
+            (Norm::And(xs), true) | (Norm::Or(xs), false) => {
+                for x in xs {
+                    if !operands.contains(&x) { operands.push(x); }
+                }
+            },
+            (x, _) => if !operands.contains(&x) { operands.push(x); },
+        }
+    }
+    match (operands.len(), conjunction) {
+        (1, _)     => operands.pop().expect("one operand"),
+        (_, true)  => Norm::And(operands),
+        (_, false) => Norm::Or(operands),
+    }
+}
+
+/// Evaluates the expression with the variables assigned from the bits of
+/// `assignment`.
+fn eval(norm: &Norm, assignment: u32) -> bool {
+    match norm {
+        Norm::Lit(idx, pos) => ((assignment >> idx) & 1 == 1) == *pos,
+        Norm::Or(xs)        => xs.iter().any(|x| eval(x, assignment)),
+        Norm::And(xs)       => xs.iter().all(|x| eval(x, assignment)),
+    }
+}
+
+/// Returns true if some assignment of the variables satisfies the expression.
+fn satisfiable(norm: &Norm, var_count: usize) -> bool {
+    (0..1u32 << var_count).any(|assignment| eval(norm, assignment))
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Emission
+////////////////////////////////////////////////////////////////////////////////
+/// Emits the nodes of the expression in post-order, returning the index of its
+/// root node. Each variable is emitted once.
+fn emit(
+    norm: &Norm,
+    vars: &[(String, TokenStream)],
+    nodes: &mut Vec<TokenStream>,
+    var_nodes: &mut Vec<Option<usize>>)
+    -> usize
+{
+    match norm {
+        Norm::Lit(idx, pos) => {
+            let var = match var_nodes[*idx] {
+                Some(var) => var,
+                None      => {
+                    let mut node = path("::simple_predicates::Node::Var");
+                    node.extend(Some(TokenTree::from(Group::new(
+                        Delimiter::Parenthesis,
+                        vars[*idx].1.clone()))));
+                    nodes.push(node);
+                    var_nodes[*idx] = Some(nodes.len() - 1);
+                    nodes.len() - 1
+                },
+            };
+            if *pos { return var; }
+            nodes.push(node("Not", &[var]));
+            nodes.len() - 1
+        },
+        Norm::Or(xs) | Norm::And(xs) => {
+            let name = if let Norm::Or(_) = norm { "Or" } else { "And" };
+            let mut acc = emit(&xs[0], vars, nodes, var_nodes);
+            for x in &xs[1..] {
+                let next = emit(x, vars, nodes, var_nodes);
+                nodes.push(node(name, &[acc, next]));
+                acc = nodes.len() - 1;
+            }
+            acc
+        },
+    }
+}
+
+/// Returns the tokens of a `Node` variant referring to the given operands.
+fn node(variant: &str, operands: &[usize]) -> TokenStream {
+    let mut args = TokenStream::new();
+    for (i, operand) in operands.iter().enumerate() {
+        if i > 0 {
+            args.extend(Some(TokenTree::from(Punct::new(',', Spacing::Alone))));
+        }
+        args.extend(Some(TokenTree::from(Literal::usize_unsuffixed(*operand))));
+    }
+    let mut node = path(&format!("::simple_predicates::Node::{}", variant));
+    node.extend(Some(TokenTree::from(Group::new(Delimiter::Parenthesis, args))));
+    node
+}
+
+/// Returns the tokens of the given path.
+fn path(path: &str) -> TokenStream {
+    path.parse().expect("valid path")
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// derive(Eval)
+////////////////////////////////////////////////////////////////////////////////
+/// Derives `Eval` for a struct with a single field, by comparing a field of the
+/// context with the struct's field.
+///
+/// The comparison is configured with an `eval` attribute giving the `context`
+/// type, the `field` of the context to compare (which may be a dotted path),
+/// and the comparison `op`, which is one of `==`, `!=`, `<`, `<=`, `>`, or
+/// `>=`. The context field is the left operand:
+///
+/// ```rust,ignore
+/// #[derive(Eval)]
+/// #[eval(context = Person, field = "age", op = ">=")]
+/// struct MinAge(u32);
+///
+/// // Expands to:
+/// impl Eval for MinAge {
+///     type Context = Person;
+///     fn eval(&self, data: &Person) -> bool { data.age >= self.0 }
+/// }
+/// ```
+#[proc_macro_derive(Eval, attributes(eval))]
+pub fn derive_eval(input: TokenStream) -> TokenStream {
+    match expand_derive_eval(input) {
+        Ok(tokens) => tokens,
+        Err(e)     => e.into_compile_error(),
+    }
+}
+
+/// Parses the struct and its `eval` attribute and emits the `Eval` impl.
+fn expand_derive_eval(input: TokenStream) -> Result<TokenStream, Error> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut args = None;
+    let mut pos = 0;
+
+    // Outer attributes and visibility.
+    loop {
+        match tokens.get(pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(g)) = tokens.get(pos + 1) {
+                    let mut inner = g.stream().into_iter();
+                    if let (
+                        Some(TokenTree::Ident(name)),
+                        Some(TokenTree::Group(a))) = (inner.next(), inner.next())
+                    {
+                        if name.to_string() == "eval" {
+                            args = Some((a.stream(), a.span()));
+                        }
+                    }
+                }
+                pos += 2;
+            },
+            Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                pos += 1;
+                if let Some(TokenTree::Group(g)) = tokens.get(pos) {
+                    if g.delimiter() == Delimiter::Parenthesis { pos += 1; }
+                }
+            },
+            _ => break,
+        }
+    }
+
+    let name = match (tokens.get(pos), tokens.get(pos + 1)) {
+        (Some(TokenTree::Ident(kw)), Some(TokenTree::Ident(name)))
+            if kw.to_string() == "struct" => name.clone(),
+        (Some(token), _) => return Err(Error::new(
+            token.span(),
+            "`Eval` can only be derived for structs")),
+        (None, _) => return Err(Error::new(
+            Span::call_site(),
+            "expected a struct")),
+    };
+    pos += 2;
+
+    let accessor = match tokens.get(pos) {
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => return Err(
+            Error::new(p.span(), "`Eval` cannot be derived for generic structs")),
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis
+            => single_field(g, false)?,
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace
+            => single_field(g, true)?,
+        _ => return Err(Error::new(
+            name.span(),
+            "`Eval` can only be derived for structs with a single field")),
+    };
+
+    let (args, span) = args.ok_or_else(|| Error::new(
+        name.span(),
+        "missing `#[eval(context = .., field = \"..\", op = \"..\")]`"))?;
+    let mut context = None;
+    let mut field = None;
+    let mut op = None;
+    for (key, value) in split_args(args, span)? {
+        match key.to_string().as_str() {
+            "context" => context = Some(value.to_string()),
+            "field"   => field = Some(string_arg(&key, &value)?),
+            "op"      => op = Some(string_arg(&key, &value)?),
+            _         => return Err(Error::new(
+                key.span(),
+                "expected `context`, `field`, or `op`")),
+        }
+    }
+    let context = context
+        .ok_or_else(|| Error::new(span, "missing `context` argument"))?;
+    let field = field
+        .ok_or_else(|| Error::new(span, "missing `field` argument"))?;
+    let op = op
+        .ok_or_else(|| Error::new(span, "missing `op` argument"))?;
+
+    if !matches!(op.as_str(), "==" | "!=" | "<" | "<=" | ">" | ">=") {
+        return Err(Error::new(span, format!("unsupported comparison `{}`", op)));
+    }
+    let valid_segment = |s: &str| !s.is_empty() && s.chars()
+        .all(|c| c.is_alphanumeric() || c == '_');
+    if !field.split('.').all(valid_segment) {
+        return Err(Error::new(span, format!("invalid field path `{}`", field)));
+    }
+
+    format!("impl ::simple_predicates::Eval for {name} {{\
+            type Context = {context};\
+            fn eval(&self, data: &Self::Context) -> bool {{\
+                data.{field} {op} self.{accessor}\
+            }}\
+        }}",
+        name = name,
+        context = context,
+        field = field,
+        op = op,
+        accessor = accessor)
+        .parse()
+        .map_err(|_| Error::new(span, "invalid `eval` arguments"))
+}
+
+/// Returns the accessor of the single field of a tuple or named struct body.
+fn single_field(body: &Group, named: bool) -> Result<String, Error> {
+    let fields: Vec<Vec<TokenTree>> = split_top_level(body.stream())
+        .into_iter()
+        .filter(|field| !field.is_empty())
+        .collect();
+    if fields.len() != 1 {
+        return Err(Error::new(
+            body.span(),
+            "`Eval` can only be derived for structs with a single field"));
+    }
+    if !named { return Ok("0".into()); }
+
+    // The field name is the identifier before the first lone `:`.
+    let field = &fields[0];
+    for (i, token) in field.iter().enumerate() {
+        if let TokenTree::Punct(p) = token {
+            if p.as_char() == ':' && p.spacing() == Spacing::Alone && i > 0 {
+                if let TokenTree::Ident(name) = &field[i - 1] {
+                    return Ok(name.to_string());
+                }
+            }
+        }
+    }
+    Err(Error::new(body.span(), "expected a named field"))
+}
+
+/// Splits the tokens at each comma which is not within angle brackets.
+fn split_top_level(tokens: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0usize;
+    for token in tokens {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => { parts.push(Vec::new()); continue; },
+                _   => (),
+            }
+        }
+        parts.last_mut().expect("nonempty parts").push(token);
+    }
+    parts
+}
+
+/// Splits `key = value` arguments.
+fn split_args(args: TokenStream, span: Span)
+    -> Result<Vec<(Ident, TokenStream)>, Error>
+{
+    let mut out = Vec::new();
+    for part in split_top_level(args) {
+        if part.is_empty() { continue; }
+        let mut iter = part.into_iter();
+        match (iter.next(), iter.next()) {
+            (Some(TokenTree::Ident(key)), Some(TokenTree::Punct(eq)))
+                if eq.as_char() == '=' => out.push((key, iter.collect())),
+            _ => return Err(Error::new(span, "expected `key = value`")),
+        }
+    }
+    Ok(out)
+}
+
+/// Returns the contents of a string literal argument.
+fn string_arg(key: &Ident, value: &TokenStream) -> Result<String, Error> {
+    let s = value.to_string();
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        Ok(s[1..s.len() - 1].to_string())
+    } else {
+        Err(Error::new(
+            key.span(),
+            format!("expected a string for `{}`", key)))
+    }
+}
@@ -0,0 +1,91 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Python bindings for `simple_predicates`.
+//!
+//! Exposes an `Expr` class over string variables which can be parsed from and
+//! serialized to JSON, and evaluated with a Python callable resolving each
+//! variable name to a truth value. Build with
+//! [maturin](https://www.maturin.rs/):
+//!
+//! ```text
+//! maturin develop --manifest-path python/Cargo.toml
+//! ```
+//!
+//! The tests in `python/tests` are then run with `pytest python/tests`.
+////////////////////////////////////////////////////////////////////////////////
+#![warn(missing_docs)]
+#![warn(rust_2018_idioms)]
+#![warn(unused)]
+// Triggered by code generated by the pyo3 attribute macros.
+#![allow(clippy::useless_conversion)]
+
+// External library imports
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use simple_predicates::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PyExpr
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression over string variables.
+#[pyclass(name = "Expr", module = "simple_predicates_py")]
+#[derive(Debug, Clone)]
+pub struct PyExpr(Expr<String>);
+
+#[pymethods]
+impl PyExpr {
+    /// Parses an expression from its JSON representation.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Expr::from_json(json)
+            .map(PyExpr)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the JSON representation of the expression.
+    fn to_json(&self) -> PyResult<String> {
+        self.0.to_json()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Evaluates the expression, calling `resolve` with the name of each
+    /// variable reached. The first exception raised by `resolve` is
+    /// propagated.
+    fn eval(&self, resolve: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let mut error = None;
+        let result = self.0.eval_with_provider(&mut |var: &String| {
+            if error.is_some() { return false; }
+            match resolve.call1((var.as_str(),)).and_then(|r| r.is_truthy()) {
+                Ok(value) => value,
+                Err(e)    => { error = Some(e); false },
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None    => Ok(result),
+        }
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Module
+////////////////////////////////////////////////////////////////////////////////
+/// The `simple_predicates_py` Python module.
+#[pymodule]
+fn simple_predicates_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyExpr>()
+}
@@ -0,0 +1,51 @@
+"""Tests for the `simple_predicates_py` bindings.
+
+Build the module into the active environment before running:
+
+    maturin develop --manifest-path python/Cargo.toml
+    pytest python/tests
+"""
+import pytest
+
+from simple_predicates_py import Expr
+
+
+SOURCE = '{"And":[{"Var":"a"},{"Not":{"Var":"b"}}]}'
+
+
+def test_json_round_trip():
+    expr = Expr.from_json(SOURCE)
+    assert Expr.from_json(expr.to_json()) == expr
+
+
+def test_invalid_json_raises_value_error():
+    with pytest.raises(ValueError):
+        Expr.from_json('{"Xor":[]}')
+
+
+def test_eval_resolves_variables():
+    expr = Expr.from_json(SOURCE)
+    assert expr.eval(lambda name: name == "a")
+    assert not expr.eval(lambda name: name in ("a", "b"))
+
+
+def test_eval_short_circuits():
+    expr = Expr.from_json(SOURCE)
+    seen = []
+
+    def resolve(name):
+        seen.append(name)
+        return False
+
+    assert not expr.eval(resolve)
+    assert seen == ["a"]
+
+
+def test_eval_propagates_exceptions():
+    expr = Expr.from_json(SOURCE)
+
+    def resolve(name):
+        raise KeyError(name)
+
+    with pytest.raises(KeyError):
+        expr.eval(resolve)
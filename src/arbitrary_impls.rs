@@ -0,0 +1,81 @@
+
+// Internal library imports.
+use crate::CnfHashSet;
+use crate::CnfVec;
+use crate::DnfHashSet;
+use crate::DnfVec;
+use crate::Eval;
+use crate::Expr;
+
+// External library imports
+use arbitrary::Arbitrary;
+use arbitrary::Result;
+use arbitrary::Unstructured;
+
+// Standard library imports
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Arbitrary expressions
+////////////////////////////////////////////////////////////////////////////////
+/// The maximum depth of an arbitrary expression. Limiting the depth keeps
+/// generated expressions from overflowing the stack in recursive operations.
+const ARBITRARY_MAX_DEPTH: usize = 16;
+
+impl<'a, V> Arbitrary<'a> for Expr<V> where V: Arbitrary<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_expr(u, ARBITRARY_MAX_DEPTH)
+    }
+}
+
+/// Generates an arbitrary expression of at most the given depth.
+fn arbitrary_expr<'a, V>(u: &mut Unstructured<'a>, depth: usize)
+    -> Result<Expr<V>>
+    where V: Arbitrary<'a>
+{
+    use Expr::*;
+    let choice = if depth == 0 || u.is_empty() {
+        0
+    } else {
+        u.int_in_range(0..=3)?
+    };
+    Ok(match choice {
+        0 => Var(V::arbitrary(u)?),
+        1 => Not(Box::new(arbitrary_expr(u, depth - 1)?)),
+        2 => Or(
+            Box::new(arbitrary_expr(u, depth - 1)?),
+            Box::new(arbitrary_expr(u, depth - 1)?)),
+        _ => And(
+            Box::new(arbitrary_expr(u, depth - 1)?),
+            Box::new(arbitrary_expr(u, depth - 1)?)),
+    })
+}
+
+impl<'a, V> Arbitrary<'a> for CnfVec<V> where V: Arbitrary<'a> + Eval + PartialEq {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CnfVec::from(Vec::<Expr<V>>::arbitrary(u)?))
+    }
+}
+
+impl<'a, V> Arbitrary<'a> for DnfVec<V> where V: Arbitrary<'a> + Eval + PartialEq {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(DnfVec::from(Vec::<Expr<V>>::arbitrary(u)?))
+    }
+}
+
+impl<'a, V> Arbitrary<'a> for CnfHashSet<V>
+    where V: Arbitrary<'a> + Eval + Eq + Hash
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CnfHashSet::from(Vec::<Expr<V>>::arbitrary(u)?))
+    }
+}
+
+impl<'a, V> Arbitrary<'a> for DnfHashSet<V>
+    where V: Arbitrary<'a> + Eval + Eq + Hash
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(DnfHashSet::from(Vec::<Expr<V>>::arbitrary(u)?))
+    }
+}
@@ -0,0 +1,107 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::stack::guard;
+
+// External library imports
+use boolean_expression::Expr as BoolExpr;
+
+// Standard library imports
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ConstantExprError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that a [`boolean_expression::Expr`] is equivalent to a
+/// constant after its constant operands are folded away, and so cannot be
+/// converted to an [`Expr`].
+///
+/// [`boolean_expression::Expr`]: https://docs.rs/boolean_expression
+/// [`Expr`]: crate::Expr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstantExprError {
+    /// The value of the constant expression.
+    pub value: bool,
+}
+
+impl Display for ConstantExprError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expression is the constant {}, which cannot be converted",
+            self.value)
+    }
+}
+
+impl Error for ConstantExprError {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Conversions
+////////////////////////////////////////////////////////////////////////////////
+impl<V> From<Expr<V>> for BoolExpr<V>
+    where V: Clone + Debug + Eq + Ord + Hash
+{
+    fn from(expr: Expr<V>) -> Self {
+        to_bool_expr(expr)
+    }
+}
+
+/// Converts the expression to a `boolean_expression::Expr`.
+fn to_bool_expr<V>(expr: Expr<V>) -> BoolExpr<V>
+    where V: Clone + Debug + Eq + Ord + Hash
+{
+    guard(|| match expr {
+        Expr::Var(v)    => BoolExpr::Terminal(v),
+        Expr::Not(p)    => BoolExpr::Not(Box::new(to_bool_expr(*p))),
+        Expr::Or(a, b)  => BoolExpr::Or(
+            Box::new(to_bool_expr(*a)),
+            Box::new(to_bool_expr(*b))),
+        Expr::And(a, b) => BoolExpr::And(
+            Box::new(to_bool_expr(*a)),
+            Box::new(to_bool_expr(*b))),
+    })
+}
+
+/// Converts a `boolean_expression::Expr`, folding away its constants.
+///
+/// Returns an error if the expression is equivalent to a constant after
+/// folding. Only constants are folded, so an expression such as `x & !x` is
+/// converted unchanged.
+impl<V> TryFrom<BoolExpr<V>> for Expr<V>
+    where V: Clone + Debug + Eq + Ord + Hash
+{
+    type Error = ConstantExprError;
+
+    fn try_from(expr: BoolExpr<V>) -> Result<Self, Self::Error> {
+        from_bool_expr(expr).map_err(|value| ConstantExprError { value })
+    }
+}
+
+/// Converts a `boolean_expression::Expr`, returning its value as an error if
+/// it is constant.
+fn from_bool_expr<V>(expr: BoolExpr<V>) -> Result<Expr<V>, bool>
+    where V: Clone + Debug + Eq + Ord + Hash
+{
+    guard(|| match expr {
+        BoolExpr::Terminal(v) => Ok(Expr::Var(v)),
+        BoolExpr::Const(x)    => Err(x),
+        BoolExpr::Not(p)      => from_bool_expr(*p)
+            .map(|p| Expr::Not(Box::new(p)))
+            .map_err(|value| !value),
+        BoolExpr::And(a, b)   => match (from_bool_expr(*a), from_bool_expr(*b)) {
+            (Err(false), _) | (_, Err(false)) => Err(false),
+            (Err(true), x) | (x, Err(true))   => x,
+            (Ok(a), Ok(b)) => Ok(Expr::And(Box::new(a), Box::new(b))),
+        },
+        BoolExpr::Or(a, b)    => match (from_bool_expr(*a), from_bool_expr(*b)) {
+            (Err(true), _) | (_, Err(true))   => Err(true),
+            (Err(false), x) | (x, Err(false)) => x,
+            (Ok(a), Ok(b)) => Ok(Expr::Or(Box::new(a), Box::new(b))),
+        },
+    })
+}
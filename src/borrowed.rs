@@ -0,0 +1,64 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::stack::guard;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EvalBorrowed
+////////////////////////////////////////////////////////////////////////////////
+/// Provides functions for performing boolean expression evaluation in the
+/// context of a borrowed `Context`, such as a `&str`, a slice, or a view
+/// type with a lifetime.
+///
+/// Unlike [`Eval`], the context type is parameterized by the lifetime of the
+/// borrow, so the variable type does not need to carry it:
+///
+/// ```rust
+/// use simple_predicates::EvalBorrowed;
+/// use simple_predicates::Expr;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Word(&'static str);
+///
+/// impl EvalBorrowed for Word {
+///     type Context<'a> = &'a str;
+///
+///     fn eval_borrowed(&self, data: &&str) -> bool {
+///         data.split_whitespace().any(|word| word == self.0)
+///     }
+/// }
+///
+/// let expr = Expr::Var(Word("quick")).and(!Expr::Var(Word("lazy")));
+/// let text = String::from("the quick brown fox");
+///
+/// assert!(expr.eval_borrowed(&text.as_str()));
+/// ```
+///
+/// The normal form types require their variables to implement [`Eval`], so
+/// only [`Expr`] implements this trait.
+///
+/// [`Eval`]: crate::Eval
+/// [`Expr`]: crate::Expr
+pub trait EvalBorrowed {
+    /// The contextual data required to evaluate the expression, borrowed for
+    /// the lifetime `'a`.
+    type Context<'a>;
+
+    /// Evaluates the expression, returning its truth value.
+    fn eval_borrowed(&self, data: &Self::Context<'_>) -> bool;
+}
+
+impl<V> EvalBorrowed for Expr<V> where V: EvalBorrowed {
+    type Context<'a> = V::Context<'a>;
+
+    fn eval_borrowed(&self, data: &Self::Context<'_>) -> bool {
+        use Expr::*;
+        guard(|| match self {
+            Var(p)    => p.eval_borrowed(data),
+            Not(p)    => !p.eval_borrowed(data),
+            Or(a, b)  => a.eval_borrowed(data) || b.eval_borrowed(data),
+            And(a, b) => a.eval_borrowed(data) && b.eval_borrowed(data),
+        })
+    }
+}
@@ -0,0 +1,193 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::TraversalOrder;
+
+// Standard library imports
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Instant;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// BudgetExceeded
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that an evaluation ran out of fuel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BudgetExceeded {
+    /// The amount of fuel the evaluation was given.
+    pub fuel: usize,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "evaluation budget of {} exceeded", self.fuel)
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// TimedOut
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that an evaluation passed its deadline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedOut<V> {
+    /// The deadline of the evaluation.
+    pub deadline: Instant,
+    /// The part of the expression which was not yet decided when the deadline
+    /// passed. Evaluating the residual in the same context gives the result of
+    /// the original expression.
+    pub residual: Expr<V>,
+}
+
+impl<V> Display for TimedOut<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "evaluation deadline passed")
+    }
+}
+
+impl<V> Error for TimedOut<V> where V: Debug {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DeadlinePassed
+////////////////////////////////////////////////////////////////////////////////
+/// The variable-independent part of a [`TimedOut`] error, which is kept when
+/// it is converted into an [`Error`].
+///
+/// [`TimedOut`]: crate::TimedOut
+/// [`Error`]: crate::Error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeadlinePassed {
+    /// The deadline of the evaluation.
+    pub deadline: Instant,
+    /// The number of nodes of the undecided part of the expression.
+    pub residual_nodes: usize,
+}
+
+impl Display for DeadlinePassed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "evaluation deadline passed with {} nodes undecided",
+            self.residual_nodes)
+    }
+}
+
+impl Error for DeadlinePassed {}
+
+impl<V> From<TimedOut<V>> for DeadlinePassed {
+    fn from(e: TimedOut<V>) -> Self {
+        DeadlinePassed {
+            deadline: e.deadline,
+            residual_nodes: e.residual
+                .iter_subexprs(TraversalOrder::PreOrder)
+                .count(),
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Budgeted evaluation
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eval {
+    /// Evaluates the expression, charging one unit of fuel for every node
+    /// visited. Returns an error if the fuel is exhausted before the result is
+    /// known.
+    ///
+    /// Nodes skipped by short-circuiting are not charged.
+    pub fn eval_with_budget(&self, data: &V::Context, fuel: usize)
+        -> Result<bool, BudgetExceeded>
+    {
+        let mut remaining = fuel;
+        self.eval_budgeted(data, &mut remaining)
+            .ok_or(BudgetExceeded { fuel })
+    }
+
+    /// Evaluates the expression, returning `None` if the fuel runs out.
+    fn eval_budgeted(&self, data: &V::Context, fuel: &mut usize)
+        -> Option<bool>
+    {
+        use Expr::*;
+        *fuel = fuel.checked_sub(1)?;
+        Some(match self {
+            Var(p)    => p.eval(data),
+            Not(p)    => !p.eval_budgeted(data, fuel)?,
+            Or(a, b)  => a.eval_budgeted(data, fuel)? ||
+                b.eval_budgeted(data, fuel)?,
+            And(a, b) => a.eval_budgeted(data, fuel)? &&
+                b.eval_budgeted(data, fuel)?,
+        })
+    }
+}
+
+impl<V> Expr<V> where V: Eval + Clone {
+    /// Evaluates the expression, checking the deadline before each variable
+    /// is evaluated. If the deadline passes before the result is known, the
+    /// undecided part of the expression is returned in the error.
+    ///
+    /// This relies on [`Instant::now`], which panics on
+    /// `wasm32-unknown-unknown`.
+    ///
+    /// [`Instant::now`]: std::time::Instant::now
+    pub fn eval_with_deadline(&self, data: &V::Context, deadline: Instant)
+        -> Result<bool, TimedOut<V>>
+    {
+        let mut timed_out = false;
+        match self.eval_deadline(data, deadline, &mut timed_out) {
+            Partial::Done(result)   => Ok(result),
+            Partial::Residual(expr) => Err(TimedOut {
+                deadline,
+                residual: expr,
+            }),
+        }
+    }
+
+    /// Evaluates the expression up to the deadline.
+    fn eval_deadline(
+        &self,
+        data: &V::Context,
+        deadline: Instant,
+        timed_out: &mut bool)
+        -> Partial<V>
+    {
+        use Expr::*;
+        use Partial::*;
+        if *timed_out { return Residual(self.clone()); }
+
+        match self {
+            Var(p) => if Instant::now() >= deadline {
+                *timed_out = true;
+                Residual(self.clone())
+            } else {
+                Done(p.eval(data))
+            },
+            Not(p) => match p.eval_deadline(data, deadline, timed_out) {
+                Done(r)     => Done(!r),
+                Residual(e) => Residual(Not(Box::new(e))),
+            },
+            Or(a, b) => match a.eval_deadline(data, deadline, timed_out) {
+                Done(true)  => Done(true),
+                Done(false) => b.eval_deadline(data, deadline, timed_out),
+                Residual(e) => Residual(Or(Box::new(e), b.clone())),
+            },
+            And(a, b) => match a.eval_deadline(data, deadline, timed_out) {
+                Done(false) => Done(false),
+                Done(true)  => b.eval_deadline(data, deadline, timed_out),
+                Residual(e) => Residual(And(Box::new(e), b.clone())),
+            },
+        }
+    }
+}
+
+/// The result of a partial evaluation.
+enum Partial<V> {
+    /// The value of the expression is known.
+    Done(bool),
+    /// The value of the expression depends on the given residual.
+    Residual(Expr<V>),
+}
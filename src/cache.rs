@@ -0,0 +1,156 @@
+
+// Internal library imports.
+use crate::Eval;
+
+// Standard library imports
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EvalCacheByContext
+////////////////////////////////////////////////////////////////////////////////
+/// A least-recently-used cache of evaluation results, keyed by a hash of the
+/// evaluated expression and a fingerprint of the context.
+///
+/// The fingerprint function must return the same value for contexts on which
+/// every cached expression evaluates the same, e.g., an entity id and
+/// revision. Results are keyed by 64-bit hashes, so distinct expressions or
+/// contexts with colliding hashes will share a result.
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// use simple_predicates::EvalCacheByContext;
+/// use simple_predicates::Expr;
+///
+/// let mut cache = EvalCacheByContext::new(100, |data: &Vec<u32>| data[0].into());
+/// let expr = Expr::Var(Contains(4)).and(!Expr::Var(Contains(5)));
+///
+/// assert!(cache.eval(&expr, &vec![1, 4]));
+/// assert!(cache.eval(&expr, &vec![1, 4]));
+/// assert_eq!(cache.hits(), 1);
+/// ```
+pub struct EvalCacheByContext<C, F> where F: Fn(&C) -> u64 {
+    /// The maximum number of cached results.
+    capacity: usize,
+    /// The context fingerprint function.
+    fingerprint: F,
+    /// The cached results and the time of their last use, by key.
+    entries: HashMap<(u64, u64), (bool, u64)>,
+    /// The cached keys, by time of last use.
+    order: BTreeMap<u64, (u64, u64)>,
+    /// The current time, incremented on every use.
+    tick: u64,
+    /// The number of lookups which found a cached result.
+    hits: u64,
+    /// The number of lookups which did not find a cached result.
+    misses: u64,
+    /// The context type.
+    context: PhantomData<fn(&C)>,
+}
+
+impl<C, F> EvalCacheByContext<C, F> where F: Fn(&C) -> u64 {
+    /// Constructs a new `EvalCacheByContext` holding at most `capacity`
+    /// results, using the given context fingerprint function.
+    pub fn new(capacity: usize, fingerprint: F) -> Self {
+        EvalCacheByContext {
+            capacity,
+            fingerprint,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            context: PhantomData,
+        }
+    }
+
+    /// Evaluates the expression on the given context, returning the cached
+    /// result if there is one.
+    pub fn eval<E>(&mut self, expr: &E, data: &C) -> bool
+        where E: Eval<Context=C> + Hash
+    {
+        let mut hasher = DefaultHasher::new();
+        expr.hash(&mut hasher);
+        let key = (hasher.finish(), (self.fingerprint)(data));
+
+        self.tick += 1;
+        if let Some((value, used)) = self.entries.get_mut(&key) {
+            let _ = self.order.remove(used);
+            let _ = self.order.insert(self.tick, key);
+            *used = self.tick;
+            self.hits += 1;
+            return *value;
+        }
+
+        self.misses += 1;
+        let value = expr.eval(data);
+        if self.capacity == 0 { return value; }
+        if self.entries.len() >= self.capacity {
+            if let Some((&oldest, _)) = self.order.iter().next() {
+                if let Some(evicted) = self.order.remove(&oldest) {
+                    let _ = self.entries.remove(&evicted);
+                }
+            }
+        }
+        let _ = self.entries.insert(key, (value, self.tick));
+        let _ = self.order.insert(self.tick, key);
+        value
+    }
+
+    /// Returns the maximum number of cached results.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no cached results.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of evaluations which used a cached result.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of evaluations which did not use a cached result.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+impl<C, F> Debug for EvalCacheByContext<C, F> where F: Fn(&C) -> u64 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvalCacheByContext")
+            .field("capacity", &self.capacity)
+            .field("len", &self.entries.len())
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish()
+    }
+}
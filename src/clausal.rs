@@ -0,0 +1,489 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+use crate::TraversalOrder;
+use crate::truth::clauses_equivalent;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ClauseViolation
+////////////////////////////////////////////////////////////////////////////////
+/// The reason a clause of a normal form is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClauseViolation {
+    /// An `And` expression was found in a clause of a conjunctive normal form.
+    UnexpectedAnd,
+    /// An `Or` expression was found in a clause of a disjunctive normal form.
+    UnexpectedOr,
+    /// A `Not` expression was applied to something other than a variable.
+    NegatedNonVariable,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ClauseError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that a clause of a normal form is not a proper
+/// disjunction (or conjunction) of literals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClauseError {
+    /// The index of the malformed clause, in iteration order.
+    pub clause: usize,
+    /// The path to the offending node within the clause.
+    pub path: ExprPath,
+    /// The reason the clause is malformed.
+    pub violation: ClauseViolation,
+}
+
+impl Display for ClauseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.violation {
+            ClauseViolation::UnexpectedAnd      => "unexpected `And`",
+            ClauseViolation::UnexpectedOr       => "unexpected `Or`",
+            ClauseViolation::NegatedNonVariable => "negated non-variable",
+        };
+        write!(f, "{} in clause {} at {:?}",
+            reason,
+            self.clause,
+            self.path.steps())
+    }
+}
+
+impl Error for ClauseError {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Clause checking
+////////////////////////////////////////////////////////////////////////////////
+/// Checks that each clause is a disjunction of literals if `conjunctive` is
+/// true, or a conjunction of literals otherwise.
+pub (in crate) fn check_clauses<'a, V, I>(clauses: I, conjunctive: bool)
+    -> Result<(), ClauseError>
+    where
+        V: 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+{
+    for (idx, clause) in clauses.into_iter().enumerate() {
+        check_clause(clause, conjunctive)
+            .map_err(|(path, violation)| ClauseError {
+                clause: idx,
+                path,
+                violation,
+            })?;
+    }
+    Ok(())
+}
+
+/// Checks a single clause, returning the path to the first malformed node.
+fn check_clause<V>(clause: &Expr<V>, conjunctive: bool)
+    -> Result<(), (ExprPath, ClauseViolation)>
+{
+    use Expr::*;
+    let mut stack = vec![(clause, ExprPath::root())];
+    while let Some((expr, path)) = stack.pop() {
+        match (expr, conjunctive) {
+            (Var(_), _) => (),
+            (Not(p), _) => if let Var(_) = **p {} else {
+                return Err((path, ClauseViolation::NegatedNonVariable));
+            },
+            (Or(a, b), true) | (And(a, b), false) => {
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+            (And(..), true) => {
+                return Err((path, ClauseViolation::UnexpectedAnd));
+            },
+            (Or(..), false) => {
+                return Err((path, ClauseViolation::UnexpectedOr));
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the expression is a conjunction of clauses in conjunctive
+/// normal form if `conjunctive` is true, or a disjunction of terms in
+/// disjunctive normal form otherwise.
+pub (in crate) fn is_normal_form<V>(expr: &Expr<V>, conjunctive: bool) -> bool {
+    use Expr::*;
+    // Each entry is a node and whether it is inside a clause.
+    let mut stack = vec![(expr, false)];
+    while let Some((expr, in_clause)) = stack.pop() {
+        match (expr, conjunctive) {
+            (Var(_), _) => (),
+            (Not(p), _) => if !matches!(**p, Var(_)) { return false; },
+            (And(a, b), true) | (Or(a, b), false) if !in_clause => {
+                stack.push((a, false));
+                stack.push((b, false));
+            },
+            (Or(a, b), true) | (And(a, b), false) => {
+                stack.push((a, true));
+                stack.push((b, true));
+            },
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Returns true if any `And` or `Or` in the expression has equal operands,
+/// which simplification would merge.
+pub (in crate) fn has_equal_operands<V>(expr: &Expr<V>) -> bool
+    where V: PartialEq
+{
+    use Expr::*;
+    expr.iter_subexprs(TraversalOrder::PreOrder)
+        .any(|expr| matches!(expr, Or(a, b) | And(a, b) if a == b))
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Literals
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the variable and polarity of a literal expression, or `None` if the
+/// expression is not a literal.
+pub (in crate) fn literal<V>(expr: &Expr<V>) -> Option<(&V, bool)> {
+    use Expr::*;
+    match expr {
+        Var(v)                  => Some((v, true)),
+        Not(p) => match &**p {
+            Var(v)              => Some((v, false)),
+            _                   => None,
+        },
+        _                       => None,
+    }
+}
+
+/// Returns true if the clause contains a literal and its negation.
+///
+/// The clause is a disjunction if `conjunctive` is true, and a conjunction
+/// otherwise, and only the operands of that connective are compared. Such a
+/// clause is always true, or always false for a conjunction.
+pub (in crate) fn is_complementary<V>(clause: &Expr<V>, conjunctive: bool)
+    -> bool
+    where V: PartialEq
+{
+    use Expr::*;
+    match (clause, conjunctive) {
+        (Or(..), true) | (And(..), false) => (),
+        _                                 => return false,
+    }
+    let literals: Vec<_> = clause.operands()
+        .into_iter()
+        .filter_map(literal)
+        .collect();
+    literals.iter().any(|(v, pos)| literals
+        .iter()
+        .any(|(w, neg)| pos != neg && v == w))
+}
+
+
+/// A variable or its negation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Literal<V> {
+    /// The variable of the literal.
+    pub var: V,
+    /// Whether the literal is unnegated, and so true when its variable is
+    /// true.
+    pub polarity: bool,
+}
+
+impl<V> Literal<V> {
+    /// Constructs a new `Literal` of the given variable and polarity.
+    pub fn new(var: V, polarity: bool) -> Self {
+        Literal { var, polarity }
+    }
+
+    /// Constructs a new unnegated `Literal`.
+    pub fn positive(var: V) -> Self {
+        Literal { var, polarity: true }
+    }
+
+    /// Constructs a new negated `Literal`.
+    pub fn negative(var: V) -> Self {
+        Literal { var, polarity: false }
+    }
+
+    /// Returns the negation of the literal.
+    pub fn negated(self) -> Self {
+        Literal { var: self.var, polarity: !self.polarity }
+    }
+}
+
+impl<V> From<Literal<V>> for Expr<V> {
+    fn from(literal: Literal<V>) -> Self {
+        if literal.polarity {
+            Expr::Var(literal.var)
+        } else {
+            Expr::Not(Box::new(Expr::Var(literal.var)))
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Polarity
+////////////////////////////////////////////////////////////////////////////////
+/// The signs with which a variable occurs in an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    /// The variable only occurs unnegated.
+    Positive,
+    /// The variable only occurs negated.
+    Negative,
+    /// The variable occurs both negated and unnegated.
+    Both,
+}
+
+impl Polarity {
+    /// Returns the polarity of a variable occurring with both polarities.
+    fn merge(self, other: Polarity) -> Polarity {
+        if self == other { self } else { Polarity::Both }
+    }
+}
+
+/// Returns the polarity of each variable in the given clauses. A variable
+/// under an odd number of negations occurs negated.
+pub (in crate) fn var_polarities<'a, V, I>(clauses: I) -> HashMap<V, Polarity>
+    where
+        V: Eq + Hash + Clone + 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+{
+    use Expr::*;
+    let mut polarities: HashMap<V, Polarity> = HashMap::new();
+    for clause in clauses {
+        let mut stack = vec![(clause, false)];
+        while let Some((expr, negated)) = stack.pop() {
+            match expr {
+                Var(v)    => {
+                    let polarity = if negated {
+                        Polarity::Negative
+                    } else {
+                        Polarity::Positive
+                    };
+                    match polarities.get_mut(v) {
+                        Some(p) => *p = p.merge(polarity),
+                        None    => {
+                            let _ = polarities.insert(v.clone(), polarity);
+                        },
+                    }
+                },
+                Not(p)    => stack.push((p, !negated)),
+                Or(a, b)  |
+                And(a, b) => {
+                    stack.push((b, negated));
+                    stack.push((a, negated));
+                },
+            }
+        }
+    }
+    polarities
+}
+
+/// Returns a literal operand of the given disjunctive clause whose variable
+/// has a single polarity, along with the value of the variable which satisfies
+/// the clause.
+pub (in crate) fn pure_literal<'a, V>(
+    clause: &'a Expr<V>,
+    polarities: &HashMap<V, Polarity>)
+    -> Option<(&'a V, bool)>
+    where V: Eq + Hash
+{
+    if let Expr::And(..) = clause { return None; }
+    clause.operands()
+        .into_iter()
+        .filter_map(literal)
+        .find(|(v, _)| polarities.get(*v) != Some(&Polarity::Both))
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Absorption
+////////////////////////////////////////////////////////////////////////////////
+/// Removes every disjunctive term whose conjuncts are a superset of the
+/// conjuncts of another term. Of any terms with the same conjuncts, only the
+/// first is kept.
+pub (in crate) fn absorb_terms<V>(terms: Vec<Expr<V>>) -> Vec<Expr<V>>
+    where V: PartialEq
+{
+    use Expr::*;
+    let keep: Vec<bool> = {
+        let conjuncts: Vec<Vec<&Expr<V>>> = terms.iter()
+            .map(|term| match term {
+                And(..) => term.operands(),
+                _       => vec![term],
+            })
+            .collect();
+        let subset = |i: usize, j: usize| conjuncts[i]
+            .iter()
+            .all(|x| conjuncts[j].contains(x));
+
+        (0..terms.len())
+            .map(|i| !(0..terms.len()).any(|j| j != i
+                && subset(j, i)
+                && (j < i || !subset(i, j))))
+            .collect()
+    };
+
+    #[cfg(all(feature = "verify-transforms", debug_assertions))]
+    {
+        let before: Vec<_> = terms.iter().collect();
+        let after: Vec<_> = terms.iter()
+            .zip(&keep)
+            .filter_map(|(term, keep)| if *keep { Some(term) } else { None })
+            .collect();
+        crate::truth::verify_removal("absorption", &before, &after, false);
+    }
+
+    terms.into_iter()
+        .zip(keep)
+        .filter_map(|(term, keep)| if keep { Some(term) } else { None })
+        .collect()
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Redundancy
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the conjunctive clauses which are implied by the conjunction of
+/// the other clauses, in their original order.
+pub (in crate) fn redundant_clauses<'a, V>(clauses: &[&'a Expr<V>])
+    -> Vec<&'a Expr<V>>
+    where V: PartialEq
+{
+    (0..clauses.len())
+        .filter(|&i| {
+            // The rest implies the clause exactly when adding the clause to
+            // the rest does not change its value.
+            let rest: Vec<_> = clauses.iter()
+                .enumerate()
+                .filter_map(|(j, c)| if j != i { Some(*c) } else { None })
+                .collect();
+            clauses_equivalent(&rest, true, clauses, true)
+        })
+        .map(|i| clauses[i])
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Batch evaluation
+////////////////////////////////////////////////////////////////////////////////
+/// Evaluates the clauses on each of the contexts, one clause at a time across
+/// all contexts. The clauses are conjoined if `conjunctive` is true, and
+/// disjoined otherwise.
+pub (in crate) fn eval_clauses_many<'a, 'c, V, I, J>(
+    clauses: I,
+    conjunctive: bool,
+    ctxs: J)
+    -> Vec<bool>
+    where
+        V: Eval + 'a,
+        V::Context: 'c,
+        I: IntoIterator<Item=&'a Expr<V>>,
+        J: IntoIterator<Item=&'c V::Context>,
+{
+    let ctxs: Vec<&V::Context> = ctxs.into_iter().collect();
+    let mut results = vec![conjunctive; ctxs.len()];
+    // The indices of the contexts whose results are not yet decided.
+    let mut pending: Vec<usize> = (0..ctxs.len()).collect();
+    for clause in clauses {
+        if pending.is_empty() { break; }
+        pending.retain(|&i| if clause.eval(ctxs[i]) == conjunctive {
+            true
+        } else {
+            results[i] = !conjunctive;
+            false
+        });
+    }
+    results
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Canonical ordering
+////////////////////////////////////////////////////////////////////////////////
+/// The sorted literals of a clause, or `None` if it is not a clause of
+/// literals.
+type ClauseKey<V> = Option<Vec<(V, bool)>>;
+
+/// Sorts the literals of each clause by variable, with unnegated literals
+/// first, and then sorts the clauses by their sorted literals. Clauses which
+/// are not disjunctions (or conjunctions, if `conjunctive` is false) of
+/// literals are left unchanged and placed after the others, in their original
+/// order.
+pub (in crate) fn sort_clauses_canonical<V>(
+    clauses: &mut Vec<Expr<V>>,
+    conjunctive: bool)
+    where V: Ord + Clone
+{
+    let mut keyed: Vec<(ClauseKey<V>, Expr<V>)> = clauses
+        .drain(..)
+        .map(|clause| {
+            let key = clause_literals(&clause, conjunctive)
+                .map(|mut literals| {
+                    literals.sort_by(|(a, pa), (b, pb)|
+                        a.cmp(b).then(pb.cmp(pa)));
+                    literals.into_iter()
+                        .map(|(var, polarity)| (var.clone(), polarity))
+                        .collect::<Vec<_>>()
+                });
+            (key, clause)
+        })
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => a.iter()
+            .map(|(v, p)| (v, !p))
+            .cmp(b.iter().map(|(v, p)| (v, !p))),
+        (Some(_), None)    => std::cmp::Ordering::Less,
+        (None, Some(_))    => std::cmp::Ordering::Greater,
+        (None, None)       => std::cmp::Ordering::Equal,
+    });
+    clauses.extend(keyed.into_iter().map(|(key, clause)| match key {
+        Some(literals) => {
+            let mut literals = literals.into_iter().map(|(var, polarity)| {
+                if polarity { Expr::Var(var) } else { !Expr::Var(var) }
+            });
+            let first = literals.next().expect("clause has literals");
+            literals.fold(first, |acc, lit| if conjunctive {
+                acc.or(lit)
+            } else {
+                acc.and(lit)
+            })
+        },
+        None => clause,
+    }));
+}
+
+/// Returns the literals of a clause, or `None` if the clause is not a
+/// disjunction (or conjunction, if `conjunctive` is false) of literals.
+fn clause_literals<V>(clause: &Expr<V>, conjunctive: bool)
+    -> Option<Vec<(&V, bool)>>
+{
+    use Expr::*;
+    let mut literals = Vec::new();
+    let mut stack = vec![clause];
+    while let Some(expr) = stack.pop() {
+        match (expr, conjunctive) {
+            (Or(a, b), true) | (And(a, b), false) => {
+                stack.push(b);
+                stack.push(a);
+            },
+            _ => literals.push(literal(expr)?),
+        }
+    }
+    Some(literals)
+}
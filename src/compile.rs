@@ -0,0 +1,373 @@
+
+// Internal library imports.
+use crate::CnfVec;
+use crate::ComplexityLimit;
+use crate::Eval;
+use crate::Expr;
+use crate::LimitViolation;
+use crate::ParseError;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Deserialize;
+#[cfg(feature = "serde")] use serde::Deserializer;
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Serializer;
+
+// Standard library imports
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CompileOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options for compiling a predicate with [`Predicate::compile`].
+///
+/// [`Predicate::compile`]: crate::Predicate::compile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CompileOptions {
+    /// The limits the parsed expression must satisfy.
+    pub limit: ComplexityLimit,
+    /// Whether to convert the simplified expression into conjunctive normal
+    /// form.
+    pub cnf: bool,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CompileError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that a predicate could not be compiled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CompileError {
+    /// The source could not be parsed.
+    Parse(ParseError),
+    /// The parsed expression exceeds the configured limits.
+    Limit(LimitViolation),
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::Parse(e) => write!(f, "parse error: {}", e),
+            CompileError::Limit(e) => write!(f, "limit exceeded: {}", e),
+        }
+    }
+}
+
+impl Error for CompileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CompileError::Parse(e) => Some(e),
+            CompileError::Limit(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseError> for CompileError {
+    fn from(e: ParseError) -> Self {
+        CompileError::Parse(e)
+    }
+}
+
+impl From<LimitViolation> for CompileError {
+    fn from(e: LimitViolation) -> Self {
+        CompileError::Limit(e)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CompiledPredicate
+////////////////////////////////////////////////////////////////////////////////
+/// A predicate which has been parsed, validated, and simplified by
+/// [`Predicate::compile`].
+///
+/// With the "serde" feature, a `CompiledPredicate` can be serialized along
+/// with a format version, so that it can be cached and later deserialized
+/// without compiling it again. Deserialization fails if the version does not
+/// match [`CompiledPredicate::FORMAT_VERSION`].
+///
+/// [`Predicate::compile`]: crate::Predicate::compile
+/// [`CompiledPredicate::FORMAT_VERSION`]: crate::CompiledPredicate::FORMAT_VERSION
+#[derive(Debug, Clone)]
+pub struct CompiledPredicate<V> where V: Eval + PartialEq {
+    /// The simplified expression, in negation normal form.
+    expr: Expr<V>,
+    /// The conjunctive normal form of the expression, if requested.
+    cnf: Option<CnfVec<V>>,
+}
+
+impl<V> CompiledPredicate<V> where V: Eval + PartialEq {
+    /// The version of the serialized form of compiled predicates. It is
+    /// incremented whenever compiled predicates serialized by earlier
+    /// versions can no longer be used.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Returns the simplified expression, in negation normal form.
+    pub fn expr(&self) -> &Expr<V> {
+        &self.expr
+    }
+
+    /// Returns the conjunctive normal form of the expression, if it was
+    /// requested in the [`CompileOptions`].
+    ///
+    /// [`CompileOptions`]: crate::CompileOptions
+    pub fn cnf(&self) -> Option<&CnfVec<V>> {
+        self.cnf.as_ref()
+    }
+
+    /// Returns the simplified expression, in negation normal form.
+    pub fn into_expr(self) -> Expr<V> {
+        self.expr
+    }
+}
+
+impl<V> Eval for CompiledPredicate<V> where V: Eval + PartialEq {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        match &self.cnf {
+            Some(cnf) => cnf.eval(data),
+            None      => self.expr.eval(data),
+        }
+    }
+}
+
+
+/// The serialized form of a [`CompiledPredicate`].
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct CompiledPredicateRef<'a, V> {
+    version: u32,
+    expr: &'a Expr<V>,
+    cnf: Option<&'a Vec<Expr<V>>>,
+}
+
+/// The deserialized form of a [`CompiledPredicate`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct CompiledPredicateData<V> {
+    version: u32,
+    expr: Expr<V>,
+    cnf: Option<Vec<Expr<V>>>,
+}
+
+#[cfg(feature = "serde")]
+impl<V> Serialize for CompiledPredicate<V>
+    where V: Eval + PartialEq + Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        CompiledPredicateRef {
+            version: Self::FORMAT_VERSION,
+            expr: &self.expr,
+            cnf: self.cnf.as_ref().map(CnfVec::as_ref),
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V> Deserialize<'de> for CompiledPredicate<V>
+    where V: Eval + PartialEq + Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        let data = CompiledPredicateData::<V>::deserialize(deserializer)?;
+        if data.version != Self::FORMAT_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported compiled predicate version {} (expected {})",
+                data.version,
+                Self::FORMAT_VERSION)));
+        }
+        Ok(CompiledPredicate {
+            expr: data.expr,
+            cnf: data.cnf.map(CnfVec::from),
+        })
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SharedPredicate
+////////////////////////////////////////////////////////////////////////////////
+/// A [`CompiledPredicate`] which can be shared between threads.
+///
+/// Cloning a `SharedPredicate` only increments a reference count, and it is
+/// always `Send` and `Sync`, so it can be handed to any number of worker
+/// threads:
+///
+/// ```rust
+/// use simple_predicates::Expr;
+/// use simple_predicates::SharedPredicate;
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+///
+/// let predicate = SharedPredicate::from(
+///     Expr::Var(Contains(1)).and(!Expr::Var(Contains(2))));
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let predicate = predicate.clone();
+///         std::thread::spawn(move || predicate.eval(&vec![1, i]))
+///     })
+///     .collect();
+/// let results: Vec<bool> = workers
+///     .into_iter()
+///     .map(|w| w.join().unwrap())
+///     .collect();
+///
+/// assert_eq!(results, vec![true, true, false, true]);
+/// ```
+///
+/// [`CompiledPredicate`]: crate::CompiledPredicate
+#[derive(Debug)]
+pub struct SharedPredicate<V>(Arc<CompiledPredicate<V>>)
+    where V: Eval + PartialEq + Send + Sync;
+
+impl<V> SharedPredicate<V> where V: Eval + PartialEq + Send + Sync {
+    /// Constructs a new `SharedPredicate` from a compiled predicate.
+    pub fn new(predicate: CompiledPredicate<V>) -> Self {
+        SharedPredicate(Arc::new(predicate))
+    }
+
+    /// Returns the shared compiled predicate.
+    pub fn predicate(&self) -> &CompiledPredicate<V> {
+        &self.0
+    }
+
+    /// Returns the simplified expression, in negation normal form.
+    pub fn expr(&self) -> &Expr<V> {
+        self.0.expr()
+    }
+
+    /// Returns the conjunctive normal form of the expression, if it was
+    /// compiled.
+    pub fn cnf(&self) -> Option<&CnfVec<V>> {
+        self.0.cnf()
+    }
+}
+
+impl<V> Eval for SharedPredicate<V> where V: Eval + PartialEq + Send + Sync {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        self.0.eval(data)
+    }
+}
+
+impl<V> Clone for SharedPredicate<V> where V: Eval + PartialEq + Send + Sync {
+    fn clone(&self) -> Self {
+        SharedPredicate(self.0.clone())
+    }
+}
+
+impl<V> From<CompiledPredicate<V>> for SharedPredicate<V>
+    where V: Eval + PartialEq + Send + Sync
+{
+    fn from(predicate: CompiledPredicate<V>) -> Self {
+        SharedPredicate::new(predicate)
+    }
+}
+
+impl<V> From<Expr<V>> for SharedPredicate<V>
+    where V: Eval + PartialEq + Clone + Send + Sync
+{
+    /// Simplifies the expression and shares it, without converting it into
+    /// conjunctive normal form.
+    fn from(expr: Expr<V>) -> Self {
+        SharedPredicate::new(CompiledPredicate {
+            expr: expr.simplify(),
+            cnf: None,
+        })
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Predicate
+////////////////////////////////////////////////////////////////////////////////
+/// The entry point for compiling predicates from text.
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// # impl std::str::FromStr for Contains {
+/// #     type Err = std::num::ParseIntError;
+/// #     fn from_str(s: &str) -> Result<Self, Self::Err> {
+/// #         s.parse().map(Contains)
+/// #     }
+/// # }
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # //-------------------------------------------------------------------
+/// use simple_predicates::CompileOptions;
+/// use simple_predicates::ComplexityLimit;
+/// use simple_predicates::Predicate;
+///
+/// let options = CompileOptions {
+///     limit: ComplexityLimit { max_nodes: Some(100), ..Default::default() },
+///     cnf: true,
+/// };
+/// let predicate = Predicate::compile::<Contains>("4 & !(5 | 6)", &options)?;
+///
+/// assert!(predicate.eval(&vec![1, 2, 4]));
+/// assert!(!predicate.eval(&vec![4, 6]));
+/// # //-------------------------------------------------------------------
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Predicate;
+
+impl Predicate {
+    /// Parses an expression with [`Expr::parse`], checks it against the
+    /// limits of the options, and simplifies it, converting it into
+    /// conjunctive normal form if the options request it.
+    ///
+    /// If the options limit the depth, the source is parsed with
+    /// [`Expr::parse_with_max_depth`], so input which is too deep is rejected
+    /// with a parse error before it is built.
+    ///
+    /// [`Expr::parse`]: crate::Expr::parse
+    /// [`Expr::parse_with_max_depth`]: crate::Expr::parse_with_max_depth
+    pub fn compile<V>(src: &str, options: &CompileOptions)
+        -> Result<CompiledPredicate<V>, CompileError>
+        where
+            V: Eval + FromStr + Eq + Hash + Clone,
+            V::Err: Display,
+    {
+        let expr = match options.limit.max_depth {
+            Some(max_depth) => Expr::<V>::parse_with_max_depth(src, max_depth)?,
+            None            => Expr::<V>::parse(src)?,
+        };
+        expr.check_limits(&options.limit)?;
+        let expr = expr.simplify();
+        let cnf = if options.cnf {
+            Some(CnfVec::from(expr.clone()))
+        } else {
+            None
+        };
+        Ok(CompiledPredicate { expr, cnf })
+    }
+}
@@ -0,0 +1,226 @@
+
+// Internal library imports.
+use crate::ClauseError;
+use crate::CnfVec;
+use crate::Eval;
+use crate::clausal::check_clauses;
+use crate::clausal::literal;
+use crate::fasthash::FastHashMap;
+
+// Standard library imports
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Propagation
+////////////////////////////////////////////////////////////////////////////////
+/// The clauses of a [`CountedCnf`] affected by a variable assignment.
+///
+/// [`CountedCnf`]: crate::CountedCnf
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Propagation {
+    /// The indices of the clauses left with a single unassigned literal, all
+    /// of their other literals being false.
+    pub units: Vec<usize>,
+    /// The indices of the clauses whose literals are now all false.
+    pub conflicts: Vec<usize>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CountedCnf
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression in [Conjunctive Normal Form] supporting incremental
+/// evaluation under a changing partial assignment of its variables.
+///
+/// The number of true and false literals of each clause is kept up to date, so
+/// assigning or unassigning a variable visits every clause containing it, and
+/// the value of the expression is known without visiting any clause.
+///
+/// Counters are used rather than two watched literals per clause because
+/// variables may be unassigned in any order. Lazy watches are only valid when
+/// assignments are undone in reverse order, and counters also give the value
+/// of the expression at every step.
+///
+/// [Conjunctive Normal Form]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
+#[derive(Debug, Clone)]
+pub struct CountedCnf<V> {
+    /// The variables, indexed by their id.
+    vars: Vec<V>,
+    /// The id of each variable.
+    ids: FastHashMap<V, usize>,
+    /// The literals of each clause, given as a variable id and polarity.
+    clauses: Vec<Vec<(usize, bool)>>,
+    /// The clauses containing each variable.
+    occurrences: Vec<Vec<usize>>,
+    /// The current value of each variable.
+    values: Vec<Option<bool>>,
+    /// The number of true literals of each clause.
+    true_counts: Vec<usize>,
+    /// The number of false literals of each clause.
+    false_counts: Vec<usize>,
+    /// The number of clauses with a true literal.
+    satisfied: usize,
+    /// The number of clauses with only false literals.
+    falsified: usize,
+}
+
+impl<V> CountedCnf<V> where V: Eq + Hash + Clone {
+    /// Constructs a new `CountedCnf` from the clauses of the given expression,
+    /// with every variable unassigned.
+    ///
+    /// Returns an error if a clause is not a disjunction of literals.
+    pub fn new(cnf: &CnfVec<V>) -> Result<Self, ClauseError>
+        where V: Eval
+    {
+        check_clauses(cnf.as_ref().iter(), true)?;
+
+        let mut vars = Vec::new();
+        let mut ids = FastHashMap::default();
+        let mut clauses = Vec::with_capacity(cnf.as_ref().len());
+        for clause in cnf.as_ref() {
+            let mut literals: Vec<(usize, bool)> = Vec::new();
+            for (var, polarity) in clause.operands()
+                .into_iter()
+                .filter_map(literal)
+            {
+                let id = *ids.entry(var.clone()).or_insert_with(|| {
+                    vars.push(var.clone());
+                    vars.len() - 1
+                });
+                if !literals.contains(&(id, polarity)) {
+                    literals.push((id, polarity));
+                }
+            }
+            clauses.push(literals);
+        }
+
+        let mut occurrences = vec![Vec::new(); vars.len()];
+        for (idx, literals) in clauses.iter().enumerate() {
+            for &(id, _) in literals {
+                if !occurrences[id].contains(&idx) { occurrences[id].push(idx); }
+            }
+        }
+
+        Ok(CountedCnf {
+            values: vec![None; vars.len()],
+            true_counts: vec![0; clauses.len()],
+            false_counts: vec![0; clauses.len()],
+            satisfied: 0,
+            falsified: 0,
+            vars,
+            ids,
+            clauses,
+            occurrences,
+        })
+    }
+
+    /// Returns the value of the expression under the current assignment, or
+    /// `None` if it depends on an unassigned variable.
+    pub fn value(&self) -> Option<bool> {
+        if self.falsified > 0 { return Some(false); }
+        if self.satisfied == self.clauses.len() { return Some(true); }
+        None
+    }
+
+    /// Returns the current value of the given variable, or `None` if it is
+    /// unassigned or does not appear in the expression.
+    pub fn var_value(&self, var: &V) -> Option<bool> {
+        self.ids.get(var).and_then(|&id| self.values[id])
+    }
+
+    /// Returns the variables of the expression, in order of first occurrence.
+    pub (in crate) fn vars(&self) -> &[V] {
+        &self.vars
+    }
+
+    /// Returns the number of clauses of the expression.
+    pub (in crate) fn clause_count(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Returns the unassigned literal of a unit clause, or `None` if the
+    /// clause does not have exactly one unassigned literal and no true
+    /// literals.
+    pub fn unit_literal(&self, clause: usize) -> Option<(&V, bool)> {
+        let literals = &self.clauses[clause];
+        if self.true_counts[clause] > 0
+            || self.false_counts[clause] + 1 != literals.len()
+        {
+            return None;
+        }
+        literals.iter()
+            .find(|(id, _)| self.values[*id].is_none())
+            .map(|&(id, polarity)| (&self.vars[id], polarity))
+    }
+
+    /// Assigns a value to the given variable, returning the clauses which
+    /// became unit or conflicting as a result. Variables not in the expression
+    /// are ignored.
+    pub fn assign(&mut self, var: &V, value: bool) -> Propagation {
+        let id = match self.ids.get(var) {
+            Some(&id) => id,
+            None      => return Propagation::default(),
+        };
+        match self.values[id] {
+            Some(current) if current == value => return Propagation::default(),
+            Some(_) => self.unassign_id(id),
+            None    => (),
+        }
+
+        self.values[id] = Some(value);
+        let mut propagation = Propagation::default();
+        for &clause in &self.occurrences[id] {
+            let mut falsified = false;
+            for &(lit_id, polarity) in &self.clauses[clause] {
+                if lit_id != id { continue; }
+                if polarity == value {
+                    if self.true_counts[clause] == 0 { self.satisfied += 1; }
+                    self.true_counts[clause] += 1;
+                } else {
+                    falsified = true;
+                    self.false_counts[clause] += 1;
+                    if self.false_counts[clause] == self.clauses[clause].len() {
+                        self.falsified += 1;
+                    }
+                }
+            }
+
+            if !falsified || self.true_counts[clause] > 0 { continue; }
+            let open = self.clauses[clause].len() - self.false_counts[clause];
+            match open {
+                0 => propagation.conflicts.push(clause),
+                1 => propagation.units.push(clause),
+                _ => (),
+            }
+        }
+        propagation
+    }
+
+    /// Unassigns the given variable. Variables not in the expression are
+    /// ignored.
+    pub fn unassign(&mut self, var: &V) {
+        if let Some(&id) = self.ids.get(var) {
+            if self.values[id].is_some() { self.unassign_id(id); }
+        }
+    }
+
+    /// Unassigns the assigned variable with the given id.
+    fn unassign_id(&mut self, id: usize) {
+        let value = self.values[id].take().expect("assigned variable");
+        for &clause in &self.occurrences[id] {
+            for &(lit_id, polarity) in &self.clauses[clause] {
+                if lit_id != id { continue; }
+                if polarity == value {
+                    self.true_counts[clause] -= 1;
+                    if self.true_counts[clause] == 0 { self.satisfied -= 1; }
+                } else {
+                    if self.false_counts[clause] == self.clauses[clause].len() {
+                        self.falsified -= 1;
+                    }
+                    self.false_counts[clause] -= 1;
+                }
+            }
+        }
+    }
+}
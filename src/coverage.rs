@@ -0,0 +1,100 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ClauseCoverage
+////////////////////////////////////////////////////////////////////////////////
+/// The evaluation counts of a single clause of a conjunctive normal form over
+/// a corpus of contexts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClauseCoverage<'a, V> {
+    /// The clause.
+    pub clause: &'a Expr<V>,
+    /// The number of contexts on which the clause was true.
+    pub satisfied: usize,
+    /// The number of contexts on which the clause was false.
+    pub rejected: usize,
+    /// The number of contexts on which the clause was the only false clause,
+    /// so that removing it would change the result.
+    pub sole_rejections: usize,
+}
+
+impl<'a, V> ClauseCoverage<'a, V> {
+    /// Returns true if the clause was true on every context, so that removing
+    /// it would not change the result on any of them.
+    pub fn never_mattered(&self) -> bool {
+        self.rejected == 0
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CoverageReport
+////////////////////////////////////////////////////////////////////////////////
+/// The evaluation counts of each clause of a conjunctive normal form over a
+/// corpus of contexts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport<'a, V> {
+    /// The number of contexts evaluated.
+    pub contexts: usize,
+    /// The number of contexts on which the expression was false.
+    pub rejected: usize,
+    /// The coverage of each clause, in the order of the clauses of the
+    /// expression.
+    pub clauses: Vec<ClauseCoverage<'a, V>>,
+}
+
+impl<'a, V> CoverageReport<'a, V> {
+    /// Returns the clauses which were true on every context.
+    pub fn never_mattered(&self) -> impl Iterator<Item=&'a Expr<V>> + '_ {
+        self.clauses.iter()
+            .filter(|coverage| coverage.never_mattered())
+            .map(|coverage| coverage.clause)
+    }
+}
+
+/// Evaluates every conjoined clause on every context, counting the contexts
+/// each clause satisfies and rejects.
+pub (in crate) fn clause_coverage<'a, 'c, V, I, J>(clauses: I, ctxs: J)
+    -> CoverageReport<'a, V>
+    where
+        V: Eval + 'a,
+        V::Context: 'c,
+        I: IntoIterator<Item=&'a Expr<V>>,
+        J: IntoIterator<Item=&'c V::Context>,
+{
+    let mut report = CoverageReport {
+        contexts: 0,
+        rejected: 0,
+        clauses: clauses.into_iter()
+            .map(|clause| ClauseCoverage {
+                clause,
+                satisfied: 0,
+                rejected: 0,
+                sole_rejections: 0,
+            })
+            .collect(),
+    };
+
+    let mut rejecting = Vec::new();
+    for ctx in ctxs {
+        report.contexts += 1;
+        rejecting.clear();
+        for (idx, coverage) in report.clauses.iter_mut().enumerate() {
+            if coverage.clause.eval(ctx) {
+                coverage.satisfied += 1;
+            } else {
+                coverage.rejected += 1;
+                rejecting.push(idx);
+            }
+        }
+        if !rejecting.is_empty() { report.rejected += 1; }
+        if let [idx] = rejecting[..] {
+            report.clauses[idx].sole_rejections += 1;
+        }
+    }
+    report
+}
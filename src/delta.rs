@@ -0,0 +1,155 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashSet;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Delta
+////////////////////////////////////////////////////////////////////////////////
+/// The result of re-evaluating an expression after a change of context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Delta {
+    /// The result is decided by unchanged variables, so it is the same in
+    /// both contexts. No changed variables were evaluated.
+    Unaffected(bool),
+    /// The result depends on the changed variables, and was evaluated in both
+    /// contexts.
+    Reevaluated {
+        /// The result in the old context.
+        old: bool,
+        /// The result in the new context.
+        new: bool,
+    },
+}
+
+impl Delta {
+    /// Returns the result in the new context.
+    pub fn value(&self) -> bool {
+        match self {
+            Delta::Unaffected(value)       => *value,
+            Delta::Reevaluated { new, .. } => *new,
+        }
+    }
+
+    /// Returns true if the result differs between the contexts.
+    pub fn is_changed(&self) -> bool {
+        match self {
+            Delta::Unaffected(_)            => false,
+            Delta::Reevaluated { old, new } => old != new,
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Delta evaluation
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eval + Eq + Hash {
+    /// Re-evaluates the expression after the context changes from `old` to
+    /// `new`, where only the variables in `changed` may have different values
+    /// in the two contexts.
+    ///
+    /// The unchanged variables are evaluated first, in the new context. If
+    /// they decide the result, the changed variables are never evaluated and
+    /// [`Delta::Unaffected`] is returned. Otherwise the expression is
+    /// evaluated in both contexts.
+    ///
+    /// [`Delta::Unaffected`]: crate::Delta::Unaffected
+    pub fn eval_delta(
+        &self,
+        old: &V::Context,
+        new: &V::Context,
+        changed: &HashSet<V>)
+        -> Delta
+    {
+        let unchanged = self.bounds(|var| if changed.contains(var) {
+            None
+        } else {
+            Some(var.eval(new))
+        });
+        match unchanged {
+            Some(value) => Delta::Unaffected(value),
+            None => Delta::Reevaluated {
+                old: self.eval(old),
+                new: self.eval(new),
+            },
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Three-valued evaluation
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> {
+    /// Evaluates the expression where the value of each variable may be
+    /// unknown, given as `None`, using three-valued (Kleene) logic: `None` is
+    /// propagated through each operator unless the known operands decide its
+    /// result.
+    ///
+    /// A result of `Some` is the value of the expression for every value of
+    /// the unknown variables. `None` is returned whenever an operator cannot
+    /// be decided from its own operands, even if the expression as a whole is
+    /// a tautology or a contradiction, so `1 | !1` is `None` if `1` is
+    /// unknown.
+    ///
+    /// Operands are evaluated from left to right, and the right operand of an
+    /// `And` or `Or` expression is skipped when the left operand decides the
+    /// result, so `f` is only called for the variables which are needed.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(3));
+    /// let known = |values: &'static [(u32, bool)]| move |var: &u32| values
+    ///     .iter()
+    ///     .find(|(v, _)| v == var)
+    ///     .map(|(_, value)| *value);
+    ///
+    /// assert_eq!(expr.bounds(known(&[(1, false)])), Some(false));
+    /// assert_eq!(expr.bounds(known(&[(1, true)])), None);
+    /// assert_eq!(expr.bounds(known(&[(1, true), (3, true)])), Some(true));
+    ///
+    /// let tautology = Expr::from(1).or(!Expr::from(1));
+    /// assert_eq!(tautology.bounds(known(&[])), None);
+    /// ```
+    pub fn bounds<F>(&self, f: F) -> Option<bool>
+        where F: Fn(&V) -> Option<bool>
+    {
+        self.bounds_inner(&f)
+    }
+
+    /// Evaluates the bounds. This method exists to prevent instantiating
+    /// infinite types if a closure is passed to `bounds`.
+    fn bounds_inner<F>(&self, f: &F) -> Option<bool>
+        where F: Fn(&V) -> Option<bool>
+    {
+        use Expr::*;
+        guard(|| match self {
+            Var(v)    => f(v),
+            Not(p)    => p.bounds_inner(f).map(|v| !v),
+            Or(a, b)  => match a.bounds_inner(f) {
+                Some(true) => Some(true),
+                a          => match (a, b.bounds_inner(f)) {
+                    (_, Some(true))            => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _                          => None,
+                },
+            },
+            And(a, b) => match a.bounds_inner(f) {
+                Some(false) => Some(false),
+                a           => match (a, b.bounds_inner(f)) {
+                    (_, Some(false))         => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _                        => None,
+                },
+            },
+        })
+    }
+}
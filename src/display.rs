@@ -0,0 +1,294 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ExprDisplay
+////////////////////////////////////////////////////////////////////////////////
+/// Displays an [`Expr`] using the syntax accepted by [`Expr::parse`].
+///
+/// Chains of `And` and `Or` expressions are written without inner
+/// parentheses, and parentheses are only added where required by the
+/// precedence of the operators, with `!` binding tighter than `&`, which
+/// binds tighter than `|`.
+///
+/// [`Expr`]: crate::Expr
+/// [`Expr::parse`]: crate::Expr::parse
+#[derive(Debug, Clone)]
+pub struct ExprDisplay<'a, V> {
+    /// The expression to display.
+    expr: &'a Expr<V>,
+    /// Whether to apply De Morgan's laws to gather negated operands.
+    de_morgan: bool,
+}
+
+impl<'a, V> ExprDisplay<'a, V> {
+    /// Sets whether negated operands of a chain of `And` (or `Or`)
+    /// expressions are gathered into a single negated disjunction (or
+    /// conjunction), so `!(a | b) & !c & d` is written as `!(a | b | c) & d`.
+    /// Gathering only applies to chains with at least two negated operands,
+    /// and only when it makes the output strictly shorter, so `!a & !b` is
+    /// written unchanged.
+    pub fn de_morgan(mut self, enabled: bool) -> Self {
+        self.de_morgan = enabled;
+        self
+    }
+}
+
+impl<V> Expr<V> where V: Display {
+    /// Returns a displayable form of the expression.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = (!Expr::from(1).or(2)).and(!Expr::from(3)).and(4);
+    ///
+    /// assert_eq!(expr.display().to_string(), "!(1 | 2) & !3 & 4");
+    /// assert_eq!(expr.display().de_morgan(true).to_string(),
+    ///     "!(1 | 2 | 3) & 4");
+    /// ```
+    pub fn display(&self) -> ExprDisplay<'_, V> {
+        ExprDisplay { expr: self, de_morgan: false }
+    }
+}
+
+impl<'a, V> Display for ExprDisplay<'a, V> where V: Display {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let style = Style {
+            var: &|f: &mut Formatter<'_>, v: &V| write!(f, "{}", v),
+            de_morgan: self.de_morgan,
+        };
+        fmt_expr(f, self.expr, &style)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RedactedDisplay
+////////////////////////////////////////////////////////////////////////////////
+/// Displays an [`Expr`] like [`ExprDisplay`], but writes each variable using
+/// a redaction function instead of its `Display` implementation.
+///
+/// [`Expr`]: crate::Expr
+/// [`ExprDisplay`]: crate::ExprDisplay
+pub struct RedactedDisplay<'a, V, F> {
+    /// The expression to display.
+    expr: &'a Expr<V>,
+    /// The function returning the text to write for each variable.
+    redact: F,
+    /// Whether to apply De Morgan's laws to gather negated operands.
+    de_morgan: bool,
+}
+
+impl<'a, V, F> RedactedDisplay<'a, V, F> {
+    /// Sets whether negated operands are gathered as described by
+    /// [`ExprDisplay::de_morgan`].
+    ///
+    /// [`ExprDisplay::de_morgan`]: crate::ExprDisplay::de_morgan
+    pub fn de_morgan(mut self, enabled: bool) -> Self {
+        self.de_morgan = enabled;
+        self
+    }
+}
+
+impl<V> Expr<V> {
+    /// Returns a displayable form of the expression which writes the text
+    /// returned by `redact` in place of each variable, so that sensitive
+    /// values can be masked while keeping the structure of the expression.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from("alice@example.com").and(!Expr::from("admin"));
+    /// let redacted = expr.display_redacted(|v: &&str| {
+    ///     if v.contains('@') { "<email>".to_string() } else { v.to_string() }
+    /// });
+    ///
+    /// assert_eq!(redacted.to_string(), "<email> & !admin");
+    /// ```
+    pub fn display_redacted<F>(&self, redact: F) -> RedactedDisplay<'_, V, F>
+        where F: Fn(&V) -> String
+    {
+        RedactedDisplay { expr: self, redact, de_morgan: false }
+    }
+}
+
+impl<'a, V, F> Display for RedactedDisplay<'a, V, F>
+    where F: Fn(&V) -> String
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let style = Style {
+            var: &|f: &mut Formatter<'_>, v: &V| {
+                write!(f, "{}", (self.redact)(v))
+            },
+            de_morgan: self.de_morgan,
+        };
+        fmt_expr(f, self.expr, &style)
+    }
+}
+
+impl<'a, V, F> Debug for RedactedDisplay<'a, V, F>
+    where F: Fn(&V) -> String
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // The expression is written redacted so no variable is leaked.
+        f.debug_struct("RedactedDisplay")
+            .field("expr", &format_args!("{}", self))
+            .field("de_morgan", &self.de_morgan)
+            .finish()
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Formatting
+////////////////////////////////////////////////////////////////////////////////
+/// The options for writing an expression.
+struct Style<'s, V> {
+    /// Writes a variable.
+    var: &'s dyn Fn(&mut Formatter<'_>, &V) -> std::fmt::Result,
+    /// Whether to apply De Morgan's laws to gather negated operands.
+    de_morgan: bool,
+}
+
+/// Returns the precedence of the expression's operator.
+fn precedence<V>(expr: &Expr<V>) -> u8 {
+    match expr {
+        Expr::Or(..)  => 1,
+        Expr::And(..) => 2,
+        Expr::Not(..) => 3,
+        Expr::Var(..) => 4,
+    }
+}
+
+/// Writes the expression, parenthesized if its operator has lower precedence
+/// than the given precedence.
+fn fmt_operand<V>(
+    f: &mut Formatter<'_>,
+    expr: &Expr<V>,
+    min_precedence: u8,
+    style: &Style<'_, V>)
+    -> std::fmt::Result
+{
+    if precedence(expr) < min_precedence {
+        write!(f, "(")?;
+        fmt_expr(f, expr, style)?;
+        write!(f, ")")
+    } else {
+        fmt_expr(f, expr, style)
+    }
+}
+
+/// Writes the operands of a chain, separated by the given operator.
+fn fmt_chain<V>(
+    f: &mut Formatter<'_>,
+    operands: &[&Expr<V>],
+    precedence: u8,
+    style: &Style<'_, V>)
+    -> std::fmt::Result
+{
+    let sep = if precedence == 2 { " & " } else { " | " };
+    for (idx, operand) in operands.iter().enumerate() {
+        if idx > 0 { write!(f, "{}", sep)?; }
+        fmt_operand(f, operand, precedence + 1, style)?;
+    }
+    Ok(())
+}
+
+/// Writes the expression.
+fn fmt_expr<V>(f: &mut Formatter<'_>, expr: &Expr<V>, style: &Style<'_, V>)
+    -> std::fmt::Result
+{
+    use Expr::*;
+    guard(|| match expr {
+        Var(v) => (style.var)(f, v),
+        Not(p) => {
+            write!(f, "!")?;
+            fmt_operand(f, p, 3, style)
+        },
+        Or(..) | And(..) => {
+            let chain_precedence = precedence(expr);
+            let operands = expr.operands();
+            let dual_precedence = 3 - chain_precedence;
+            let negated: Vec<&Expr<V>> = operands.iter()
+                .filter_map(|operand| match operand {
+                    Not(p) => Some(&**p),
+                    _      => None,
+                })
+                .collect();
+            if !style.de_morgan || negated.len() < 2 {
+                return fmt_chain(f, &operands, chain_precedence, style);
+            }
+            // Chains of the dual operator merge into the group.
+            let negated: Vec<&Expr<V>> = negated.into_iter()
+                .flat_map(|p| if precedence(p) == dual_precedence {
+                    p.operands()
+                } else {
+                    vec![p]
+                })
+                .collect();
+
+            // The group is only used if it is strictly shorter.
+            let plain = FmtWith(|f: &mut Formatter<'_>| {
+                fmt_chain(f, &operands, chain_precedence, style)
+            }).to_string();
+            let grouped = FmtWith(|f: &mut Formatter<'_>| {
+                fmt_grouped(f, &operands, &negated, chain_precedence, style)
+            }).to_string();
+            if grouped.chars().count() < plain.chars().count() {
+                f.write_str(&grouped)
+            } else {
+                f.write_str(&plain)
+            }
+        },
+    })
+}
+
+/// Writes the operands of a chain with its negated operands written as a
+/// single negated group of the dual operator, at the position of the first of
+/// them.
+fn fmt_grouped<V>(
+    f: &mut Formatter<'_>,
+    operands: &[&Expr<V>],
+    negated: &[&Expr<V>],
+    precedence: u8,
+    style: &Style<'_, V>)
+    -> std::fmt::Result
+{
+    let sep = if precedence == 2 { " & " } else { " | " };
+    let mut grouped = false;
+    let mut first = true;
+    for operand in operands {
+        if let Expr::Not(_) = operand {
+            if grouped { continue; }
+            grouped = true;
+            if !first { write!(f, "{}", sep)?; }
+            write!(f, "!(")?;
+            fmt_chain(f, negated, 3 - precedence, style)?;
+            write!(f, ")")?;
+        } else {
+            if !first { write!(f, "{}", sep)?; }
+            fmt_operand(f, operand, precedence + 1, style)?;
+        }
+        first = false;
+    }
+    Ok(())
+}
+
+/// Displays the output of a formatting function.
+struct FmtWith<F>(F);
+
+impl<F> Display for FmtWith<F>
+    where F: Fn(&mut Formatter<'_>) -> std::fmt::Result
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.0)(f)
+    }
+}
@@ -0,0 +1,214 @@
+
+// Internal library imports.
+use crate::BudgetExceeded;
+use crate::CapacityError;
+use crate::ClauseError;
+use crate::CompileError;
+#[cfg(feature = "boolean_expression")]
+use crate::ConstantExprError;
+use crate::DeadlinePassed;
+use crate::LimitViolation;
+use crate::ParseError;
+use crate::RenameError;
+use crate::RenameErrorText;
+use crate::RuleStoreError;
+use crate::TimedOut;
+
+// Standard library imports
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Error
+////////////////////////////////////////////////////////////////////////////////
+/// A unified error type for the crate.
+///
+/// Every error type of the crate converts into an `Error`, so the `?`
+/// operator can be used to combine fallible operations:
+///
+/// ```rust
+/// use simple_predicates::ComplexityLimit;
+/// use simple_predicates::Error;
+/// use simple_predicates::Expr;
+///
+/// fn load(src: &str) -> Result<Expr<u32>, Error> {
+///     let expr = Expr::parse(src)?;
+///     expr.check_limits(&ComplexityLimit {
+///         max_vars: Some(2),
+///         ..Default::default()
+///     })?;
+///     Ok(expr)
+/// }
+///
+/// assert!(matches!(load("1 &"), Err(Error::Parse(_))));
+/// assert!(matches!(load("1 & 2 & 3"), Err(Error::Limit(_))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// An expression could not be parsed.
+    Parse(ParseError),
+    /// An expression exceeds a complexity limit.
+    Limit(LimitViolation),
+    /// An expression does not fit in a fixed-capacity expression.
+    Capacity(CapacityError),
+    /// A normal form clause is malformed.
+    Clause(ClauseError),
+    /// Renaming variables would merge distinct variables. Contains the
+    /// [`RenameError`] with its variables written as text.
+    ///
+    /// [`RenameError`]: crate::RenameError
+    Rename(RenameErrorText),
+    /// A rule store could not be reloaded.
+    RuleStore(RuleStoreError),
+    /// An evaluation ran out of fuel.
+    Budget(BudgetExceeded),
+    /// An evaluation passed its deadline. Contains the [`TimedOut`] error
+    /// without its residual expression.
+    ///
+    /// [`TimedOut`]: crate::TimedOut
+    Deadline(DeadlinePassed),
+    /// A variable could not be evaluated. Contains the description of the
+    /// variable's error.
+    Eval(String),
+    /// A converted expression is equivalent to a constant.
+    #[cfg(feature = "boolean_expression")]
+    Constant(ConstantExprError),
+    /// An expression could not be read from or written as JSON. Contains the
+    /// description of the `serde_json` error.
+    #[cfg(feature = "json")]
+    Json(String),
+}
+
+impl Error {
+    /// Constructs an [`Error::Eval`] from the error of a fallible variable.
+    ///
+    /// [`Error::Eval`]: crate::Error::Eval
+    pub fn eval<E>(error: E) -> Self where E: Display {
+        Error::Eval(error.to_string())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(e)     => write!(f, "parse error: {}", e),
+            Error::Limit(e)     => write!(f, "limit exceeded: {}", e),
+            Error::Capacity(e)  => write!(f, "capacity exceeded: {}", e),
+            Error::Clause(e)    => write!(f, "malformed clause: {}", e),
+            Error::Rename(e)    => write!(f, "invalid renaming: {}", e),
+            Error::RuleStore(e) => write!(f, "rule store error: {}", e),
+            Error::Budget(e)    => write!(f, "{}", e),
+            Error::Deadline(e)  => write!(f, "{}", e),
+            Error::Eval(msg)    => write!(f, "evaluation error: {}", msg),
+            #[cfg(feature = "boolean_expression")]
+            Error::Constant(e)  => write!(f, "conversion error: {}", e),
+            #[cfg(feature = "json")]
+            Error::Json(msg)    => write!(f, "JSON error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e)     => Some(e),
+            Error::Limit(e)     => Some(e),
+            Error::Capacity(e)  => Some(e),
+            Error::Clause(e)    => Some(e),
+            Error::RuleStore(e) => Some(e),
+            Error::Budget(e)    => Some(e),
+            Error::Rename(e)    => Some(e),
+            Error::Deadline(e)  => Some(e),
+            Error::Eval(_)      => None,
+            #[cfg(feature = "boolean_expression")]
+            Error::Constant(e)  => Some(e),
+            #[cfg(feature = "json")]
+            Error::Json(_)      => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<LimitViolation> for Error {
+    fn from(e: LimitViolation) -> Self {
+        Error::Limit(e)
+    }
+}
+
+impl From<CompileError> for Error {
+    fn from(e: CompileError) -> Self {
+        match e {
+            CompileError::Parse(e) => Error::Parse(e),
+            CompileError::Limit(e) => Error::Limit(e),
+        }
+    }
+}
+
+impl From<CapacityError> for Error {
+    fn from(e: CapacityError) -> Self {
+        Error::Capacity(e)
+    }
+}
+
+impl From<ClauseError> for Error {
+    fn from(e: ClauseError) -> Self {
+        Error::Clause(e)
+    }
+}
+
+impl<V> From<RenameError<V>> for Error where V: Debug {
+    fn from(e: RenameError<V>) -> Self {
+        Error::Rename(e.into())
+    }
+}
+
+impl From<RenameErrorText> for Error {
+    fn from(e: RenameErrorText) -> Self {
+        Error::Rename(e)
+    }
+}
+
+impl From<RuleStoreError> for Error {
+    fn from(e: RuleStoreError) -> Self {
+        Error::RuleStore(e)
+    }
+}
+
+impl From<BudgetExceeded> for Error {
+    fn from(e: BudgetExceeded) -> Self {
+        Error::Budget(e)
+    }
+}
+
+impl From<DeadlinePassed> for Error {
+    fn from(e: DeadlinePassed) -> Self {
+        Error::Deadline(e)
+    }
+}
+
+impl<V> From<TimedOut<V>> for Error {
+    fn from(e: TimedOut<V>) -> Self {
+        Error::Deadline(e.into())
+    }
+}
+
+#[cfg(feature = "boolean_expression")]
+impl From<ConstantExprError> for Error {
+    fn from(e: ConstantExprError) -> Self {
+        Error::Constant(e)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e.to_string())
+    }
+}
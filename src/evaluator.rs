@@ -0,0 +1,186 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+
+// Standard library imports
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Step
+////////////////////////////////////////////////////////////////////////////////
+/// A single step of a step-through evaluation by an [`Evaluator`].
+///
+/// [`Evaluator`]: crate::Evaluator
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step<'a, V> {
+    /// A variable was evaluated.
+    Var {
+        /// The path of the variable in the expression.
+        path: ExprPath,
+        /// The variable.
+        var: &'a V,
+        /// The value of the variable.
+        value: bool,
+    },
+    /// The value of an operator was determined from its operands.
+    Op {
+        /// The path of the operator in the expression.
+        path: ExprPath,
+        /// The operator expression.
+        expr: &'a Expr<V>,
+        /// The value of the operator.
+        value: bool,
+        /// Whether the right operand was skipped by short-circuiting.
+        short_circuit: bool,
+    },
+    /// The evaluation is complete.
+    Done(bool),
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Evaluator
+////////////////////////////////////////////////////////////////////////////////
+/// The progress of evaluating a subexpression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Progress {
+    /// No operands have been evaluated.
+    Start,
+    /// The first operand has been evaluated.
+    Left,
+    /// All operands have been evaluated.
+    Right,
+}
+
+/// A resumable evaluation of an expression, which can be advanced one
+/// variable evaluation or operator at a time.
+///
+/// The value of the most recent step can be replaced with
+/// [`override_value`] before advancing, to explore how the rest of the
+/// evaluation would proceed:
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// use simple_predicates::Evaluator;
+/// use simple_predicates::Expr;
+/// use simple_predicates::Step;
+///
+/// let expr = Expr::Var(Contains(1)).and(Expr::Var(Contains(2)));
+/// let data = vec![2];
+/// let mut evaluator = Evaluator::new(&expr, &data);
+///
+/// assert!(matches!(evaluator.next_step(), Step::Var { value: false, .. }));
+/// evaluator.override_value(true);
+/// assert!(matches!(evaluator.next_step(), Step::Var { value: true, .. }));
+/// assert!(matches!(evaluator.next_step(), Step::Op { value: true, .. }));
+/// assert_eq!(evaluator.next_step(), Step::Done(true));
+/// ```
+///
+/// [`override_value`]: crate::Evaluator::override_value
+pub struct Evaluator<'a, V> where V: Eval {
+    /// The context to evaluate the variables in.
+    data: &'a V::Context,
+    /// The subexpressions being evaluated, innermost last.
+    stack: Vec<(&'a Expr<V>, ExprPath, Progress)>,
+    /// The value of the most recent step.
+    value: Option<bool>,
+}
+
+impl<'a, V> Evaluator<'a, V> where V: Eval {
+    /// Constructs a new `Evaluator` for the expression in the given context.
+    pub fn new(expr: &'a Expr<V>, data: &'a V::Context) -> Self {
+        Evaluator {
+            data,
+            stack: vec![(expr, ExprPath::root(), Progress::Start)],
+            value: None,
+        }
+    }
+
+    /// Advances the evaluation to the next variable evaluation or operator,
+    /// returning [`Step::Done`] once the result is known.
+    ///
+    /// [`Step::Done`]: crate::Step::Done
+    pub fn next_step(&mut self) -> Step<'a, V> {
+        use Expr::*;
+        while let Some((expr, path, progress)) = self.stack.pop() {
+            let last = self.value.unwrap_or(false);
+            match (expr, progress) {
+                (Var(var), _) => {
+                    let value = var.eval(self.data);
+                    self.value = Some(value);
+                    return Step::Var { path, var, value };
+                },
+                (Not(p), Progress::Start) => {
+                    let child = path.child(PathStep::Child);
+                    self.stack.push((expr, path, Progress::Right));
+                    self.stack.push((p, child, Progress::Start));
+                },
+                (Or(a, _), Progress::Start) | (And(a, _), Progress::Start) => {
+                    let child = path.child(PathStep::Left);
+                    self.stack.push((expr, path, Progress::Left));
+                    self.stack.push((a, child, Progress::Start));
+                },
+                (Or(_, b), Progress::Left) | (And(_, b), Progress::Left) => {
+                    // Or short-circuits on true, And on false.
+                    if last == matches!(expr, Or(..)) {
+                        return Step::Op {
+                            path,
+                            expr,
+                            value: last,
+                            short_circuit: true,
+                        };
+                    }
+                    let child = path.child(PathStep::Right);
+                    self.stack.push((expr, path, Progress::Right));
+                    self.stack.push((b, child, Progress::Start));
+                },
+                (_, _) => {
+                    let value = if let Not(_) = expr { !last } else { last };
+                    self.value = Some(value);
+                    return Step::Op { path, expr, value, short_circuit: false };
+                },
+            }
+        }
+        Step::Done(self.value.unwrap_or(false))
+    }
+
+    /// Replaces the value of the most recent step, as seen by the rest of the
+    /// evaluation.
+    pub fn override_value(&mut self, value: bool) {
+        self.value = Some(value);
+    }
+
+    /// Returns the result of the evaluation, if it is complete.
+    pub fn result(&self) -> Option<bool> {
+        if self.stack.is_empty() { self.value } else { None }
+    }
+
+    /// Runs the evaluation to completion, returning the result.
+    pub fn finish(&mut self) -> bool {
+        loop {
+            if let Step::Done(value) = self.next_step() { return value; }
+        }
+    }
+}
+
+impl<'a, V> Debug for Evaluator<'a, V> where V: Eval + Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Evaluator")
+            .field("stack", &self.stack)
+            .field("value", &self.value)
+            .finish()
+    }
+}
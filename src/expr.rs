@@ -1,15 +1,39 @@
 
+// Internal library imports.
+use crate::clausal::has_equal_operands;
+use crate::clausal::is_normal_form;
+use crate::stack::guard;
 
 // External library imports
 #[cfg(feature = "serde")] use serde::Serialize;
 #[cfg(feature = "serde")] use serde::Deserialize;
 
+// Standard library imports
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::ops::Not;
+
 ////////////////////////////////////////////////////////////////////////////////
 // Eval
 ////////////////////////////////////////////////////////////////////////////////
 /// Provides functions for performing boolean expression evaluation in the
 /// context of some provided `Context`.
-pub trait Eval: Clone + PartialEq  {
+///
+/// Evaluation places no other requirements on the implementing type. The
+/// normal form conversions additionally require [`Clone`] and [`PartialEq`]
+/// (or [`Eq`] and [`Hash`] for the `HashSet`-backed forms.)
+///
+/// For contexts which borrow data with a lifetime, see [`EvalBorrowed`].
+///
+/// [`EvalBorrowed`]: crate::EvalBorrowed
+/// [`Clone`]: std::clone::Clone
+/// [`PartialEq`]: std::cmp::PartialEq
+/// [`Eq`]: std::cmp::Eq
+/// [`Hash`]: std::hash::Hash
+pub trait Eval {
     /// The contextual data required to evaluate the expression.
     type Context;
 
@@ -22,7 +46,7 @@ pub trait Eval: Clone + PartialEq  {
 // Expr
 ////////////////////////////////////////////////////////////////////////////////
 /// A boolean expression consisting of boolean operators and variables.
-#[derive(Debug, Clone, Eq, Hash)]
+#[derive(Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expr<V> {
     // NOTE: There is a consideration to add an `Empty` variant. This would make
@@ -60,7 +84,7 @@ impl<V> Expr<V> {
         where F: Fn(V) -> X
     {
         use Expr::*;
-        match self {
+        guard(|| match self {
             Var(v)    => Var(f(v)),
             Not(p)    => Not(Box::new(p.map_inner(f))),
             And(a, b) => And(
@@ -69,24 +93,203 @@ impl<V> Expr<V> {
             Or(a, b)  => Or(
                 Box::new(a.map_inner(f)),
                 Box::new(b.map_inner(f))),
+        })
+    }
+
+    /// Returns the conjunction of the expression with the given expression or
+    /// variable.
+    pub fn and<E>(self, other: E) -> Self
+        where E: Into<Expr<V>>
+    {
+        Expr::And(Box::new(self), Box::new(other.into()))
+    }
+
+    /// Returns the disjunction of the expression with the given expression or
+    /// variable.
+    pub fn or<E>(self, other: E) -> Self
+        where E: Into<Expr<V>>
+    {
+        Expr::Or(Box::new(self), Box::new(other.into()))
+    }
+
+    /// Returns the negated conjunction of the expression with the given
+    /// expression or variable, expanded as `!(self & other)`.
+    pub fn nand<E>(self, other: E) -> Self
+        where E: Into<Expr<V>>
+    {
+        !self.and(other)
+    }
+
+    /// Returns the negated disjunction of the expression with the given
+    /// expression or variable, expanded as `!(self | other)`.
+    pub fn nor<E>(self, other: E) -> Self
+        where E: Into<Expr<V>>
+    {
+        !self.or(other)
+    }
+
+    /// Returns the conjunction of the expression with the given expression, if
+    /// any. This is convenient for applying optional filters.
+    pub fn and_option(self, other: Option<Expr<V>>) -> Self {
+        match other {
+            Some(other) => self.and(other),
+            None        => self,
         }
     }
 
-    // Pushes a `Not` expr below an `And` or `Or` expr, or removes it if it is
-    // above another `Not` expr.
-    pub (in crate) fn pushdown_not(self) -> Self {
+    /// Returns the conjunction of the given expressions, arranged as a balanced
+    /// tree. Returns `None` if the iterator is empty.
+    pub fn conjoin<I>(exprs: I) -> Option<Self>
+        where I: IntoIterator<Item=Expr<V>>
+    {
+        Expr::balanced(exprs.into_iter().collect(), Expr::And)
+    }
+
+    /// Returns the disjunction of the given expressions, arranged as a
+    /// balanced tree. Returns `None` if the iterator is empty.
+    pub fn disjoin<I>(exprs: I) -> Option<Self>
+        where I: IntoIterator<Item=Expr<V>>
+    {
+        Expr::balanced(exprs.into_iter().collect(), Expr::Or)
+    }
+
+    /// Joins adjacent pairs of expressions until one remains.
+    fn balanced<F>(mut exprs: Vec<Expr<V>>, join: F) -> Option<Self>
+        where F: Fn(Box<Expr<V>>, Box<Expr<V>>) -> Expr<V>
+    {
+        while exprs.len() > 1 {
+            let mut joined = Vec::with_capacity(exprs.len().div_ceil(2));
+            let mut iter = exprs.into_iter();
+            while let Some(a) = iter.next() {
+                joined.push(match iter.next() {
+                    Some(b) => join(Box::new(a), Box::new(b)),
+                    None    => a,
+                });
+            }
+            exprs = joined;
+        }
+        exprs.pop()
+    }
+
+    /// Converts the expression into negation normal form, where negations are
+    /// applied only to variables.
+    pub fn into_nnf(self) -> Self {
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        let table = crate::truth::occurrence_table(&self);
+
+        let nnf = self.into_nnf_inner(false);
+
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        crate::truth::verify_occurrence_table(
+            "negation normal form conversion",
+            table,
+            &nnf);
+        nnf
+    }
+
+    /// Converts the expression into negation normal form, negating it if
+    /// `negate` is true. The boxes of the input are reused wherever its
+    /// structure is preserved.
+    fn into_nnf_inner(self, negate: bool) -> Self {
         use Expr::*;
-        if let Not(expr) = self {
-            match *expr {
-                Var(p) => Not(Box::new(Var(p))),
-                Not(p) => p.pushdown_not(),
-                Or(a, b) => And(Box::new(Not(a)), Box::new(Not(b))),
-                And(a, b) => Or(Box::new(Not(a)), Box::new(Not(b))),
+        guard(|| match self {
+            Var(_) if negate => Not(Box::new(self)),
+            Var(_)           => self,
+            Not(p) if !negate && matches!(*p, Var(_)) => Not(p),
+            Not(p)           => p.into_nnf_inner(!negate),
+            And(mut a, mut b) => {
+                *a = (*a).into_nnf_inner(negate);
+                *b = (*b).into_nnf_inner(negate);
+                if negate { Or(a, b) } else { And(a, b) }
+            },
+            Or(mut a, mut b) => {
+                *a = (*a).into_nnf_inner(negate);
+                *b = (*b).into_nnf_inner(negate);
+                if negate { And(a, b) } else { Or(a, b) }
+            },
+        })
+    }
+
+    /// Returns the operands of a chain of `And` or `Or` expressions of the same
+    /// kind as this expression. Any other expression is its own sole operand.
+    pub (in crate) fn operands(&self) -> Vec<&Expr<V>> {
+        use Expr::*;
+        let mut operands = Vec::new();
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match (self, expr) {
+                (And(..), And(a, b)) |
+                (Or(..), Or(a, b))   => {
+                    stack.push(b);
+                    stack.push(a);
+                },
+                _ => operands.push(expr),
             }
-        } else {
-            self
         }
+        operands
     }
+
+    /// Returns the top-level conjuncts of the expression, from left to right.
+    /// Any expression other than an `And` is its own sole conjunct.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(3)).and(4);
+    ///
+    /// assert_eq!(expr.split_top_and(), vec![
+    ///     Expr::from(1),
+    ///     Expr::from(2).or(3),
+    ///     Expr::from(4),
+    /// ]);
+    /// ```
+    pub fn split_top_and(self) -> Vec<Expr<V>> {
+        self.split_top(true)
+    }
+
+    /// Returns the top-level disjuncts of the expression, from left to right.
+    /// Any expression other than an `Or` is its own sole disjunct.
+    pub fn split_top_or(self) -> Vec<Expr<V>> {
+        self.split_top(false)
+    }
+
+    /// Splits a chain of `And` expressions if `conjunctive` is true, or a
+    /// chain of `Or` expressions otherwise.
+    fn split_top(self, conjunctive: bool) -> Vec<Expr<V>> {
+        use Expr::*;
+        let mut operands = Vec::new();
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match (expr, conjunctive) {
+                (And(a, b), true) |
+                (Or(a, b), false) => {
+                    stack.push(*b);
+                    stack.push(*a);
+                },
+                (expr, _) => operands.push(expr),
+            }
+        }
+        operands
+    }
+}
+
+/// Returns the conjunction of the given expressions, if any are provided. This
+/// is convenient for combining optional filter parameters:
+///
+/// ```rust
+/// use simple_predicates::combine_all;
+/// use simple_predicates::Expr;
+///
+/// let filters: Option<Vec<Expr<u32>>> = None;
+/// assert_eq!(combine_all(filters), None);
+///
+/// let filters = Some(vec![Expr::Var(1), Expr::Var(2)]);
+/// assert_eq!(combine_all(filters), Some(Expr::from(1).and(2)));
+/// ```
+pub fn combine_all<I, V>(exprs: Option<I>) -> Option<Expr<V>>
+    where I: IntoIterator<Item=Expr<V>>
+{
+    exprs.and_then(Expr::conjoin)
 }
 
 impl<V> Expr<V> where V: PartialEq {
@@ -95,7 +298,7 @@ impl<V> Expr<V> where V: PartialEq {
     /// are the same and applied to equivalent variables.
     pub fn eq_repr(&self, other: &Self) -> bool {
         use Expr::*;
-        match (self, other) {
+        guard(|| match (self, other) {
             (Var(a), Var(b)) => a == b,
             (Not(a), Not(b)) => a.eq_repr(b),
             (Or(a1, b1), Or(a2, b2)) => {
@@ -107,78 +310,202 @@ impl<V> Expr<V> where V: PartialEq {
                 a2.eq_repr(b2)
             },
             _ => false,
-        }
+        })
+    }
+
+    /// Simplifies the expr by removing double-negations and equal subexprs, and
+    /// converts it into negation normal form.
+    pub (in crate) fn simplify(self) -> Self where V: Clone {
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        let table = crate::truth::truth_table(&self);
+
+        let simplified = self.simplify_inner(false);
+
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        crate::truth::verify_truth_table("simplification", table, &simplified);
+        simplified
     }
 
-    /// Simplifies the expr by removing double-negations and equal subexprs.
-    pub (in crate) fn simplify(self) -> Self {
+    /// Simplifies the expr, negating it if `negate` is true. Negations are
+    /// carried down to the variables rather than applied to each subexpr.
+    ///
+    /// The boxes of the input are reused wherever its structure is preserved,
+    /// so simplifying an already simplified expr allocates nothing.
+    fn simplify_inner(self, negate: bool) -> Self {
         use Expr::*;
-        
-        match self {
-            Not(p) => match *p {
-                Not(q) => q.simplify(),
-                q      => Not(Box::new(q.simplify())),
-            }
-            And(a, b) => {
-                let a = a.simplify();
-                let b = b.simplify();
-                if a == b { a } else { And(Box::new(a), Box::new(b)) }
+        guard(|| match self {
+            Var(_) if negate => Not(Box::new(self)),
+            Var(_)           => self,
+            Not(p) if !negate && matches!(*p, Var(_)) => Not(p),
+            Not(p)           => p.simplify_inner(!negate),
+            And(mut a, mut b) => {
+                *a = (*a).simplify_inner(negate);
+                *b = (*b).simplify_inner(negate);
+                Expr::join_simplified(a, b, !negate)
             },
-            Or(a, b) => {
-                let a = a.simplify();
-                let b = b.simplify();
-                if a == b { a } else { Or(Box::new(a), Box::new(b)) }
+            Or(mut a, mut b) => {
+                *a = (*a).simplify_inner(negate);
+                *b = (*b).simplify_inner(negate);
+                Expr::join_simplified(a, b, negate)
             },
-            _ => self,
-        }
+        })
+    }
+
+    /// Joins simplified operands into a conjunction or disjunction, or returns
+    /// one of them if they are equal.
+    fn join_simplified(a: Box<Self>, b: Box<Self>, conjunction: bool) -> Self {
+        use Expr::*;
+        if a == b { *a }
+        else if conjunction { And(a, b) }
+        else { Or(a, b) }
     }
 }
 
 impl<V> Expr<V> where V: Clone {
-    /// Distributes `And` over `Or`.
-    pub (in crate) fn distribute_and(self) -> Self {
+    /// Splits an expression in negation normal form into the clauses of its
+    /// conjunctive normal form if `conjunctive` is true, or the terms of its
+    /// disjunctive normal form otherwise.
+    ///
+    /// Each popped expression is either split into its operands, kept as a
+    /// clause if none of the operands of its `Or` chain (or `And` chain) is an
+    /// `And` (or `Or`), or has one level of distribution applied. Distributed
+    /// clauses are popped again, so nested operands are distributed until
+    /// every clause is in normal form.
+    ///
+    /// The clauses are produced from left to right: operands are pushed in
+    /// reverse so the left operand is split first, and distributing over
+    /// `q & r` (or `q | r`) produces the clause for `q` before the clause for
+    /// `r`. The distributed operand is the last such operand of the chain, and
+    /// the remaining operands are always the first operand of the new clauses.
+    pub (in crate) fn into_clauses(self, conjunctive: bool) -> Vec<Expr<V>> {
         use Expr::*;
-        if let And(a, b) = self {
-            match (*a, *b) {
-                (p, Or(q, r)) |
-                (Or(q, r), p) => Or(
-                    Box::new(And(Box::new(p.clone()), q)),
-                    Box::new(And(Box::new(p), r))),
-                (a, b) => And(Box::new(a), Box::new(b))
+        let mut clauses = Vec::new();
+        let mut queue = vec![self];
+
+        while let Some(expr) = queue.pop() {
+            match (expr, conjunctive) {
+                (And(a, b), true) |
+                (Or(a, b), false) => {
+                    queue.push(*b);
+                    queue.push(*a);
+                },
+                (expr, _) => {
+                    let distributes = |operand: &&Expr<V>| match operand {
+                        And(..) => conjunctive,
+                        Or(..)  => !conjunctive,
+                        _       => false,
+                    };
+                    if expr.operands().iter().any(distributes) {
+                        distribute(expr.split_top(!conjunctive),
+                            conjunctive,
+                            &mut queue);
+                    } else {
+                        clauses.push(expr);
+                    }
+                },
             }
-        } else {
-            self
         }
+        clauses
     }
+}
 
-    /// Distributes `Or` over `And`.
-    pub (in crate) fn distribute_or(self) -> Self {
-        use Expr::*;
-        if let Or(a, b) = self {
-            match (*a, *b) {
-                (p, And(q, r)) |
-                (And(q, r), p) => And(
-                    Box::new(Or(Box::new(p.clone()), q)),
-                    Box::new(Or(Box::new(p), r))),
-                (a, b) => Or(Box::new(a), Box::new(b))
-            }
+/// Distributes the last of the given disjuncts which is an `And` over the
+/// others if `conjunctive` is true, or the last of the given conjuncts which is
+/// an `Or` over the others otherwise, pushing the two resulting expressions
+/// onto the queue.
+fn distribute<V>(
+    mut operands: Vec<Expr<V>>,
+    conjunctive: bool,
+    queue: &mut Vec<Expr<V>>)
+    where V: Clone
+{
+    use Expr::*;
+    let idx = operands.iter()
+        .rposition(|operand| match operand {
+            And(..) => conjunctive,
+            Or(..)  => !conjunctive,
+            _       => false,
+        })
+        .expect("distributed operand");
+    let (q, r) = match operands.remove(idx) {
+        And(q, r) |
+        Or(q, r)  => (q, r),
+        _ => unreachable!("distributed operand is not a variable or negation"),
+    };
+    let join = |a: Expr<V>, b: Box<Expr<V>>| if conjunctive {
+        Or(Box::new(a), b)
+    } else {
+        And(Box::new(a), b)
+    };
+    let p = operands.into_iter()
+        .reduce(|a, b| join(a, Box::new(b)))
+        .expect("remaining operand");
+    queue.push(join(p.clone(), r));
+    queue.push(join(p, q));
+}
+
+impl<V> Expr<V> where V: Clone + PartialEq {
+    /// Converts the expression into the clauses of its conjunctive normal form
+    /// if `conjunctive` is true, or the terms of its disjunctive normal form
+    /// otherwise.
+    pub (in crate) fn into_normal_form(self, conjunctive: bool) -> Vec<Expr<V>> {
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        let original = self.clone();
+
+        // Input already in the target form only needs to be split, unless
+        // simplification would merge some of its operands.
+        let clauses = if is_normal_form(&self, conjunctive)
+            && !has_equal_operands(&self)
+        {
+            self.into_clauses(conjunctive)
         } else {
-            self
-        }
+            self.simplify().into_clauses(conjunctive)
+        };
+
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        crate::truth::verify_clauses(&original, &clauses, conjunctive);
+        clauses
+    }
+
+    /// Returns true if the expressions are equal after conversion to negation
+    /// normal form, up to the ordering, grouping, and repetition of the
+    /// operands of `And` and `Or` expressions.
+    pub fn nnf_eq(&self, other: &Self) -> bool {
+        nnf_eq_inner(&self.clone().into_nnf(), &other.clone().into_nnf())
     }
 }
 
+/// Compares expressions in negation normal form, treating chains of `And` and
+/// `Or` expressions as sets of operands. Any other expression is compared as a
+/// set of itself, so a chain of a single repeated operand equals that operand.
+fn nnf_eq_inner<V>(a: &Expr<V>, b: &Expr<V>) -> bool where V: PartialEq {
+    use Expr::*;
+    guard(|| match (a, b) {
+        (Var(x), Var(y)) => x == y,
+        (Not(x), Not(y)) => nnf_eq_inner(x, y),
+        (Var(_) | Not(_), Var(_) | Not(_)) |
+        (Or(..), And(..)) |
+        (And(..), Or(..)) => false,
+        _ => {
+            let xs = a.operands();
+            let ys = b.operands();
+            xs.iter().all(|x| ys.iter().any(|y| nnf_eq_inner(x, y))) &&
+            ys.iter().all(|y| xs.iter().any(|x| nnf_eq_inner(x, y)))
+        },
+    })
+}
+
 impl<V> Eval for Expr<V> where V: Eval {
     type Context = V::Context;
 
     fn eval(&self, data: &Self::Context) -> bool {
         use Expr::*;
-        match self {
+        guard(|| match self {
             Var(p) => p.eval(data),
             Not(p) => !p.eval(data),
             Or(a, b) => a.eval(data) || b.eval(data),
             And(a, b) => a.eval(data) && b.eval(data),
-        }
+        })
     }
 }
 
@@ -186,7 +513,7 @@ impl<V> PartialEq for Expr<V> where V: PartialEq {
     fn eq(&self, other: &Self) -> bool {
         use Expr::*;
 
-        match (self, other) {
+        guard(|| match (self, other) {
             (Var(p1),    Var(p2))    => p1 == p2,
             (Not(p1),     Not(p2))     => p1 == p2,
             (Or(a1, b1),  Or(a2, b2))  => 
@@ -194,7 +521,72 @@ impl<V> PartialEq for Expr<V> where V: PartialEq {
             (And(a1, b1), And(a2, b2)) => 
                 (a1 == a2 && b1 == b2) || (a1 == b2 && b1 == a2),
             _ => false,
-        }
+        })
+    }
+}
+
+impl<V> Clone for Expr<V> where V: Clone {
+    fn clone(&self) -> Self {
+        use Expr::*;
+
+        guard(|| match self {
+            Var(p)    => Var(p.clone()),
+            Not(p)    => Not(p.clone()),
+            Or(a, b)  => Or(a.clone(), b.clone()),
+            And(a, b) => And(a.clone(), b.clone()),
+        })
+    }
+}
+
+impl<V> Hash for Expr<V> where V: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(commutative_hash(self));
     }
 }
 
+/// Returns a hash of the expression which does not depend on the order of the
+/// operands of each `Or` and `And`, so that expressions which compare equal
+/// hash equally.
+fn commutative_hash<V>(expr: &Expr<V>) -> u64 where V: Hash {
+    use Expr::*;
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(expr).hash(&mut hasher);
+    guard(|| match expr {
+        Var(p)    => p.hash(&mut hasher),
+        Not(p)    => commutative_hash(p).hash(&mut hasher),
+        Or(a, b)  |
+        And(a, b) => {
+            let (a, b) = (commutative_hash(a), commutative_hash(b));
+            a.min(b).hash(&mut hasher);
+            a.max(b).hash(&mut hasher);
+        },
+    });
+    hasher.finish()
+}
+
+impl<V> Debug for Expr<V> where V: Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use Expr::*;
+
+        guard(|| match self {
+            Var(p)    => f.debug_tuple("Var").field(p).finish(),
+            Not(p)    => f.debug_tuple("Not").field(p).finish(),
+            Or(a, b)  => f.debug_tuple("Or").field(a).field(b).finish(),
+            And(a, b) => f.debug_tuple("And").field(a).field(b).finish(),
+        })
+    }
+}
+
+impl<V> From<V> for Expr<V> {
+    fn from(var: V) -> Self {
+        Expr::Var(var)
+    }
+}
+
+impl<V> Not for Expr<V> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Expr::Not(Box::new(self))
+    }
+}
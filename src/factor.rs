@@ -0,0 +1,147 @@
+
+// Internal library imports.
+use crate::CnfVec;
+use crate::Eval;
+use crate::Expr;
+use crate::TraversalOrder;
+use crate::fasthash::FastHashMap;
+use crate::stack::guard;
+
+// Standard library imports
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Atom
+////////////////////////////////////////////////////////////////////////////////
+/// A variable of a factored clause.
+#[derive(Debug, Clone, PartialEq)]
+enum Atom<V> {
+    /// A variable appearing more than once, given by its index in the shared
+    /// atoms.
+    Shared(usize),
+    /// A variable appearing only once.
+    Local(V),
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FactoredCnf
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression in [Conjunctive Normal Form], evaluated in two layers:
+/// the variables which appear more than once are evaluated at most once per
+/// evaluation, and the clauses then refer to their cached values.
+///
+/// Clauses are evaluated in order and short-circuit as in [`CnfVec`], so a
+/// shared variable is only evaluated when a clause first needs it.
+///
+/// [Conjunctive Normal Form]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
+#[derive(Debug, Clone)]
+pub struct FactoredCnf<V> {
+    /// The variables appearing more than once, in order of first occurrence.
+    shared: Vec<V>,
+    /// The conjunctive clauses.
+    clauses: Vec<Expr<Atom<V>>>,
+}
+
+impl<V> FactoredCnf<V> {
+    /// Returns the variables which are evaluated at most once per evaluation,
+    /// in order of first occurrence.
+    pub fn shared_atoms(&self) -> &[V] {
+        &self.shared
+    }
+
+    /// Returns the number of clauses.
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Returns true if the boolean expression contains no clauses.
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+}
+
+impl<V> FactoredCnf<V> where V: Eval + PartialEq + Clone {
+    /// Returns the clauses as a `CnfVec`.
+    pub fn to_cnf(&self) -> CnfVec<V> {
+        CnfVec::from(self.clauses.iter().map(|clause| clause.clone()
+            .map(|atom| match atom {
+                Atom::Shared(idx) => self.shared[idx].clone(),
+                Atom::Local(var)  => var,
+            })))
+    }
+}
+
+impl<V> Eval for FactoredCnf<V> where V: Eval {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        let mut values = vec![None; self.shared.len()];
+        self.clauses.iter()
+            .all(|clause| eval_factored(clause, &self.shared, &mut values, data))
+    }
+}
+
+/// Evaluates a factored clause, evaluating each shared variable only if its
+/// value is not already in `values`.
+fn eval_factored<V>(
+    expr: &Expr<Atom<V>>,
+    shared: &[V],
+    values: &mut Vec<Option<bool>>,
+    data: &V::Context)
+    -> bool
+    where V: Eval
+{
+    use Expr::*;
+    guard(|| match expr {
+        Var(Atom::Local(var)) => var.eval(data),
+        Var(Atom::Shared(idx)) => match values[*idx] {
+            Some(value) => value,
+            None => {
+                let value = shared[*idx].eval(data);
+                values[*idx] = Some(value);
+                value
+            },
+        },
+        Not(p)    => !eval_factored(p, shared, values, data),
+        Or(a, b)  => eval_factored(a, shared, values, data)
+            || eval_factored(b, shared, values, data),
+        And(a, b) => eval_factored(a, shared, values, data)
+            && eval_factored(b, shared, values, data),
+    })
+}
+
+/// Factors the variables appearing more than once out of the given clauses.
+pub (in crate) fn factor_common<V>(clauses: &[Expr<V>]) -> FactoredCnf<V>
+    where V: Eq + Hash + Clone
+{
+    let occurrences = || clauses.iter()
+        .flat_map(|clause| clause.iter_subexprs(TraversalOrder::PreOrder))
+        .filter_map(|expr| match expr {
+            Expr::Var(var) => Some(var),
+            _              => None,
+        });
+
+    let mut counts: FastHashMap<&V, usize> = FastHashMap::default();
+    for var in occurrences() {
+        *counts.entry(var).or_default() += 1;
+    }
+
+    let mut shared = Vec::new();
+    let mut indices: FastHashMap<&V, usize> = FastHashMap::default();
+    for var in occurrences() {
+        if counts[var] > 1 && !indices.contains_key(var) {
+            let _ = indices.insert(var, shared.len());
+            shared.push(var.clone());
+        }
+    }
+
+    let clauses = clauses.iter()
+        .map(|clause| clause.clone().map(|var| match indices.get(&var) {
+            Some(idx) => Atom::Shared(*idx),
+            None      => Atom::Local(var),
+        }))
+        .collect();
+    FactoredCnf { shared, clauses }
+}
@@ -0,0 +1,105 @@
+
+// Internal library imports.
+use crate::Expr;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// TryEval
+////////////////////////////////////////////////////////////////////////////////
+/// Provides functions for performing fallible boolean expression evaluation in
+/// the context of some provided `Context`.
+pub trait TryEval {
+    /// The contextual data required to evaluate the expression.
+    type Context;
+
+    /// The error produced when the expression cannot be evaluated.
+    type Error;
+
+    /// Evaluates the expression, returning its truth value or an error.
+    fn try_eval(&self, data: &Self::Context) -> Result<bool, Self::Error>;
+}
+
+impl<V> Expr<V> where V: TryEval {
+    /// Evaluates the expression, returning the first error produced by a
+    /// variable. Variables skipped by short-circuiting are not evaluated.
+    pub fn try_eval(&self, data: &V::Context) -> Result<bool, V::Error> {
+        use Expr::*;
+        Ok(match self {
+            Var(p)    => p.try_eval(data)?,
+            Not(p)    => !p.try_eval(data)?,
+            Or(a, b)  => a.try_eval(data)? || b.try_eval(data)?,
+            And(a, b) => a.try_eval(data)? && b.try_eval(data)?,
+        })
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ErrorPolicy
+////////////////////////////////////////////////////////////////////////////////
+/// Determines how an [`OnError`] variable treats evaluation errors.
+///
+/// [`OnError`]: crate::OnError
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ErrorPolicy {
+    /// Errors are treated as if the variable evaluated to false.
+    TreatAsFalse,
+    /// Errors are treated as if the variable evaluated to true.
+    TreatAsTrue,
+    /// Errors are returned to the caller.
+    #[default]
+    Propagate,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// OnError
+////////////////////////////////////////////////////////////////////////////////
+/// A [`TryEval`] variable wrapper which applies an [`ErrorPolicy`] to the
+/// errors of the wrapped variable.
+///
+/// [`TryEval`]: crate::TryEval
+/// [`ErrorPolicy`]: crate::ErrorPolicy
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OnError<V> {
+    /// The wrapped variable.
+    pub var: V,
+    /// The policy applied to errors of the wrapped variable.
+    pub policy: ErrorPolicy,
+}
+
+impl<V> OnError<V> {
+    /// Wraps the variable, treating errors as false.
+    pub fn treat_as_false(var: V) -> Self {
+        OnError { var, policy: ErrorPolicy::TreatAsFalse }
+    }
+
+    /// Wraps the variable, treating errors as true.
+    pub fn treat_as_true(var: V) -> Self {
+        OnError { var, policy: ErrorPolicy::TreatAsTrue }
+    }
+
+    /// Wraps the variable, returning errors to the caller.
+    pub fn propagate(var: V) -> Self {
+        OnError { var, policy: ErrorPolicy::Propagate }
+    }
+}
+
+impl<V> TryEval for OnError<V> where V: TryEval {
+    type Context = V::Context;
+    type Error = V::Error;
+
+    fn try_eval(&self, data: &Self::Context) -> Result<bool, Self::Error> {
+        match (self.var.try_eval(data), self.policy) {
+            (Err(_), ErrorPolicy::TreatAsFalse) => Ok(false),
+            (Err(_), ErrorPolicy::TreatAsTrue)  => Ok(true),
+            (res, _)                            => res,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+
+// NOTE: These aliases are only used for sets and maps which never appear in
+// the public API, so enabling the "fast-hash" feature cannot change the type
+// of anything returned to users.
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal hash collections
+////////////////////////////////////////////////////////////////////////////////
+/// The hash set used internally, which uses the faster non-cryptographic
+/// `FxHash` if the "fast-hash" feature is enabled. Construct with `default()`.
+#[cfg(feature = "fast-hash")]
+pub (in crate) type FastHashSet<T> = rustc_hash::FxHashSet<T>;
+
+/// The hash set used internally, which uses the faster non-cryptographic
+/// `FxHash` if the "fast-hash" feature is enabled. Construct with `default()`.
+#[cfg(not(feature = "fast-hash"))]
+pub (in crate) type FastHashSet<T> = std::collections::HashSet<T>;
+
+/// The hash map used internally, which uses the faster non-cryptographic
+/// `FxHash` if the "fast-hash" feature is enabled. Construct with `default()`.
+#[cfg(feature = "fast-hash")]
+pub (in crate) type FastHashMap<K, V> = rustc_hash::FxHashMap<K, V>;
+
+/// The hash map used internally, which uses the faster non-cryptographic
+/// `FxHash` if the "fast-hash" feature is enabled. Construct with `default()`.
+#[cfg(not(feature = "fast-hash"))]
+pub (in crate) type FastHashMap<K, V> = std::collections::HashMap<K, V>;
@@ -0,0 +1,128 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! C foreign function interface.
+//!
+//! Expressions over string variables are exchanged in their JSON
+//! representation (see [`Expr::from_json`]) and held behind an opaque
+//! [`SpExpr`] handle. The functions here are suitable for generating a C
+//! header with [cbindgen](https://crates.io/crates/cbindgen). To link against
+//! them, build the crate as a C library, e.g.,
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+//!
+//! [`Expr::from_json`]: crate::Expr::from_json
+//! [`SpExpr`]: crate::ffi::SpExpr
+////////////////////////////////////////////////////////////////////////////////
+
+// Internal library imports.
+use crate::Expr;
+
+// Standard library imports
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::os::raw::c_void;
+use std::ptr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SpExpr
+////////////////////////////////////////////////////////////////////////////////
+/// An opaque handle to an expression over string variables.
+#[derive(Debug)]
+pub struct SpExpr(Expr<String>);
+
+/// A nullable callback returning the truth value of the named variable. The
+/// name is a NUL-terminated UTF-8 string which is only valid for the duration
+/// of the call.
+pub type SpVarCallback = Option<extern "C" fn(
+    var: *const c_char,
+    user_data: *mut c_void)
+    -> bool>;
+
+/// Parses an expression from a NUL-terminated JSON string.
+///
+/// Returns a null pointer if the string is not valid UTF-8 or is not a valid
+/// expression. A non-null result must be released with [`sp_expr_free`].
+///
+/// # Safety
+///
+/// `json` must be null or point to a valid NUL-terminated string.
+///
+/// [`sp_expr_free`]: crate::ffi::sp_expr_free
+#[no_mangle]
+pub unsafe extern "C" fn sp_expr_parse(json: *const c_char) -> *mut SpExpr {
+    if json.is_null() { return ptr::null_mut(); }
+
+    let json = match CStr::from_ptr(json).to_str() {
+        Ok(json) => json,
+        Err(_)   => return ptr::null_mut(),
+    };
+    match Expr::from_json(json) {
+        Ok(expr) => Box::into_raw(Box::new(SpExpr(expr))),
+        Err(_)   => ptr::null_mut(),
+    }
+}
+
+/// Evaluates the expression, calling the callback to resolve each variable
+/// reached. The `user_data` pointer is passed through to the callback.
+///
+/// Returns 1 if the expression is true, 0 if it is false, and -1 if the
+/// expression or callback is null or the expression contains a variable name
+/// with an interior NUL.
+///
+/// # Safety
+///
+/// `expr` must be null or a handle returned by [`sp_expr_parse`] which has not
+/// been freed.
+///
+/// [`sp_expr_parse`]: crate::ffi::sp_expr_parse
+#[no_mangle]
+pub unsafe extern "C" fn sp_expr_eval_with_callback(
+    expr: *const SpExpr,
+    callback: SpVarCallback,
+    user_data: *mut c_void)
+    -> c_int
+{
+    let expr = match expr.as_ref() {
+        Some(expr) => &expr.0,
+        None       => return -1,
+    };
+    let callback = match callback {
+        Some(callback) => callback,
+        None           => return -1,
+    };
+
+    let mut failed = false;
+    let result = expr.eval_with_provider(&mut |var: &String| {
+        match CString::new(var.as_bytes()) {
+            Ok(name) => callback(name.as_ptr(), user_data),
+            Err(_)   => { failed = true; false },
+        }
+    });
+
+    match (failed, result) {
+        (true, _)      => -1,
+        (false, true)  => 1,
+        (false, false) => 0,
+    }
+}
+
+/// Releases an expression handle. Passing a null pointer does nothing.
+///
+/// # Safety
+///
+/// `expr` must be null or a handle returned by [`sp_expr_parse`] which has not
+/// already been freed.
+///
+/// [`sp_expr_parse`]: crate::ffi::sp_expr_parse
+#[no_mangle]
+pub unsafe extern "C" fn sp_expr_free(expr: *mut SpExpr) {
+    if !expr.is_null() {
+        drop(Box::from_raw(expr));
+    }
+}
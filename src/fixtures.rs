@@ -0,0 +1,141 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Deterministic expression builders for tests and benchmarks.
+//!
+//! Each builder produces a well-known shape of expression over the variables
+//! `0..n`. The random builders take a seed, and always produce the same
+//! expression from the same arguments on every platform:
+//!
+//! ```rust
+//! use simple_predicates::Expr;
+//! use simple_predicates::fixtures::chain_and;
+//! use simple_predicates::fixtures::random_cnf;
+//!
+//! assert_eq!(chain_and(3), Expr::from(0).and(1).and(2));
+//! assert_eq!(random_cnf(5, 10, 42), random_cnf(5, 10, 42));
+//! ```
+////////////////////////////////////////////////////////////////////////////////
+
+// Internal library imports.
+use crate::Expr;
+use crate::rng::Rng;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Chains
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the conjunction of the variables `0..n`, grouped to the left.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn chain_and(n: u32) -> Expr<u32> {
+    chain(n, Expr::and)
+}
+
+/// Returns the disjunction of the variables `0..n`, grouped to the left.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn chain_or(n: u32) -> Expr<u32> {
+    chain(n, Expr::or)
+}
+
+/// Returns the variables `0..n` joined by alternating `And` and `Or`
+/// operators, grouped to the left, as in `((0 & 1) | 2) & 3`. No two adjacent
+/// operators can be flattened into the same chain.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn alternating(n: u32) -> Expr<u32> {
+    assert!(n > 0, "empty expression");
+    (1..n).fold(Expr::Var(0), |expr, var| if var % 2 == 1 {
+        expr.and(var)
+    } else {
+        expr.or(var)
+    })
+}
+
+/// Returns `depth` negations of the variable `0`.
+pub fn nested_not(depth: usize) -> Expr<u32> {
+    (0..depth).fold(Expr::Var(0), |expr, _| !expr)
+}
+
+/// Joins the variables `0..n` with the given operator.
+fn chain<F>(n: u32, join: F) -> Expr<u32>
+    where F: Fn(Expr<u32>, u32) -> Expr<u32>
+{
+    assert!(n > 0, "empty expression");
+    (1..n).fold(Expr::Var(0), join)
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Random normal forms
+////////////////////////////////////////////////////////////////////////////////
+/// The number of literals in each clause of the random normal forms.
+pub const RANDOM_CLAUSE_WIDTH: u32 = 3;
+
+/// Returns a pseudo-random expression in conjunctive normal form with the
+/// given number of clauses over the variables `0..vars`.
+///
+/// Each clause is a disjunction of [`RANDOM_CLAUSE_WIDTH`] literals of
+/// distinct variables, or of every variable if there are fewer, each negated
+/// with even odds.
+///
+/// # Panics
+///
+/// Panics if `vars` or `clauses` is zero.
+pub fn random_cnf(vars: u32, clauses: usize, seed: u64) -> Expr<u32> {
+    random_normal_form(vars, clauses, seed, Expr::and, Expr::or)
+}
+
+/// Returns a pseudo-random expression in disjunctive normal form with the
+/// given number of terms over the variables `0..vars`. The terms are chosen
+/// like the clauses of [`random_cnf`].
+///
+/// # Panics
+///
+/// Panics if `vars` or `terms` is zero.
+pub fn random_dnf(vars: u32, terms: usize, seed: u64) -> Expr<u32> {
+    random_normal_form(vars, terms, seed, Expr::or, Expr::and)
+}
+
+/// Returns a pseudo-random normal form, joining clauses with `outer` and
+/// their literals with `inner`.
+fn random_normal_form<F, G>(
+    vars: u32,
+    clauses: usize,
+    seed: u64,
+    outer: F,
+    inner: G)
+    -> Expr<u32>
+    where
+        F: Fn(Expr<u32>, Expr<u32>) -> Expr<u32>,
+        G: Fn(Expr<u32>, Expr<u32>) -> Expr<u32>,
+{
+    assert!(vars > 0, "no variables");
+    assert!(clauses > 0, "empty expression");
+    let mut rng = Rng::new(seed);
+    let mut all: Vec<u32> = (0..vars).collect();
+    let mut clause = || {
+        rng.shuffle(&mut all);
+        all.iter()
+            .take(RANDOM_CLAUSE_WIDTH as usize)
+            .map(|&var| if rng.next_u64() & 1 == 1 {
+                !Expr::Var(var)
+            } else {
+                Expr::Var(var)
+            })
+            .reduce(&inner)
+            .expect("nonempty clause")
+    };
+    let first = clause();
+    (1..clauses).fold(first, |expr, _| outer(expr, clause()))
+}
@@ -0,0 +1,453 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::TraversalOrder;
+use crate::stack::guard;
+
+// Standard library imports
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Node
+////////////////////////////////////////////////////////////////////////////////
+/// A node of a flat expression, referring to its operands by their index in
+/// the enclosing sequence of nodes.
+///
+/// The operands of a node always precede it, so the last node of a sequence
+/// is the root of the expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node<V> {
+    /// A boolean variable.
+    Var(V),
+    /// A negation of the node at the given index.
+    Not(usize),
+    /// A disjunction of the nodes at the given indices.
+    Or(usize, usize),
+    /// A conjunction of the nodes at the given indices.
+    And(usize, usize),
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CapacityError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that an expression has more nodes than a fixed-capacity
+/// expression can hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapacityError {
+    /// The number of nodes the fixed-capacity expression can hold.
+    pub capacity: usize,
+    /// The number of nodes in the expression.
+    pub required: usize,
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expression has {} nodes, but capacity is {}",
+            self.required,
+            self.capacity)
+    }
+}
+
+impl Error for CapacityError {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// StackExpr
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression of at most `N` nodes, stored inline in a fixed-size
+/// array.
+///
+/// Converting an [`Expr`] which fits does not allocate, aside from freeing the
+/// boxes of the converted `Expr`.
+///
+/// A `StackExpr` is constructed by converting an [`Expr`] with `TryFrom`:
+///
+/// ```rust
+/// use simple_predicates::Expr;
+/// use simple_predicates::StackExpr;
+/// use std::convert::TryFrom;
+///
+/// let expr = Expr::from(1).and(!Expr::from(2));
+/// let small = StackExpr::<u32, 4>::try_from(expr).unwrap();
+/// assert_eq!(small.len(), 4);
+/// ```
+///
+/// [`Expr`]: crate::Expr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StackExpr<V, const N: usize> {
+    /// The nodes of the expression, in post-order. The slots past `len` are
+    /// never read and hold `Node::Not(0)`.
+    nodes: [Node<V>; N],
+    /// The number of nodes in use.
+    len: usize,
+}
+
+impl<V, const N: usize> StackExpr<V, N> {
+    /// Returns the number of nodes in the expression.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns false. A `StackExpr` always has a root node.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the nodes of the expression. The last node is the root.
+    pub fn nodes(&self) -> impl Iterator<Item=&Node<V>> + '_ {
+        self.nodes[..self.len].iter()
+    }
+
+    /// Returns the node at the given index.
+    fn node(&self, idx: usize) -> &Node<V> {
+        &self.nodes[..self.len][idx]
+    }
+
+    /// Appends the nodes of the given expression in post-order, returning the
+    /// index of its root.
+    ///
+    /// The expression must fit in the remaining capacity.
+    fn push_expr(&mut self, expr: Expr<V>) -> usize {
+        let node = match expr {
+            Expr::Var(v)    => Node::Var(v),
+            Expr::Not(p)    => Node::Not(self.push_expr(*p)),
+            Expr::Or(a, b)  => {
+                let a = self.push_expr(*a);
+                Node::Or(a, self.push_expr(*b))
+            },
+            Expr::And(a, b) => {
+                let a = self.push_expr(*a);
+                Node::And(a, self.push_expr(*b))
+            },
+        };
+        self.nodes[self.len] = node;
+        self.len += 1;
+        self.len - 1
+    }
+
+    /// Evaluates the node at the given index.
+    fn eval_at(&self, idx: usize, data: &V::Context) -> bool where V: Eval {
+        use Node::*;
+        match self.node(idx) {
+            Var(v)    => v.eval(data),
+            Not(a)    => !self.eval_at(*a, data),
+            Or(a, b)  => self.eval_at(*a, data) || self.eval_at(*b, data),
+            And(a, b) => self.eval_at(*a, data) && self.eval_at(*b, data),
+        }
+    }
+
+    /// Rebuilds the expression rooted at the node at the given index.
+    fn expr_at(&self, idx: usize) -> Expr<V> where V: Clone {
+        match self.node(idx) {
+            Node::Var(v)    => Expr::Var(v.clone()),
+            Node::Not(a)    => Expr::Not(Box::new(self.expr_at(*a))),
+            Node::Or(a, b)  => Expr::Or(
+                Box::new(self.expr_at(*a)),
+                Box::new(self.expr_at(*b))),
+            Node::And(a, b) => Expr::And(
+                Box::new(self.expr_at(*a)),
+                Box::new(self.expr_at(*b))),
+        }
+    }
+}
+
+impl<V, const N: usize> StackExpr<V, N> where V: Display {
+    /// Returns a [`SharedDisplay`] of the expression, which labels shared
+    /// nodes instead of expanding them at each use.
+    pub fn display_shared(&self) -> SharedDisplay<'_, V> {
+        SharedDisplay { nodes: self.nodes().collect() }
+    }
+}
+
+impl<V, const N: usize> StackExpr<V, N> where V: Clone {
+    /// Converts the expression into an [`Expr`].
+    ///
+    /// [`Expr`]: crate::Expr
+    pub fn to_expr(&self) -> Expr<V> {
+        self.expr_at(self.len - 1)
+    }
+}
+
+impl<V, const N: usize> Eval for StackExpr<V, N> where V: Eval {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        self.eval_at(self.len - 1, data)
+    }
+}
+
+impl<V, const N: usize> TryFrom<Expr<V>> for StackExpr<V, N> {
+    type Error = CapacityError;
+
+    fn try_from(expr: Expr<V>) -> Result<Self, Self::Error> {
+        let mut remaining = N;
+        if !fits(&expr, &mut remaining) {
+            let required = expr.iter_subexprs(TraversalOrder::PreOrder).count();
+            return Err(CapacityError { capacity: N, required });
+        }
+
+        let mut stack_expr = StackExpr {
+            nodes: std::array::from_fn(|_| Node::Not(0)),
+            len: 0,
+        };
+        let _ = stack_expr.push_expr(expr);
+        Ok(stack_expr)
+    }
+}
+
+/// Returns true if the expression has at most `remaining` nodes, subtracting
+/// the nodes counted from `remaining`.
+///
+/// The count stops as soon as the capacity is exceeded, so this recurses at
+/// most `remaining` levels deep.
+fn fits<V>(expr: &Expr<V>, remaining: &mut usize) -> bool {
+    use Expr::*;
+    if *remaining == 0 { return false; }
+    *remaining -= 1;
+    match expr {
+        Var(_)    => true,
+        Not(p)    => fits(p, remaining),
+        Or(a, b)  |
+        And(a, b) => fits(a, remaining) && fits(b, remaining),
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// StaticExpr
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression backed by a static slice of nodes, which can be
+/// constructed in `const` and `static` items:
+///
+/// ```rust
+/// use simple_predicates::Eval;
+/// use simple_predicates::Node;
+/// use simple_predicates::StaticExpr;
+///
+/// # #[derive(Debug)]
+/// # struct Flag(u8);
+/// # impl Eval for Flag {
+/// #     type Context = u8;
+/// #     fn eval(&self, data: &u8) -> bool { data & self.0 != 0 }
+/// # }
+/// // (Flag(1) & !Flag(2))
+/// static RULE: StaticExpr<Flag> = StaticExpr::new(&[
+///     Node::Var(Flag(1)),
+///     Node::Var(Flag(2)),
+///     Node::Not(1),
+///     Node::And(0, 2),
+/// ]);
+///
+/// assert!(RULE.eval(&0b01));
+/// assert!(!RULE.eval(&0b11));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticExpr<V: 'static> {
+    /// The nodes of the expression, in post-order.
+    nodes: &'static [Node<V>],
+}
+
+impl<V> StaticExpr<V> {
+    /// Constructs a new `StaticExpr` from the given nodes. The last node is
+    /// the root of the expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty or if any node refers to an operand which
+    /// does not precede it. When used to initialize a `const` or `static`
+    /// item, this is reported at compile time.
+    pub const fn new(nodes: &'static [Node<V>]) -> Self {
+        if nodes.is_empty() {
+            panic!("static expression has no nodes");
+        }
+        let mut idx = 0;
+        while idx < nodes.len() {
+            let valid = match &nodes[idx] {
+                Node::Var(_)    => true,
+                Node::Not(a)    => *a < idx,
+                Node::Or(a, b)  |
+                Node::And(a, b) => *a < idx && *b < idx,
+            };
+            if !valid {
+                panic!("static expression node refers to a later node");
+            }
+            idx += 1;
+        }
+        StaticExpr { nodes }
+    }
+
+    /// Returns the nodes of the expression. The last node is the root.
+    pub const fn nodes(&self) -> &'static [Node<V>] {
+        self.nodes
+    }
+
+    /// Returns the number of nodes in the expression.
+    pub const fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns false. A `StaticExpr` always has a root node.
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Evaluates the node at the given index.
+    fn eval_at(&self, idx: usize, data: &V::Context) -> bool where V: Eval {
+        use Node::*;
+        match &self.nodes[idx] {
+            Var(v)    => v.eval(data),
+            Not(a)    => !self.eval_at(*a, data),
+            Or(a, b)  => self.eval_at(*a, data) || self.eval_at(*b, data),
+            And(a, b) => self.eval_at(*a, data) && self.eval_at(*b, data),
+        }
+    }
+
+    /// Rebuilds the expression rooted at the node at the given index.
+    fn expr_at(&self, idx: usize) -> Expr<V> where V: Clone {
+        match &self.nodes[idx] {
+            Node::Var(v)    => Expr::Var(v.clone()),
+            Node::Not(a)    => Expr::Not(Box::new(self.expr_at(*a))),
+            Node::Or(a, b)  => Expr::Or(
+                Box::new(self.expr_at(*a)),
+                Box::new(self.expr_at(*b))),
+            Node::And(a, b) => Expr::And(
+                Box::new(self.expr_at(*a)),
+                Box::new(self.expr_at(*b))),
+        }
+    }
+}
+
+impl<V> StaticExpr<V> where V: Display {
+    /// Returns a [`SharedDisplay`] of the expression, which labels shared
+    /// nodes instead of expanding them at each use.
+    pub fn display_shared(&self) -> SharedDisplay<'_, V> {
+        SharedDisplay { nodes: self.nodes.iter().collect() }
+    }
+}
+
+impl<V> StaticExpr<V> where V: Clone {
+    /// Converts the expression into an [`Expr`].
+    ///
+    /// [`Expr`]: crate::Expr
+    pub fn to_expr(&self) -> Expr<V> {
+        self.expr_at(self.nodes.len() - 1)
+    }
+}
+
+impl<V> Eval for StaticExpr<V> where V: Eval {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        self.eval_at(self.nodes.len() - 1, data)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SharedDisplay
+////////////////////////////////////////////////////////////////////////////////
+/// Displays a flat expression, labeling each operator node used as an operand
+/// more than once rather than expanding it at each use.
+///
+/// Each shared node is written on its own line as `#k = ...`, in order, and
+/// is referred to as `#k` afterward. The final line is the root expression:
+///
+/// ```rust
+/// use simple_predicates::Node;
+/// use simple_predicates::StaticExpr;
+///
+/// // (1 & 2) | !(1 & 2)
+/// static RULE: StaticExpr<u32> = StaticExpr::new(&[
+///     Node::Var(1),
+///     Node::Var(2),
+///     Node::And(0, 1),
+///     Node::Not(2),
+///     Node::Or(2, 3),
+/// ]);
+///
+/// assert_eq!(RULE.display_shared().to_string(), "#1 = (1 & 2)\n(#1 | !#1)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedDisplay<'a, V> {
+    /// The nodes of the expression, in post-order.
+    nodes: Vec<&'a Node<V>>,
+}
+
+impl<'a, V> SharedDisplay<'a, V> where V: Display {
+    /// Writes the node at the given index, referring to labeled operands by
+    /// their label.
+    fn fmt_node(&self, f: &mut Formatter<'_>, idx: usize, labels: &[usize])
+        -> std::fmt::Result
+    {
+        guard(|| match self.nodes[idx] {
+            Node::Var(v)    => write!(f, "{}", v),
+            Node::Not(a)    => {
+                write!(f, "!")?;
+                self.fmt_operand(f, *a, labels)
+            },
+            Node::Or(a, b)  => {
+                write!(f, "(")?;
+                self.fmt_operand(f, *a, labels)?;
+                write!(f, " | ")?;
+                self.fmt_operand(f, *b, labels)?;
+                write!(f, ")")
+            },
+            Node::And(a, b) => {
+                write!(f, "(")?;
+                self.fmt_operand(f, *a, labels)?;
+                write!(f, " & ")?;
+                self.fmt_operand(f, *b, labels)?;
+                write!(f, ")")
+            },
+        })
+    }
+
+    /// Writes an operand node, or its label if it has one.
+    fn fmt_operand(&self, f: &mut Formatter<'_>, idx: usize, labels: &[usize])
+        -> std::fmt::Result
+    {
+        match labels[idx] {
+            0     => self.fmt_node(f, idx, labels),
+            label => write!(f, "#{}", label),
+        }
+    }
+}
+
+impl<'a, V> Display for SharedDisplay<'a, V> where V: Display {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut uses = vec![0usize; self.nodes.len()];
+        for node in &self.nodes {
+            match node {
+                Node::Var(_)    => (),
+                Node::Not(a)    => uses[*a] += 1,
+                Node::Or(a, b)  |
+                Node::And(a, b) => { uses[*a] += 1; uses[*b] += 1; },
+            }
+        }
+
+        // Label shared operator nodes in order, starting from 1.
+        let mut labels = vec![0usize; self.nodes.len()];
+        let mut next = 1;
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if uses[idx] > 1 && !matches!(node, Node::Var(_)) {
+                labels[idx] = next;
+                next += 1;
+            }
+        }
+
+        for (idx, &label) in labels.iter().enumerate() {
+            if label != 0 {
+                write!(f, "#{} = ", label)?;
+                self.fmt_node(f, idx, &labels)?;
+                writeln!(f)?;
+            }
+        }
+        self.fmt_node(f, self.nodes.len() - 1, &labels)
+    }
+}
@@ -0,0 +1,37 @@
+
+////////////////////////////////////////////////////////////////////////////////
+// FNV-1a
+////////////////////////////////////////////////////////////////////////////////
+// The FNV-1a hashes are used wherever a hash must be stable across releases
+// and platforms, which the standard library hashers do not guarantee.
+
+/// The 64-bit FNV offset basis.
+pub (in crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The 64-bit FNV prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The 128-bit FNV offset basis.
+pub (in crate) const FNV_OFFSET_BASIS_128: u128 =
+    0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+
+/// The 128-bit FNV prime.
+const FNV_PRIME_128: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// Continues a 64-bit FNV-1a hash over the given bytes.
+pub (in crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Continues a 128-bit FNV-1a hash over the given bytes.
+pub (in crate) fn fnv1a_128(mut hash: u128, bytes: &[u8]) -> u128 {
+    for byte in bytes {
+        hash ^= u128::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME_128);
+    }
+    hash
+}
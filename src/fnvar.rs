@@ -0,0 +1,96 @@
+
+// Internal library imports.
+use crate::Eval;
+
+// Standard library imports
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FnVar
+////////////////////////////////////////////////////////////////////////////////
+/// A labeled variable evaluated by calling a function or closure on the
+/// context.
+///
+/// Variables are compared, hashed, and displayed by their label alone, so two
+/// variables with the same label are assumed to be the same condition:
+///
+/// ```rust
+/// use simple_predicates::Eval;
+/// use simple_predicates::Expr;
+/// use simple_predicates::FnVar;
+///
+/// let adult = FnVar::new("adult", |age: &u32| *age >= 18);
+/// let senior = FnVar::new("senior", |age: &u32| *age >= 65);
+/// let expr = Expr::Var(adult).and(!Expr::Var(senior));
+///
+/// assert!(expr.eval(&30));
+/// assert!(!expr.eval(&70));
+/// ```
+pub struct FnVar<C> {
+    /// The label identifying the condition.
+    label: Arc<str>,
+    /// The function evaluating the condition.
+    f: Arc<dyn Fn(&C) -> bool + Send + Sync>,
+}
+
+impl<C> FnVar<C> {
+    /// Constructs a new `FnVar` with the given label and function.
+    pub fn new<L, F>(label: L, f: F) -> Self
+        where
+            L: Into<String>,
+            F: Fn(&C) -> bool + Send + Sync + 'static,
+    {
+        FnVar { label: Arc::from(label.into()), f: Arc::new(f) }
+    }
+
+    /// Returns the label of the variable.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<C> Eval for FnVar<C> {
+    type Context = C;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        (self.f)(data)
+    }
+}
+
+impl<C> Clone for FnVar<C> {
+    fn clone(&self) -> Self {
+        FnVar { label: self.label.clone(), f: self.f.clone() }
+    }
+}
+
+impl<C> PartialEq for FnVar<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+impl<C> Eq for FnVar<C> {}
+
+impl<C> Hash for FnVar<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.label.hash(state);
+    }
+}
+
+impl<C> Display for FnVar<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl<C> Debug for FnVar<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FnVar").field(&self.label).finish()
+    }
+}
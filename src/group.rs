@@ -0,0 +1,162 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Labeled
+////////////////////////////////////////////////////////////////////////////////
+/// A variable of an expression with labeled groups, which can be disabled by
+/// their labels at evaluation time without editing the expression.
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// use simple_predicates::Expr::Var;
+/// use simple_predicates::Labeled;
+/// use std::collections::HashSet;
+///
+/// let expr = Var(Contains(1)).map(Labeled::Var)
+///     .and(Var(Contains(2)).or(Var(Contains(3))).labeled("beta", true));
+/// let data = vec![1];
+///
+/// assert!(!expr.eval(&data));
+/// let disabled: HashSet<_> = vec!["beta"].into_iter().collect();
+/// assert!(expr.eval_with_disabled(&data, &disabled));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Labeled<V> {
+    /// A variable.
+    Var(V),
+    /// A labeled group.
+    Group {
+        /// The label of the group.
+        label: String,
+        /// The expression of the group.
+        expr: Box<Expr<Labeled<V>>>,
+        /// The value of the group when it is disabled.
+        when_disabled: bool,
+    },
+}
+
+impl<V> Labeled<V> {
+    /// Constructs a new labeled group of the given expression, which has the
+    /// value `when_disabled` when it is disabled.
+    pub fn group<L>(label: L, expr: Expr<Labeled<V>>, when_disabled: bool)
+        -> Self
+        where L: Into<String>
+    {
+        Labeled::Group {
+            label: label.into(),
+            expr: Box::new(expr),
+            when_disabled,
+        }
+    }
+
+    /// Returns the label of the group, or `None` for a variable.
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Labeled::Var(_)              => None,
+            Labeled::Group { label, .. } => Some(label),
+        }
+    }
+}
+
+impl<V> Eval for Labeled<V> where V: Eval {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        match self {
+            Labeled::Var(v)             => v.eval(data),
+            Labeled::Group { expr, .. } => expr.eval(data),
+        }
+    }
+}
+
+impl<V> From<V> for Labeled<V> {
+    fn from(var: V) -> Self {
+        Labeled::Var(var)
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Labeled expressions
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> {
+    /// Returns a labeled expression consisting of a single group containing
+    /// the expression, which has the value `when_disabled` when it is
+    /// disabled.
+    pub fn labeled<L>(self, label: L, when_disabled: bool)
+        -> Expr<Labeled<V>>
+        where L: Into<String>
+    {
+        Expr::Var(Labeled::group(label, self.map(Labeled::Var), when_disabled))
+    }
+}
+
+impl<V> Expr<Labeled<V>> {
+    /// Returns the labels of the groups of the expression, including nested
+    /// groups, in pre-order.
+    pub fn group_labels(&self) -> Vec<&str> {
+        use Expr::*;
+        guard(|| match self {
+            Var(Labeled::Var(_)) => Vec::new(),
+            Var(Labeled::Group { label, expr, .. }) => {
+                let mut labels = vec![label.as_str()];
+                labels.extend(expr.group_labels());
+                labels
+            },
+            Not(p)    => p.group_labels(),
+            Or(a, b)  |
+            And(a, b) => {
+                let mut labels = a.group_labels();
+                labels.extend(b.group_labels());
+                labels
+            },
+        })
+    }
+
+    /// Evaluates the expression, using the configured value of each group
+    /// whose label is in `disabled` instead of evaluating it.
+    pub fn eval_with_disabled<S>(
+        &self,
+        data: &V::Context,
+        disabled: &HashSet<S>)
+        -> bool
+        where
+            V: Eval,
+            S: Borrow<str> + Eq + Hash,
+    {
+        use Expr::*;
+        guard(|| match self {
+            Var(Labeled::Var(v)) => v.eval(data),
+            Var(Labeled::Group { label, expr, when_disabled }) => {
+                if disabled.contains(label.as_str()) {
+                    *when_disabled
+                } else {
+                    expr.eval_with_disabled(data, disabled)
+                }
+            },
+            Not(p)    => !p.eval_with_disabled(data, disabled),
+            Or(a, b)  => a.eval_with_disabled(data, disabled)
+                || b.eval_with_disabled(data, disabled),
+            And(a, b) => a.eval_with_disabled(data, disabled)
+                && b.eval_with_disabled(data, disabled),
+        })
+    }
+}
@@ -0,0 +1,151 @@
+
+// Internal library imports.
+use crate::CnfHashSet;
+use crate::CnfVec;
+use crate::Eval;
+use crate::Expr;
+use crate::vars::expr_vars;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// IndexedCnf
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression in [Conjunctive Normal Form], with an index from each
+/// variable to the clauses containing it.
+///
+/// The clauses can only be modified through the methods of this type, which
+/// keep the index up to date.
+///
+/// [Conjunctive Normal Form]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
+#[derive(Debug, Clone)]
+pub struct IndexedCnf<V> where V: Eval + Eq + Hash {
+    /// The conjunctive clauses.
+    clauses: Vec<Expr<V>>,
+    /// The indices of the clauses containing each variable.
+    index: HashMap<V, Vec<usize>>,
+}
+
+impl<V> IndexedCnf<V> where V: Eval + Eq + Hash + Clone {
+    /// Constructs a new `IndexedCnf` from the given conjunctive clauses.
+    pub fn new(clauses: Vec<Expr<V>>) -> Self {
+        let mut indexed = IndexedCnf {
+            clauses: Vec::with_capacity(clauses.len()),
+            index: HashMap::new(),
+        };
+        for clause in clauses {
+            let _ = indexed.push(clause);
+        }
+        indexed
+    }
+
+    /// Returns the conjunctive clauses.
+    pub fn clauses(&self) -> &[Expr<V>] {
+        &self.clauses
+    }
+
+    /// Returns the number of clauses.
+    pub fn len(&self) -> usize {
+        self.clauses.len()
+    }
+
+    /// Returns true if the boolean expression contains no terms.
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Returns the indices of the clauses containing the given variable, in no
+    /// particular order.
+    pub fn clauses_with(&self, var: &V) -> &[usize] {
+        self.index.get(var).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Adds a clause, returning its index.
+    pub fn push(&mut self, clause: Expr<V>) -> usize {
+        let idx = self.clauses.len();
+        for var in expr_vars(&clause) {
+            self.index.entry(var).or_default().push(idx);
+        }
+        self.clauses.push(clause);
+        idx
+    }
+
+    /// Removes and returns the clause at the given index. The last clause is
+    /// moved into its place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn remove(&mut self, idx: usize) -> Expr<V> {
+        let last = self.clauses.len() - 1;
+        let removed = self.clauses.swap_remove(idx);
+        self.unindex(&removed, idx);
+        if idx != last {
+            for var in expr_vars(&self.clauses[idx]) {
+                let entries = self.index.get_mut(&var).expect("indexed var");
+                for entry in entries.iter_mut().filter(|e| **e == last) {
+                    *entry = idx;
+                }
+            }
+        }
+        removed
+    }
+
+    /// Replaces the clause at the given index, returning the replaced clause.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn replace(&mut self, idx: usize, clause: Expr<V>) -> Expr<V> {
+        let replaced = std::mem::replace(&mut self.clauses[idx], clause);
+        self.unindex(&replaced, idx);
+        for var in expr_vars(&self.clauses[idx]) {
+            self.index.entry(var).or_default().push(idx);
+        }
+        replaced
+    }
+
+    /// Removes the index entries for the given clause at the given index.
+    fn unindex(&mut self, clause: &Expr<V>, idx: usize) {
+        for var in expr_vars(clause) {
+            if let Some(entries) = self.index.get_mut(&var) {
+                entries.retain(|e| *e != idx);
+                if entries.is_empty() { let _ = self.index.remove(&var); }
+            }
+        }
+    }
+
+    /// Returns the conjunctive clauses as a [`CnfVec`], discarding the index.
+    ///
+    /// [`CnfVec`]: crate::CnfVec
+    pub fn into_cnf(self) -> CnfVec<V> {
+        CnfVec::from(self.clauses)
+    }
+}
+
+impl<V> Eval for IndexedCnf<V> where V: Eval + Eq + Hash {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        self.clauses.iter().all(|expr| expr.eval(data))
+    }
+}
+
+impl<V> CnfVec<V> where V: Eval + Eq + Hash + Clone {
+    /// Returns the clauses of the expression with an index from each variable
+    /// to the clauses containing it. Clauses keep their order.
+    pub fn index_by_var(self) -> IndexedCnf<V> {
+        IndexedCnf::new(self.into_vec())
+    }
+}
+
+impl<V> CnfHashSet<V> where V: Eval + Eq + Hash + Clone {
+    /// Returns the clauses of the expression with an index from each variable
+    /// to the clauses containing it. Clauses are in no particular order.
+    pub fn index_by_var(self) -> IndexedCnf<V> {
+        IndexedCnf::new(self.into_vec())
+    }
+}
@@ -0,0 +1,259 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// VarIndexer
+////////////////////////////////////////////////////////////////////////////////
+/// Assigns dense `u32` indices to variables, in order of first appearance.
+///
+/// Indexed expressions can be processed by code which needs compact variable
+/// identifiers, and converted back afterwards:
+///
+/// ```rust
+/// use simple_predicates::Expr;
+/// use simple_predicates::VarIndexer;
+///
+/// let expr = Expr::Var("b").and(!Expr::Var("a")).or(Expr::Var("b"));
+/// let mut indexer = VarIndexer::new();
+/// let indexed = indexer.index_expr(&expr);
+///
+/// assert_eq!(indexed, Expr::Var(0).and(!Expr::Var(1)).or(Expr::Var(0)));
+/// assert_eq!(indexer.resolve_expr(&indexed), Some(expr));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VarIndexer<V> {
+    /// The variables, by index.
+    vars: Vec<V>,
+    /// The index of each variable.
+    indices: HashMap<V, u32>,
+}
+
+impl<V> VarIndexer<V> where V: Eq + Hash + Clone {
+    /// Constructs a new `VarIndexer` with no variables.
+    pub fn new() -> Self {
+        VarIndexer { vars: Vec::new(), indices: HashMap::new() }
+    }
+
+    /// Returns the index of the variable, assigning the next index if the
+    /// variable has not been indexed.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if more than `u32::MAX` variables are indexed.
+    pub fn index(&mut self, var: &V) -> u32 {
+        if let Some(idx) = self.indices.get(var) { return *idx; }
+        let idx = u32::try_from(self.vars.len())
+            .expect("too many variables to index");
+        self.vars.push(var.clone());
+        let _ = self.indices.insert(var.clone(), idx);
+        idx
+    }
+
+    /// Returns the index of the variable, if it has been indexed.
+    pub fn get(&self, var: &V) -> Option<u32> {
+        self.indices.get(var).copied()
+    }
+
+    /// Returns the variable with the given index.
+    pub fn var(&self, idx: u32) -> Option<&V> {
+        self.vars.get(idx as usize)
+    }
+
+    /// Returns the indexed variables, in order of their indices.
+    pub fn vars(&self) -> &[V] {
+        &self.vars
+    }
+
+    /// Returns the number of indexed variables.
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// Returns true if no variables have been indexed.
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Returns the expression with each variable replaced by its index,
+    /// indexing any new variables.
+    pub fn index_expr(&mut self, expr: &Expr<V>) -> Expr<u32> {
+        use Expr::*;
+        guard(|| match expr {
+            Var(v)    => Var(self.index(v)),
+            Not(p)    => Not(Box::new(self.index_expr(p))),
+            Or(a, b)  => Or(
+                Box::new(self.index_expr(a)),
+                Box::new(self.index_expr(b))),
+            And(a, b) => And(
+                Box::new(self.index_expr(a)),
+                Box::new(self.index_expr(b))),
+        })
+    }
+
+    /// Returns the expression with each index replaced by its variable, or
+    /// `None` if the expression contains an unassigned index.
+    pub fn resolve_expr(&self, expr: &Expr<u32>) -> Option<Expr<V>> {
+        use Expr::*;
+        guard(|| Some(match expr {
+            Var(idx)  => Var(self.var(*idx)?.clone()),
+            Not(p)    => Not(Box::new(self.resolve_expr(p)?)),
+            Or(a, b)  => Or(
+                Box::new(self.resolve_expr(a)?),
+                Box::new(self.resolve_expr(b)?)),
+            And(a, b) => And(
+                Box::new(self.resolve_expr(a)?),
+                Box::new(self.resolve_expr(b)?)),
+        }))
+    }
+}
+
+impl<V> Default for VarIndexer<V> where V: Eq + Hash + Clone {
+    fn default() -> Self {
+        VarIndexer::new()
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Assignment
+////////////////////////////////////////////////////////////////////////////////
+/// A partial assignment of truth values to variables indexed by a
+/// [`VarIndexer`], stored as a pair of bitsets.
+///
+/// [`VarIndexer`]: crate::VarIndexer
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Assignment {
+    /// The value of each assigned variable. Unassigned bits are clear.
+    values: Vec<u64>,
+    /// Which variables are assigned.
+    assigned: Vec<u64>,
+}
+
+impl Assignment {
+    /// Constructs a new `Assignment` with no assigned variables.
+    pub fn new() -> Self {
+        Assignment { values: Vec::new(), assigned: Vec::new() }
+    }
+
+    /// Returns the value of the variable with the given index, if it is
+    /// assigned.
+    pub fn get(&self, idx: u32) -> Option<bool> {
+        let (word, bit) = (idx as usize / 64, 1u64 << (idx % 64));
+        match self.assigned.get(word) {
+            Some(assigned) if assigned & bit != 0 => {
+                Some(self.values[word] & bit != 0)
+            },
+            _ => None,
+        }
+    }
+
+    /// Assigns a value to the variable with the given index.
+    pub fn set(&mut self, idx: u32, value: bool) {
+        let (word, bit) = (idx as usize / 64, 1u64 << (idx % 64));
+        if word >= self.assigned.len() {
+            self.values.resize(word + 1, 0);
+            self.assigned.resize(word + 1, 0);
+        }
+        self.assigned[word] |= bit;
+        if value {
+            self.values[word] |= bit;
+        } else {
+            self.values[word] &= !bit;
+        }
+    }
+
+    /// Removes the value of the variable with the given index.
+    pub fn unset(&mut self, idx: u32) {
+        let (word, bit) = (idx as usize / 64, 1u64 << (idx % 64));
+        if word < self.assigned.len() {
+            self.assigned[word] &= !bit;
+            self.values[word] &= !bit;
+        }
+        // Keep the representation canonical for comparisons.
+        while self.assigned.last() == Some(&0) {
+            let _ = self.assigned.pop();
+            let _ = self.values.pop();
+        }
+    }
+
+    /// Returns the number of assigned variables.
+    pub fn len(&self) -> usize {
+        self.assigned.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns true if no variables are assigned.
+    pub fn is_empty(&self) -> bool {
+        self.assigned.iter().all(|w| *w == 0)
+    }
+
+    /// Removes the values of all variables.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.assigned.clear();
+    }
+
+    /// Returns an iterator over the indices and values of the assigned
+    /// variables, in order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item=(u32, bool)> + '_ {
+        self.assigned.iter()
+            .enumerate()
+            .flat_map(move |(word, &assigned)| (0..64)
+                .filter(move |bit| assigned & (1u64 << bit) != 0)
+                .map(move |bit| {
+                    let value = self.values[word] & (1u64 << bit) != 0;
+                    ((word * 64 + bit) as u32, value)
+                }))
+    }
+
+    /// Evaluates the indexed expression under the assignment. Returns `None`
+    /// if the result depends on an unassigned variable.
+    pub fn eval(&self, expr: &Expr<u32>) -> Option<bool> {
+        use Expr::*;
+        guard(|| match expr {
+            Var(idx)  => self.get(*idx),
+            Not(p)    => self.eval(p).map(|v| !v),
+            Or(a, b)  => match (self.eval(a), self.eval(b)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false))        => Some(false),
+                _                                 => None,
+            },
+            And(a, b) => match (self.eval(a), self.eval(b)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true))            => Some(true),
+                _                                   => None,
+            },
+        })
+    }
+
+    /// Constructs an assignment from a map of variable values, indexing any
+    /// new variables.
+    pub fn from_map<V>(map: &HashMap<V, bool>, indexer: &mut VarIndexer<V>)
+        -> Self
+        where V: Eq + Hash + Clone
+    {
+        let mut assignment = Assignment::new();
+        for (var, value) in map {
+            assignment.set(indexer.index(var), *value);
+        }
+        assignment
+    }
+
+    /// Returns the assigned values as a map from variables. Indices without a
+    /// variable in the indexer are omitted.
+    pub fn to_map<V>(&self, indexer: &VarIndexer<V>) -> HashMap<V, bool>
+        where V: Eq + Hash + Clone
+    {
+        self.iter()
+            .filter_map(|(idx, value)| indexer.var(idx)
+                .map(|var| (var.clone(), value)))
+            .collect()
+    }
+}
@@ -0,0 +1,117 @@
+
+// Internal library imports.
+use crate::CnfVec;
+use crate::Eval;
+use crate::Expr;
+use crate::stack::guard;
+use crate::truth::distinct_vars;
+use crate::truth::find_model;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Pruning under invariants
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eval + PartialEq + Clone {
+    /// Returns an equivalent expression for every assignment satisfying the
+    /// given invariants, with the subexpressions whose value the invariants
+    /// decide removed from their enclosing `And` or `Or` expressions.
+    ///
+    /// A subexpression which is always true under the invariants is dropped
+    /// from a conjunction, and one which is always false is dropped from a
+    /// disjunction. A conjunction with an operand which is always false, or a
+    /// disjunction with an operand which is always true, is itself decided and
+    /// is removed from its parent in turn.
+    ///
+    /// Since an expression cannot be a constant, the expression is returned
+    /// unchanged if the invariants decide its value or are unsatisfiable.
+    ///
+    /// Each subexpression is checked by searching for a model, which may take
+    /// time exponential in the number of variables.
+    ///
+    /// ```rust
+    /// # use simple_predicates::Eval;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::CnfVec;
+    /// use simple_predicates::Expr::Var;
+    ///
+    /// // Variable 1 is never true, so the first term is never satisfied.
+    /// let invariants = CnfVec::from(vec![!Var(Contains(1))]);
+    /// let expr = Var(Contains(1)).and(Var(Contains(2)))
+    ///     .or(Var(Contains(3)).and(Var(Contains(4))));
+    ///
+    /// assert_eq!(
+    ///     expr.prune_with_invariants(&invariants),
+    ///     Var(Contains(3)).and(Var(Contains(4))));
+    /// ```
+    pub fn prune_with_invariants(&self, invariants: &CnfVec<V>) -> Expr<V> {
+        let invariants: Vec<&Expr<V>> = invariants.as_ref().iter().collect();
+        let vars = distinct_vars(invariants.iter().copied());
+        let mut values = vec![None; vars.len()];
+        if !find_model(&invariants, &vars, &mut values, 0) {
+            return self.clone();
+        }
+
+        match self.prune_inner(&invariants) {
+            Ok(expr) => expr,
+            Err(_)   => self.clone(),
+        }
+    }
+
+    /// Prunes the expression, returning its value as an error if the
+    /// invariants decide it.
+    fn prune_inner(&self, invariants: &[&Expr<V>]) -> Result<Expr<V>, bool> {
+        use Expr::*;
+        if let Some(value) = decided_value(self, invariants) {
+            return Err(value);
+        }
+
+        guard(|| match self {
+            Var(_)    => Ok(self.clone()),
+            Not(p)    => p.prune_inner(invariants)
+                .map(|p| Not(Box::new(p)))
+                .map_err(|value| !value),
+            And(a, b) => match (
+                a.prune_inner(invariants),
+                b.prune_inner(invariants))
+            {
+                (Err(false), _) | (_, Err(false)) => Err(false),
+                (Err(true), x) | (x, Err(true))   => x,
+                (Ok(a), Ok(b)) => Ok(And(Box::new(a), Box::new(b))),
+            },
+            Or(a, b)  => match (
+                a.prune_inner(invariants),
+                b.prune_inner(invariants))
+            {
+                (Err(true), _) | (_, Err(true))   => Err(true),
+                (Err(false), x) | (x, Err(false)) => x,
+                (Ok(a), Ok(b)) => Ok(Or(Box::new(a), Box::new(b))),
+            },
+        })
+    }
+}
+
+/// Returns the value of the expression under every assignment satisfying the
+/// invariants, or `None` if it has different values under different
+/// assignments. The invariants must be satisfiable.
+fn decided_value<V>(expr: &Expr<V>, invariants: &[&Expr<V>]) -> Option<bool>
+    where V: PartialEq + Clone
+{
+    let negated = Expr::Not(Box::new(expr.clone()));
+    for (value, clause) in [(false, expr), (true, &negated)].iter().copied() {
+        let mut clauses = invariants.to_vec();
+        clauses.push(clause);
+        let vars = distinct_vars(clauses.iter().copied());
+        let mut values = vec![None; vars.len()];
+        if !find_model(&clauses, &vars, &mut values, 0) {
+            return Some(value);
+        }
+    }
+    None
+}
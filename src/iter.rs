@@ -0,0 +1,109 @@
+
+// Internal library imports.
+use crate::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// TraversalOrder
+////////////////////////////////////////////////////////////////////////////////
+/// The order in which [`Subexprs`] visits an expression tree.
+///
+/// [`Subexprs`]: crate::Subexprs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraversalOrder {
+    /// Each expression is visited before its operands.
+    PreOrder,
+    /// Each expression is visited after its operands.
+    PostOrder,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Subexprs
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the subexpressions of an [`Expr`], including the
+/// expression itself. Operands are visited left to right.
+///
+/// [`Expr`]: crate::Expr
+#[derive(Debug, Clone)]
+pub struct Subexprs<'a, V> {
+    /// The traversal order.
+    order: TraversalOrder,
+    /// The pending expressions, and whether their operands have been pushed.
+    stack: Vec<(&'a Expr<V>, bool)>,
+}
+
+impl<'a, V> Iterator for Subexprs<'a, V> {
+    type Item = &'a Expr<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use Expr::*;
+        loop {
+            let (expr, expanded) = self.stack.pop()?;
+            if expanded { return Some(expr); }
+
+            if self.order == TraversalOrder::PostOrder {
+                self.stack.push((expr, true));
+            }
+            match expr {
+                Var(_)    => (),
+                Not(p)    => self.stack.push((p, false)),
+                Or(a, b)  |
+                And(a, b) => {
+                    self.stack.push((b, false));
+                    self.stack.push((a, false));
+                },
+            }
+            if self.order == TraversalOrder::PreOrder {
+                return Some(expr);
+            }
+        }
+    }
+}
+
+impl<V> Expr<V> {
+    /// Returns an iterator over the subexpressions of the expression in the
+    /// given order, including the expression itself.
+    pub fn iter_subexprs(&self, order: TraversalOrder) -> Subexprs<'_, V> {
+        Subexprs { order, stack: vec![(self, false)] }
+    }
+
+    /// Folds the subexpressions of the expression in pre-order, stopping at
+    /// the first error returned by `f`. Subexpressions after the error are
+    /// not visited.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(3));
+    /// let vars = expr.try_fold(0, |count, e| match e {
+    ///     Expr::Var(v) if *v > 2 => Err(*v),
+    ///     Expr::Var(_)           => Ok(count + 1),
+    ///     _                      => Ok(count),
+    /// });
+    /// assert_eq!(vars, Err(3));
+    /// ```
+    pub fn try_fold<B, E, F>(&self, init: B, f: F) -> Result<B, E>
+        where F: FnMut(B, &Expr<V>) -> Result<B, E>
+    {
+        self.iter_subexprs(TraversalOrder::PreOrder).try_fold(init, f)
+    }
+
+    /// Returns true if the predicate holds for any subexpression of the
+    /// expression, including the expression itself. Subexpressions are
+    /// visited in pre-order, stopping at the first match.
+    pub fn any_subexpr<F>(&self, pred: F) -> bool
+        where F: FnMut(&Expr<V>) -> bool
+    {
+        self.iter_subexprs(TraversalOrder::PreOrder).any(pred)
+    }
+
+    /// Returns true if the predicate holds for every subexpression of the
+    /// expression, including the expression itself. Subexpressions are
+    /// visited in pre-order, stopping at the first failure.
+    pub fn all_subexprs<F>(&self, pred: F) -> bool
+        where F: FnMut(&Expr<V>) -> bool
+    {
+        self.iter_subexprs(TraversalOrder::PreOrder).all(pred)
+    }
+}
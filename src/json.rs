@@ -0,0 +1,50 @@
+
+// Internal library imports.
+use crate::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// JSON helpers
+////////////////////////////////////////////////////////////////////////////////
+/// Helpers for exchanging string-variable expressions as JSON, e.g., across a
+/// `wasm-bindgen` boundary.
+///
+/// The JSON representation follows the `serde` encoding of [`Expr`]:
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # //-------------------------------------------------------------------
+/// use simple_predicates::Expr;
+///
+/// let expr = Expr::from_json(r#"{"And":[{"Var":"a"},{"Not":{"Var":"b"}}]}"#)?;
+///
+/// assert!(expr.eval_names(&["a"]));
+/// assert!(!expr.eval_names(&["a", "b"]));
+/// # //-------------------------------------------------------------------
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Expr`]: crate::Expr
+impl Expr<String> {
+    /// Parses an expression from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the JSON representation of the expression.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Evaluates the expression, treating the named variables as true and all
+    /// other variables as false.
+    pub fn eval_names<S>(&self, true_vars: &[S]) -> bool
+        where S: AsRef<str>
+    {
+        self.eval_with_provider(&mut |var: &String| true_vars
+            .iter()
+            .any(|name| name.as_ref() == var))
+    }
+}
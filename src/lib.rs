@@ -8,12 +8,11 @@
 //!
 //! A predicate is a formula that can be evaluated to true or false as a 
 //! function of the values of the variables that occur in it. In
-//! `simple_predicates`, the variables denoted by a user-chosen type satisfying
-//! the [`Eval`] trait (which requires [`Clone`] and [`PartialEq`].) Vhe
-//! [`Eval`] trait also provides an associated [`Context`] type which can be
-//! used to provide contextual data needed to resolve the variables. Vhe
-//! [`Expr`], [`CnfHashSet`], and [`DnfHashSet`] types can be used to construct evaluable
-//! expressions.
+//! `simple_predicates`, the variables are denoted by a user-chosen type
+//! satisfying the [`Eval`] trait. The [`Eval`] trait also provides an
+//! associated [`Context`] type which can be used to provide contextual data
+//! needed to resolve the variables. The [`Expr`], [`CnfHashSet`], and
+//! [`DnfHashSet`] types can be used to construct evaluable expressions.
 //! 
 //! 
 //! # Installation
@@ -30,8 +29,19 @@
 //! | Feature | Description |
 //! | ------- | ----------- |
 //! | "serde" | Enables serialization and deserialization of data using [serde](https://crates.io/crates/serde). |
+//! | "arbitrary" | Implements `Arbitrary` for expressions and normal forms using [arbitrary](https://crates.io/crates/arbitrary), for use in fuzzing. |
+//! | "boolean_expression" | Implements conversions between `Expr` and the expression type of [boolean_expression](https://crates.io/crates/boolean_expression), for use with its BDD tooling. |
+//! | "fast-hash" | Uses the faster non-cryptographic hasher from [rustc-hash](https://crates.io/crates/rustc-hash) for the hash sets and maps used internally, such as when collecting variables. Public types are unaffected. |
+//! | "ffi" | Enables the C foreign function interface in the `ffi` module. Implies "json". |
+//! | "json" | Enables JSON helpers for `Expr<String>` using [serde_json](https://crates.io/crates/serde_json). Implies "serde". |
+//! | "macros" | Enables the `static_expr!` macro, which builds a `StaticExpr` at compile time, and the `Eval` derive macro for comparison types. |
+//! | "stacker" | Grows the stack on demand during recursive operations using [stacker](https://crates.io/crates/stacker), so arbitrarily deep expressions can be evaluated, transformed, compared, cloned, hashed, and formatted. Dropping and (de)serialization with "serde" are not guarded. |
+//! | "tracing" | Enables the `TracedExpr` wrapper, which emits [tracing](https://crates.io/crates/tracing) events during evaluation. |
+//! | "verify-transforms" | Checks every normal form conversion, simplification, negation normal form conversion, tautology removal, and absorption against the original expression with a truth table in debug builds, for expressions of at most 12 variables. |
 //!
 //! By default, there are no features enabled.
+//! Without features, the crate has no dependencies and builds for
+//! `wasm32-unknown-unknown`.
 //! 
 //! # Example Usage
 //! 
@@ -111,7 +121,8 @@
 //! type implements [`Eq`] and [`Hash`]. They have identical APIs, so the
 //! examples below are representative of either. There are version backed by
 //! `Vec` ([`CnfVec`] and [`DnfVec`]) which have more relaxed requirements, but
-//! are not as efficient in some instances.
+//! are not as efficient in some instances. Converting an [`Expr`] into any of
+//! these types also requires the variable type to implement [`Clone`].
 //! 
 //! 
 //! ## Examples
@@ -244,13 +255,124 @@
 
 
 // Internal modules
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "boolean_expression")]
+mod boolean_expression_impls;
+mod borrowed;
+mod budget;
+mod cache;
+mod clausal;
+mod compile;
+mod counted;
+mod coverage;
+mod delta;
+mod display;
+mod error;
+mod evaluator;
 mod expr;
+mod factor;
+mod fasthash;
+mod fallible;
+mod flat;
+mod fnv;
+mod fnvar;
+mod group;
 mod hash;
+mod index;
+mod indexer;
+mod invariant;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod limits;
+mod lint;
+mod merkle;
+mod observe;
+mod parse;
+mod path;
+mod policy;
+mod profile;
+mod provider;
+mod rng;
+mod rule;
+mod sample;
+mod shrink;
+mod snapshot;
+mod solver;
+mod stable;
+mod stack;
+mod steps;
+mod store;
+mod stream;
+mod targeting;
+mod terms;
+#[cfg(feature = "tracing")]
+mod trace;
+mod truth;
+mod vars;
 mod vec;
 #[cfg(test)]
 mod tests;
 
+// Allows `static_expr!` output to name this crate in its own tests.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as simple_predicates;
 
+// Public modules
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixtures;
+pub mod mutate;
+pub mod testing;
+
+
+#[cfg(feature = "boolean_expression")]
+pub use crate::boolean_expression_impls::*;
+pub use crate::borrowed::*;
+pub use crate::budget::*;
+pub use crate::cache::*;
+pub use crate::clausal::*;
+pub use crate::compile::*;
+pub use crate::counted::*;
+pub use crate::coverage::*;
+pub use crate::delta::*;
+pub use crate::display::*;
+pub use crate::error::*;
+pub use crate::evaluator::*;
 pub use crate::expr::*;
+pub use crate::factor::*;
+pub use crate::fallible::*;
+pub use crate::flat::*;
+pub use crate::fnvar::*;
+pub use crate::group::*;
 pub use crate::hash::*;
+pub use crate::index::*;
+pub use crate::indexer::*;
+pub use crate::iter::*;
+pub use crate::limits::*;
+pub use crate::lint::*;
+pub use crate::merkle::*;
+pub use crate::observe::*;
+pub use crate::parse::*;
+pub use crate::path::*;
+pub use crate::policy::*;
+pub use crate::profile::*;
+pub use crate::provider::*;
+pub use crate::rule::*;
+pub use crate::snapshot::*;
+pub use crate::solver::*;
+pub use crate::steps::*;
+pub use crate::store::*;
+pub use crate::stream::*;
+pub use crate::targeting::*;
+pub use crate::terms::*;
+#[cfg(feature = "tracing")]
+pub use crate::trace::*;
+pub use crate::vars::*;
 pub use crate::vec::*;
+
+#[cfg(feature = "macros")]
+pub use simple_predicates_macros::Eval;
+#[cfg(feature = "macros")]
+pub use simple_predicates_macros::static_expr;
@@ -0,0 +1,134 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+use crate::fasthash::FastHashSet;
+
+// Standard library imports
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ComplexityLimit
+////////////////////////////////////////////////////////////////////////////////
+/// Limits on the size of an expression, e.g., for validating user-provided
+/// predicates. A limit of `None` is not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ComplexityLimit {
+    /// The maximum depth of the expression. A lone variable has depth 1.
+    pub max_depth: Option<usize>,
+    /// The maximum number of nodes in the expression.
+    pub max_nodes: Option<usize>,
+    /// The maximum number of distinct variables in the expression.
+    pub max_vars: Option<usize>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LimitViolation
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that an expression exceeds a [`ComplexityLimit`].
+///
+/// [`ComplexityLimit`]: crate::ComplexityLimit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LimitViolation {
+    /// The expression is deeper than the maximum depth.
+    Depth {
+        /// The maximum depth.
+        limit: usize,
+        /// The path to the first node, in pre-order, below the maximum depth.
+        path: ExprPath,
+    },
+    /// The expression has more nodes than the maximum.
+    Nodes {
+        /// The maximum number of nodes.
+        limit: usize,
+        /// The number of nodes in the expression.
+        actual: usize,
+    },
+    /// The expression has more distinct variables than the maximum.
+    Vars {
+        /// The maximum number of distinct variables.
+        limit: usize,
+        /// The number of distinct variables in the expression.
+        actual: usize,
+    },
+}
+
+impl Display for LimitViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitViolation::Depth { limit, path } => write!(f,
+                "expression exceeds maximum depth of {} at {:?}",
+                limit,
+                path.steps()),
+            LimitViolation::Nodes { limit, actual } => write!(f,
+                "expression has {} nodes, but at most {} are allowed",
+                actual,
+                limit),
+            LimitViolation::Vars { limit, actual } => write!(f,
+                "expression has {} variables, but at most {} are allowed",
+                actual,
+                limit),
+        }
+    }
+}
+
+impl Error for LimitViolation {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Limit checking
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash {
+    /// Checks the expression against the given limits. The depth is checked
+    /// first, then the number of nodes, then the number of variables.
+    pub fn check_limits(&self, limit: &ComplexityLimit)
+        -> Result<(), LimitViolation>
+    {
+        use Expr::*;
+        let mut nodes = 0;
+        let mut vars: FastHashSet<&V> = FastHashSet::default();
+        let mut too_deep: Option<ExprPath> = None;
+
+        let mut stack = vec![(self, ExprPath::root())];
+        while let Some((expr, path)) = stack.pop() {
+            nodes += 1;
+            if too_deep.is_none()
+                && matches!(limit.max_depth, Some(max) if path.len() >= max)
+            {
+                too_deep = Some(path.clone());
+            }
+            match expr {
+                Var(v)    => { let _ = vars.insert(v); },
+                Not(p)    => stack.push((p, path.child(PathStep::Child))),
+                Or(a, b)  |
+                And(a, b) => {
+                    stack.push((b, path.child(PathStep::Right)));
+                    stack.push((a, path.child(PathStep::Left)));
+                },
+            }
+        }
+
+        if let (Some(limit), Some(path)) = (limit.max_depth, too_deep) {
+            return Err(LimitViolation::Depth { limit, path });
+        }
+        match limit.max_nodes {
+            Some(max) if nodes > max => {
+                return Err(LimitViolation::Nodes { limit: max, actual: nodes });
+            },
+            _ => (),
+        }
+        match limit.max_vars {
+            Some(max) if vars.len() > max => Err(LimitViolation::Vars {
+                limit: max,
+                actual: vars.len(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+use crate::clausal::literal;
+use crate::fasthash::FastHashMap;
+
+// Standard library imports
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LintKind
+////////////////////////////////////////////////////////////////////////////////
+/// A structural problem found by [`Expr::lint`].
+///
+/// [`Expr::lint`]: crate::Expr::lint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A negation of a negation: `!!a`.
+    DoubleNegation,
+    /// An operand repeated in a chain of `And` or `Or` expressions:
+    /// `a & b & a`.
+    DuplicateOperand,
+    /// A subexpression other than a literal repeated anywhere else in the
+    /// expression: `(a | b) & c | (a | b) & d`.
+    DuplicateSubexpr,
+    /// A chain of `And` expressions containing a variable and its negation,
+    /// which is always false: `a & !a`.
+    Contradiction,
+    /// A chain of `Or` expressions containing a variable and its negation,
+    /// which is always true: `a | !a`.
+    Tautology,
+}
+
+impl Display for LintKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintKind::DoubleNegation   => write!(f, "double negation"),
+            LintKind::DuplicateOperand => write!(f, "duplicated operand"),
+            LintKind::DuplicateSubexpr => write!(f,
+                "duplicated subexpression"),
+            LintKind::Contradiction    => write!(f,
+                "conjunction of a variable and its negation is always false"),
+            LintKind::Tautology        => write!(f,
+                "disjunction of a variable and its negation is always true"),
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LintWarning
+////////////////////////////////////////////////////////////////////////////////
+/// A structural problem found by [`Expr::lint`], with its location.
+///
+/// [`Expr::lint`]: crate::Expr::lint
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LintWarning {
+    /// The kind of problem.
+    pub kind: LintKind,
+    /// The path of the subexpression with the problem. For duplicated
+    /// operands and subexpressions, this is the path of the repeat; for
+    /// contradictions and tautologies, it is the path of the chain.
+    pub path: ExprPath,
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {:?}", self.kind, self.path.steps())
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Linting
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: PartialEq + Hash {
+    /// Checks the expression for structural problems which suggest a mistake,
+    /// without evaluating it. Warnings about a chain of `And` or `Or`
+    /// expressions precede the warnings about its operands, and repeated
+    /// subexpressions are reported last, in pre-order.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    /// use simple_predicates::LintKind;
+    ///
+    /// let expr = Expr::from(1).and(!Expr::from(1)).or(Expr::from(2));
+    /// let kinds: Vec<_> = expr.lint().into_iter().map(|w| w.kind).collect();
+    ///
+    /// assert_eq!(kinds, vec![LintKind::Contradiction]);
+    /// ```
+    pub fn lint(&self) -> Vec<LintWarning> {
+        use Expr::*;
+        let mut warnings = Vec::new();
+        let mut warn = |kind, path: &ExprPath| warnings.push(LintWarning {
+            kind,
+            path: path.clone(),
+        });
+
+        // Each entry is a subexpression, its path, and whether it continues
+        // a chain of the same operator as its parent.
+        let mut stack = vec![(self, ExprPath::root(), false)];
+        while let Some((expr, path, in_chain)) = stack.pop() {
+            match expr {
+                Var(_) => (),
+                Not(p) => {
+                    if let Not(_) = **p { warn(LintKind::DoubleNegation, &path); }
+                    stack.push((p, path.child(PathStep::Child), false));
+                },
+                Or(a, b) | And(a, b) => {
+                    if !in_chain { lint_chain(expr, &path, &mut warn); }
+                    let same = |e: &Expr<V>| matches!((expr, e),
+                        (Or(..), Or(..)) | (And(..), And(..)));
+                    stack.push((b, path.child(PathStep::Right), same(b)));
+                    stack.push((a, path.child(PathStep::Left), same(a)));
+                },
+            }
+        }
+        lint_subexprs(self, &mut warnings);
+        warnings
+    }
+}
+
+/// Warns about each subexpression other than a literal which repeats an
+/// earlier one in pre-order, unless it is already reported as a duplicated
+/// operand. The subexpressions of a repeat are not checked.
+///
+/// Every subexpression is hashed bottom-up, ignoring the order of operands as
+/// `PartialEq` does, so only subexpressions with equal hashes are compared.
+fn lint_subexprs<V>(expr: &Expr<V>, warnings: &mut Vec<LintWarning>)
+    where V: PartialEq + Hash
+{
+    use Expr::*;
+    let mut nodes = Vec::new();
+    let mut stack = vec![(expr, ExprPath::root())];
+    while let Some((expr, path)) = stack.pop() {
+        match expr {
+            Var(_)    => (),
+            Not(p)    => stack.push((p, path.child(PathStep::Child))),
+            Or(a, b)  |
+            And(a, b) => {
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+        }
+        nodes.push((expr, path));
+    }
+
+    // The operands of each node follow it in pre-order, so visiting the nodes
+    // in reverse computes the sizes and hashes of the operands first.
+    let mut sizes = vec![1; nodes.len()];
+    let mut hashes = vec![0; nodes.len()];
+    for idx in (0..nodes.len()).rev() {
+        let mut hasher = DefaultHasher::new();
+        std::mem::discriminant(nodes[idx].0).hash(&mut hasher);
+        match nodes[idx].0 {
+            Var(v)    => v.hash(&mut hasher),
+            Not(_)    => {
+                sizes[idx] += sizes[idx + 1];
+                hashes[idx + 1].hash(&mut hasher);
+            },
+            Or(..)    |
+            And(..)   => {
+                let (a, b) = (idx + 1, idx + 1 + sizes[idx + 1]);
+                sizes[idx] += sizes[a] + sizes[b];
+                hashes[a].min(hashes[b]).hash(&mut hasher);
+                hashes[a].max(hashes[b]).hash(&mut hasher);
+            },
+        }
+        hashes[idx] = hasher.finish();
+    }
+
+    let mut seen: FastHashMap<u64, Vec<usize>> = FastHashMap::default();
+    let mut idx = 0;
+    while idx < nodes.len() {
+        let (expr, path) = &nodes[idx];
+        if literal(expr).is_some() {
+            idx += 1;
+            continue;
+        }
+        let earlier = seen.entry(hashes[idx]).or_default();
+        if earlier.iter().any(|&other| nodes[other].0 == *expr) {
+            let reported = warnings.iter().any(|w| w.path == *path
+                && w.kind == LintKind::DuplicateOperand);
+            if !reported {
+                warnings.push(LintWarning {
+                    kind: LintKind::DuplicateSubexpr,
+                    path: path.clone(),
+                });
+            }
+            idx += sizes[idx];
+        } else {
+            earlier.push(idx);
+            idx += 1;
+        }
+    }
+}
+
+/// Checks the operands of the chain of `And` or `Or` expressions rooted at the
+/// given expression.
+fn lint_chain<V, F>(chain: &Expr<V>, path: &ExprPath, warn: &mut F)
+    where
+        V: PartialEq,
+        F: FnMut(LintKind, &ExprPath),
+{
+    use Expr::*;
+    let mut operands = Vec::new();
+    let mut stack = vec![(chain, path.clone())];
+    while let Some((expr, path)) = stack.pop() {
+        match (chain, expr) {
+            (And(..), And(a, b)) |
+            (Or(..), Or(a, b))   => {
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+            _ => operands.push((expr, path)),
+        }
+    }
+
+    let literals: Vec<_> = operands.iter()
+        .filter_map(|(e, _)| literal(e))
+        .collect();
+    let complementary = literals.iter()
+        .any(|&(var, polarity)| literals.contains(&(var, !polarity)));
+    if complementary {
+        let kind = if let And(..) = chain {
+            LintKind::Contradiction
+        } else {
+            LintKind::Tautology
+        };
+        warn(kind, path);
+    }
+
+    for (idx, (operand, path)) in operands.iter().enumerate() {
+        if operands[..idx].iter().any(|(e, _)| e == operand) {
+            warn(LintKind::DuplicateOperand, path);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+use crate::fnv::fnv1a;
+use crate::fnv::FNV_OFFSET_BASIS;
+use crate::stack::guard;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::fmt::Display;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MerkleTree
+////////////////////////////////////////////////////////////////////////////////
+/// The hashes of every subexpression of an expression, arranged in the same
+/// shape as the expression.
+///
+/// The hash of each subexpression covers its operator and the hashes of its
+/// operands, so two trees can be compared from the root down, only
+/// descending into subexpressions whose hashes differ. Like
+/// [`Expr::content_hash`], the hashes are stable across processes and
+/// platforms, but they depend on the order and grouping of operands.
+///
+/// ```rust
+/// use simple_predicates::Expr;
+/// use simple_predicates::ExprPath;
+/// use simple_predicates::PathStep;
+///
+/// let a = Expr::from(1).and(Expr::from(2).or(3)).and(4);
+/// let b = Expr::from(1).and(Expr::from(2).or(5)).and(4);
+///
+/// let diff = a.merkle_tree().diff(&b.merkle_tree());
+/// assert_eq!(diff, vec![ExprPath::from(vec![
+///     PathStep::Left,
+///     PathStep::Right,
+///     PathStep::Right,
+/// ])]);
+/// ```
+///
+/// [`Expr::content_hash`]: crate::Expr::content_hash
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MerkleTree {
+    /// The hash of the subexpression.
+    hash: u64,
+    /// The operator of the subexpression: `v`, `!`, `|`, or `&`.
+    op: u8,
+    /// The trees of the operands of the subexpression.
+    children: Vec<MerkleTree>,
+}
+
+impl MerkleTree {
+    /// Returns the hash of the subexpression.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the trees of the operands of the subexpression.
+    pub fn children(&self) -> &[MerkleTree] {
+        &self.children
+    }
+
+    /// Returns the tree of the subexpression at the given path, or `None` if
+    /// there is no such subexpression.
+    pub fn get_path(&self, path: &ExprPath) -> Option<&MerkleTree> {
+        let mut tree = self;
+        for step in path.steps() {
+            tree = match (step, &tree.children[..]) {
+                (PathStep::Child, [p])    => p,
+                (PathStep::Left,  [a, _]) => a,
+                (PathStep::Right, [_, b]) => b,
+                _                         => return None,
+            };
+        }
+        Some(tree)
+    }
+
+    /// Returns the paths of the outermost subexpressions which differ between
+    /// the trees. Subexpressions with different operators are reported
+    /// without comparing their operands.
+    pub fn diff(&self, other: &MerkleTree) -> Vec<ExprPath> {
+        let mut paths = Vec::new();
+        self.diff_inner(other, &mut ExprPath::root(), &mut paths);
+        paths
+    }
+
+    /// Appends the paths of the differing subexpressions under the given
+    /// path.
+    fn diff_inner(
+        &self,
+        other: &MerkleTree,
+        path: &mut ExprPath,
+        paths: &mut Vec<ExprPath>)
+    {
+        guard(|| {
+            if self.hash == other.hash { return; }
+            if self.op != other.op || self.children.is_empty() {
+                paths.push(path.clone());
+                return;
+            }
+            let steps: &[PathStep] = if self.children.len() == 1 {
+                &[PathStep::Child]
+            } else {
+                &[PathStep::Left, PathStep::Right]
+            };
+            for ((a, b), step) in self.children.iter()
+                .zip(&other.children)
+                .zip(steps)
+            {
+                path.push(*step);
+                a.diff_inner(b, path, paths);
+                let _ = path.pop();
+            }
+        })
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Merkle hashing
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Display {
+    /// Returns the hashes of every subexpression of the expression. Variables
+    /// are hashed by their `Display` output.
+    pub fn merkle_tree(&self) -> MerkleTree {
+        use Expr::*;
+        guard(|| {
+            let (op, children) = match self {
+                Var(v)    => {
+                    let hash = fnv1a(
+                        fnv1a(FNV_OFFSET_BASIS, b"v"),
+                        v.to_string().as_bytes());
+                    return MerkleTree { hash, op: b'v', children: Vec::new() };
+                },
+                Not(p)    => (b'!', vec![p.merkle_tree()]),
+                Or(a, b)  => (b'|', vec![a.merkle_tree(), b.merkle_tree()]),
+                And(a, b) => (b'&', vec![a.merkle_tree(), b.merkle_tree()]),
+            };
+            let hash = children.iter().fold(
+                fnv1a(FNV_OFFSET_BASIS, &[op]),
+                |hash, child| fnv1a(hash, &child.hash.to_le_bytes()));
+            MerkleTree { hash, op, children }
+        })
+    }
+
+    /// Returns the hash of the root of the expression's [`MerkleTree`].
+    ///
+    /// [`MerkleTree`]: crate::MerkleTree
+    pub fn merkle_hash(&self) -> u64 {
+        self.merkle_tree().hash
+    }
+}
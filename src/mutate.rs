@@ -0,0 +1,127 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Expression mutation operators.
+//!
+//! A mutant of an expression is a copy with a single small change, such as an
+//! `And` replaced by an `Or`. Mutants are useful for checking the coverage of
+//! a suite of test cases for a predicate: a suite which cannot distinguish a
+//! mutant from the original is missing a case.
+//!
+//! ```rust
+//! use simple_predicates::Expr;
+//! use simple_predicates::mutate::mutants;
+//!
+//! let expr = Expr::from(1).and(2);
+//! // One operator swap, three negation insertions, and two variable swaps.
+//! assert_eq!(mutants(&expr).len(), 6);
+//! ```
+////////////////////////////////////////////////////////////////////////////////
+
+// Internal library imports.
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+use crate::rng::Rng;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MutationKind
+////////////////////////////////////////////////////////////////////////////////
+/// The kind of change made to produce a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MutationKind {
+    /// An `And` expression was replaced with an `Or`, or vice versa.
+    SwapOperator,
+    /// A subexpression was negated.
+    InsertNegation,
+    /// A `Not` expression was replaced with its operand.
+    RemoveNegation,
+    /// A variable was replaced with another variable of the expression.
+    SwapVariable,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Mutant
+////////////////////////////////////////////////////////////////////////////////
+/// A copy of an expression with a single change applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mutant<V> {
+    /// The kind of change which was made.
+    pub kind: MutationKind,
+    /// The location of the changed subexpression.
+    pub path: ExprPath,
+    /// The mutated expression.
+    pub expr: Expr<V>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Mutant generation
+////////////////////////////////////////////////////////////////////////////////
+/// Returns every mutant of the expression, ordered by the pre-order position
+/// of the changed subexpression.
+///
+/// Negations are not inserted directly around a `Not` expression, as the
+/// result would be equivalent to the original expression.
+pub fn mutants<V>(expr: &Expr<V>) -> Vec<Mutant<V>>
+    where V: Clone + PartialEq
+{
+    use Expr::*;
+    let vars = crate::truth::distinct_vars(std::iter::once(expr));
+
+    let mut mutants = Vec::new();
+    let mut push = |kind, path: &ExprPath, new: Expr<V>| {
+        let mut mutated = expr.clone();
+        let _ = mutated.replace_at(path, new);
+        mutants.push(Mutant { kind, path: path.clone(), expr: mutated });
+    };
+
+    let mut stack = vec![(expr, ExprPath::root())];
+    while let Some((sub, path)) = stack.pop() {
+        match sub {
+            Var(v)    => {
+                push(MutationKind::InsertNegation, &path,
+                    Not(Box::new(sub.clone())));
+                for &w in vars.iter().filter(|w| **w != v) {
+                    push(MutationKind::SwapVariable, &path, Var(w.clone()));
+                }
+            },
+            Not(p)    => {
+                push(MutationKind::RemoveNegation, &path, (**p).clone());
+                stack.push((p, path.child(PathStep::Child)));
+            },
+            And(a, b) => {
+                push(MutationKind::SwapOperator, &path, Or(a.clone(), b.clone()));
+                push(MutationKind::InsertNegation, &path,
+                    Not(Box::new(sub.clone())));
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+            Or(a, b)  => {
+                push(MutationKind::SwapOperator, &path, And(a.clone(), b.clone()));
+                push(MutationKind::InsertNegation, &path,
+                    Not(Box::new(sub.clone())));
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+        }
+    }
+    mutants
+}
+
+/// Returns up to `count` mutants of the expression, chosen pseudo-randomly.
+/// The same seed always selects the same mutants of a given expression.
+pub fn sample_mutants<V>(expr: &Expr<V>, count: usize, seed: u64)
+    -> Vec<Mutant<V>>
+    where V: Clone + PartialEq
+{
+    let mut all = mutants(expr);
+    Rng::new(seed).shuffle(&mut all);
+    all.truncate(count);
+    all
+}
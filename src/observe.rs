@@ -0,0 +1,86 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EvalObserver
+////////////////////////////////////////////////////////////////////////////////
+/// Receives callbacks during an observed evaluation.
+///
+/// All methods have empty default implementations, so implementors only need
+/// to provide the callbacks they are interested in.
+pub trait EvalObserver<V> {
+    /// Called after each variable is evaluated.
+    fn on_var_eval(&mut self, _var: &V, _result: bool) {}
+
+    /// Called after each clause of a normal form is evaluated, with the index
+    /// of the clause in iteration order.
+    fn on_clause_result(&mut self, _clause: usize, _result: bool) {}
+
+    /// Called once the result of the evaluation is known.
+    fn on_complete(&mut self, _result: bool) {}
+}
+
+impl<V> Expr<V> where V: Eval {
+    /// Evaluates the expression, reporting each variable evaluation to the
+    /// given observer.
+    pub fn eval_observed<O>(&self, data: &V::Context, observer: &mut O) -> bool
+        where O: EvalObserver<V>
+    {
+        let result = self.eval_observed_inner(data, observer);
+        observer.on_complete(result);
+        result
+    }
+
+    /// Evaluates the expression without signalling completion.
+    pub (in crate) fn eval_observed_inner<O>(
+        &self,
+        data: &V::Context,
+        observer: &mut O)
+        -> bool
+        where O: EvalObserver<V>
+    {
+        use Expr::*;
+        match self {
+            Var(p) => {
+                let result = p.eval(data);
+                observer.on_var_eval(p, result);
+                result
+            },
+            Not(p)    => !p.eval_observed_inner(data, observer),
+            Or(a, b)  => a.eval_observed_inner(data, observer) ||
+                b.eval_observed_inner(data, observer),
+            And(a, b) => a.eval_observed_inner(data, observer) &&
+                b.eval_observed_inner(data, observer),
+        }
+    }
+}
+
+/// Evaluates the clauses of a normal form, reporting each clause result to
+/// the given observer. If `conjunctive` is true, the clauses are `And`ed
+/// together; otherwise they are `Or`ed together.
+pub (in crate) fn eval_clauses_observed<'a, V, I, O>(
+    clauses: I,
+    conjunctive: bool,
+    data: &V::Context,
+    observer: &mut O)
+    -> bool
+    where
+        V: Eval + 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+        O: EvalObserver<V>,
+{
+    let mut result = conjunctive;
+    for (idx, clause) in clauses.into_iter().enumerate() {
+        let clause_result = clause.eval_observed_inner(data, observer);
+        observer.on_clause_result(idx, clause_result);
+        if clause_result != conjunctive {
+            result = clause_result;
+            break;
+        }
+    }
+    observer.on_complete(result);
+    result
+}
@@ -0,0 +1,622 @@
+
+// Internal library imports.
+use crate::Expr;
+
+// Standard library imports
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParseErrorKind
+////////////////////////////////////////////////////////////////////////////////
+/// The reason an expression could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// The input ended where an operand was expected.
+    UnexpectedEnd,
+    /// A token was found where it is not allowed.
+    UnexpectedToken(String),
+    /// A `(` was not closed.
+    UnclosedParen,
+    /// A `)` has no matching `(`.
+    UnmatchedParen,
+    /// A quoted variable is missing its closing `"`.
+    UnterminatedQuote,
+    /// The expression is deeper than the maximum depth.
+    TooDeep {
+        /// The maximum depth.
+        limit: usize,
+    },
+    /// A variable could not be parsed with `FromStr`.
+    InvalidVar {
+        /// The text of the variable.
+        text: String,
+        /// The `Display` output of the `FromStr` error.
+        message: String,
+    },
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParseError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that an expression could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The byte offset in the input at which the error was found.
+    pub offset: usize,
+    /// The reason the expression could not be parsed.
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ParseErrorKind::UnexpectedEnd => write!(f,
+                "unexpected end of input"),
+            ParseErrorKind::UnexpectedToken(token) => write!(f,
+                "unexpected `{}`", token),
+            ParseErrorKind::UnclosedParen => write!(f,
+                "unclosed `(`"),
+            ParseErrorKind::UnmatchedParen => write!(f,
+                "unmatched `)`"),
+            ParseErrorKind::UnterminatedQuote => write!(f,
+                "unterminated quoted variable"),
+            ParseErrorKind::TooDeep { limit } => write!(f,
+                "expression is deeper than the maximum depth {}", limit),
+            ParseErrorKind::InvalidVar { text, message } => write!(f,
+                "invalid variable {:?}: {}", text, message),
+        }?;
+        write!(f, " at offset {}", self.offset)
+    }
+}
+
+impl Error for ParseError {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParserDialect
+////////////////////////////////////////////////////////////////////////////////
+/// The precedence of implication relative to disjunction. Implication always
+/// binds more loosely than conjunction, and groups to the right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImpliesPrecedence {
+    /// Implication binds more loosely than disjunction, so `a | b -> c` is
+    /// parsed as `(a | b) -> c`.
+    BelowOr,
+    /// Implication binds more tightly than disjunction, so `a | b -> c` is
+    /// parsed as `a | (b -> c)`.
+    AboveOr,
+}
+
+/// The symbols and keywords of an operator in a [`ParserDialect`].
+///
+/// [`ParserDialect`]: crate::ParserDialect
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct OperatorSyntax {
+    /// The symbols of the operator, such as `&&`. A symbol ends any unquoted
+    /// variable before it, and the longest matching symbol is used.
+    pub symbols: Vec<String>,
+    /// The keywords of the operator, such as `AND`. A keyword is only
+    /// recognized as a whole unquoted variable.
+    pub keywords: Vec<String>,
+}
+
+impl OperatorSyntax {
+    /// Constructs a new `OperatorSyntax` with the given symbols and keywords.
+    pub fn new(symbols: &[&str], keywords: &[&str]) -> Self {
+        OperatorSyntax {
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            keywords: keywords.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// The operator syntax accepted by [`Expr::parse_with_dialect`].
+///
+/// Parentheses and double-quoted variables are part of every dialect. The
+/// default dialect is the syntax accepted by [`Expr::parse`].
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// # //-------------------------------------------------------------------
+/// use simple_predicates::Expr;
+/// use simple_predicates::ParserDialect;
+///
+/// let sql: Expr<String> = Expr::parse_with_dialect(
+///     "a and not (b OR c)",
+///     &ParserDialect::sql())?;
+/// let lucene: Expr<String> = Expr::parse_with_dialect(
+///     "a AND -(b || c)",
+///     &ParserDialect::lucene())?;
+///
+/// assert_eq!(sql, lucene);
+/// # //-------------------------------------------------------------------
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Expr::parse`]: crate::Expr::parse
+/// [`Expr::parse_with_dialect`]: crate::Expr::parse_with_dialect
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParserDialect {
+    /// The syntax of negation.
+    pub not: OperatorSyntax,
+    /// The syntax of conjunction.
+    pub and: OperatorSyntax,
+    /// The syntax of disjunction.
+    pub or: OperatorSyntax,
+    /// The syntax of implication, which is parsed as the disjunction of its
+    /// negated left operand and its right operand.
+    pub implies: OperatorSyntax,
+    /// The precedence of implication.
+    pub implies_precedence: ImpliesPrecedence,
+    /// Whether keywords are matched with the same case. Otherwise they are
+    /// matched ignoring ASCII case, independent of the locale.
+    pub case_sensitive_keywords: bool,
+    /// Whether a `-` at the start of an operand negates it, as in `-a`.
+    /// Unlike a symbol, a `-` does not end a variable, so `a-b` is a single
+    /// variable.
+    pub minus_is_not: bool,
+}
+
+impl ParserDialect {
+    /// Returns a dialect with the operators of C: `!`, `&&`, and `||`.
+    pub fn c_like() -> Self {
+        ParserDialect {
+            not: OperatorSyntax::new(&["!"], &[]),
+            and: OperatorSyntax::new(&["&&"], &[]),
+            or: OperatorSyntax::new(&["||"], &[]),
+            ..ParserDialect::default()
+        }
+    }
+
+    /// Returns a dialect resembling the Lucene query syntax, with the
+    /// uppercase keywords `NOT`, `AND`, and `OR`, the symbols `!`, `&&`, and
+    /// `||`, and `-` for negation.
+    pub fn lucene() -> Self {
+        ParserDialect {
+            not: OperatorSyntax::new(&["!"], &["NOT"]),
+            and: OperatorSyntax::new(&["&&"], &["AND"]),
+            or: OperatorSyntax::new(&["||"], &["OR"]),
+            case_sensitive_keywords: true,
+            minus_is_not: true,
+            ..ParserDialect::default()
+        }
+    }
+
+    /// Returns a dialect resembling SQL, with the keywords `NOT`, `AND`, and
+    /// `OR` in any case, and no symbols.
+    pub fn sql() -> Self {
+        ParserDialect {
+            not: OperatorSyntax::new(&[], &["NOT"]),
+            and: OperatorSyntax::new(&[], &["AND"]),
+            or: OperatorSyntax::new(&[], &["OR"]),
+            case_sensitive_keywords: false,
+            ..ParserDialect::default()
+        }
+    }
+
+    /// Returns the operators of the dialect with their syntax.
+    fn operators(&self) -> [(&OperatorSyntax, Token); 4] {
+        [
+            (&self.not, Token::Not),
+            (&self.and, Token::And),
+            (&self.or, Token::Or),
+            (&self.implies, Token::Implies),
+        ]
+    }
+
+    /// Returns the length and token of the longest symbol at the start of the
+    /// text.
+    fn symbol(&self, text: &str) -> Option<(usize, Token)> {
+        let mut longest = None;
+        for (syntax, token) in self.operators().iter() {
+            for symbol in &syntax.symbols {
+                if symbol.is_empty() || !text.starts_with(symbol.as_str()) {
+                    continue;
+                }
+                match longest {
+                    Some((len, _)) if len >= symbol.len() => (),
+                    _ => longest = Some((symbol.len(), token.clone())),
+                }
+            }
+        }
+        longest
+    }
+
+    /// Returns the token of the keyword, if the text is a keyword.
+    fn keyword(&self, text: &str) -> Option<Token> {
+        self.operators().iter()
+            .find(|(syntax, _)| syntax.keywords.iter()
+                .any(|keyword| if self.case_sensitive_keywords {
+                    keyword == text
+                } else {
+                    keyword.eq_ignore_ascii_case(text)
+                }))
+            .map(|(_, token)| token.clone())
+    }
+}
+
+impl Default for ParserDialect {
+    fn default() -> Self {
+        ParserDialect {
+            not: OperatorSyntax::new(&["!"], &[]),
+            and: OperatorSyntax::new(&["&", "&&"], &[]),
+            or: OperatorSyntax::new(&["|", "||"], &[]),
+            implies: OperatorSyntax::default(),
+            implies_precedence: ImpliesPrecedence::BelowOr,
+            case_sensitive_keywords: true,
+            minus_is_not: false,
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsing
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: FromStr, V::Err: Display {
+    /// Parses an expression from text.
+    ///
+    /// Expressions are written with `!` for negation, `&` or `&&` for
+    /// conjunction, and `|` or `||` for disjunction, in decreasing order of
+    /// precedence, and may be grouped with parentheses. Conjunctions and
+    /// disjunctions group to the left.
+    ///
+    /// A variable is any run of characters other than whitespace and
+    /// `!&|()"`, or any text in double quotes, in which `"` and `\` are
+    /// escaped by a preceding `\`. The text of each variable is parsed with
+    /// `FromStr`:
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # //-------------------------------------------------------------------
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr: Expr<String> = Expr::parse(r#"a & !(b || "c d")"#)?;
+    ///
+    /// assert_eq!(expr, Expr::from("a".to_string())
+    ///     .and(!Expr::from("b".to_string()).or("c d".to_string())));
+    /// # //-------------------------------------------------------------------
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Parsing does not recurse, but other operations on a deeply nested
+    /// result may. Use [`Expr::parse_with_max_depth`] to reject such input
+    /// before it is built.
+    ///
+    /// [`Expr::parse_with_max_depth`]: crate::Expr::parse_with_max_depth
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        Expr::parse_with_dialect(src, &ParserDialect::default())
+    }
+
+    /// Parses an expression from text as described by [`Expr::parse`],
+    /// returning an error as soon as the expression is known to be deeper than
+    /// the given maximum depth. A lone variable has depth 1.
+    ///
+    /// No part of an expression deeper than the maximum depth is ever built,
+    /// so the result can be checked and dropped without growing the stack.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    /// use simple_predicates::ParseErrorKind;
+    ///
+    /// let expr = Expr::<u32>::parse_with_max_depth("!!1", 2);
+    ///
+    /// assert_eq!(expr.unwrap_err().kind, ParseErrorKind::TooDeep { limit: 2 });
+    /// ```
+    ///
+    /// [`Expr::parse`]: crate::Expr::parse
+    pub fn parse_with_max_depth(src: &str, max_depth: usize)
+        -> Result<Self, ParseError>
+    {
+        Parser::new(src, &ParserDialect::default())
+            .with_max_depth(max_depth)
+            .parse()
+    }
+
+    /// Parses an expression from text written with the operators of the given
+    /// dialect, as described by [`Expr::parse`].
+    ///
+    /// [`Expr::parse`]: crate::Expr::parse
+    pub fn parse_with_dialect(src: &str, dialect: &ParserDialect)
+        -> Result<Self, ParseError>
+    {
+        Parser::new(src, dialect).parse()
+    }
+}
+
+impl<V> FromStr for Expr<V> where V: FromStr, V::Err: Display {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Expr::parse(src)
+    }
+}
+
+/// A lexical token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Var(String),
+    Not,
+    And,
+    Or,
+    Implies,
+    Open,
+    Close,
+}
+
+/// A pending operator of the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Not,
+    And,
+    Or,
+    Implies,
+    /// An open parenthesis, with its offset.
+    Open(usize),
+}
+
+impl Op {
+    /// Returns the binding precedence of the operator.
+    fn precedence(self, dialect: &ParserDialect) -> u8 {
+        match self {
+            Op::Open(_) => 0,
+            Op::Implies => match dialect.implies_precedence {
+                ImpliesPrecedence::BelowOr => 1,
+                ImpliesPrecedence::AboveOr => 3,
+            },
+            Op::Or      => 2,
+            Op::And     => 4,
+            Op::Not     => 5,
+        }
+    }
+}
+
+/// A shunting-yard expression parser.
+#[derive(Debug)]
+struct Parser<'s, V> {
+    /// The input text.
+    src: &'s str,
+    /// The operator syntax of the input.
+    dialect: &'s ParserDialect,
+    /// The byte offset of the next character.
+    pos: usize,
+    /// The parsed operands, with their depths.
+    operands: Vec<(Expr<V>, usize)>,
+    /// The pending operators.
+    ops: Vec<Op>,
+    /// The number of pending operators other than parentheses.
+    pending: usize,
+    /// The maximum depth of the expression.
+    max_depth: Option<usize>,
+}
+
+impl<'s, V> Parser<'s, V> where V: FromStr, V::Err: Display {
+    /// Constructs a new `Parser` for the given text.
+    fn new(src: &'s str, dialect: &'s ParserDialect) -> Self {
+        Parser {
+            src,
+            dialect,
+            pos: 0,
+            operands: Vec::new(),
+            ops: Vec::new(),
+            pending: 0,
+            max_depth: None,
+        }
+    }
+
+    /// Sets the maximum depth of the parsed expression.
+    fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Returns an error at the given offset if the depth exceeds the maximum
+    /// depth.
+    fn check_depth(&self, depth: usize, offset: usize)
+        -> Result<(), ParseError>
+    {
+        match self.max_depth {
+            Some(limit) if depth > limit => Err(ParseError {
+                offset,
+                kind: ParseErrorKind::TooDeep { limit },
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Pushes a pending operator other than a parenthesis. Each pending
+    /// operator will be an ancestor of the next operand, so the expression is
+    /// too deep if there are too many of them.
+    fn push_op(&mut self, op: Op, offset: usize) -> Result<(), ParseError> {
+        self.pending += 1;
+        self.check_depth(self.pending + 1, offset)?;
+        self.ops.push(op);
+        Ok(())
+    }
+
+    /// Parses the text as a single expression.
+    fn parse(mut self) -> Result<Expr<V>, ParseError> {
+        let src = self.src;
+        let mut expect_operand = true;
+        while let Some((offset, token)) = self.next_token()? {
+            let end = self.pos;
+            let unexpected = || Err(ParseError {
+                offset,
+                kind: ParseErrorKind::UnexpectedToken(
+                    src[offset..end].to_string()),
+            });
+            match token {
+                Token::Var(text) => {
+                    if !expect_operand { return unexpected(); }
+                    let var = text.parse::<V>().map_err(|e| ParseError {
+                        offset,
+                        kind: ParseErrorKind::InvalidVar {
+                            message: e.to_string(),
+                            text,
+                        },
+                    })?;
+                    self.operands.push((Expr::Var(var), 1));
+                    expect_operand = false;
+                },
+                Token::Not => {
+                    if !expect_operand { return unexpected(); }
+                    self.push_op(Op::Not, offset)?;
+                },
+                Token::Open => {
+                    if !expect_operand { return unexpected(); }
+                    self.ops.push(Op::Open(offset));
+                },
+                Token::Close => {
+                    if expect_operand { return unexpected(); }
+                    loop {
+                        match self.ops.pop() {
+                            Some(Op::Open(_)) => break,
+                            Some(op)          => self.apply(op, offset)?,
+                            None              => return Err(ParseError {
+                                offset,
+                                kind: ParseErrorKind::UnmatchedParen,
+                            }),
+                        }
+                    }
+                },
+                Token::And | Token::Or | Token::Implies => {
+                    if expect_operand { return unexpected(); }
+                    let op = match token {
+                        Token::And => Op::And,
+                        Token::Or  => Op::Or,
+                        _          => Op::Implies,
+                    };
+                    // Implication groups to the right, and the other binary
+                    // operators group to the left.
+                    let precedence = op.precedence(self.dialect);
+                    while let Some(&top) = self.ops.last() {
+                        let top_precedence = top.precedence(self.dialect);
+                        if top_precedence < precedence
+                            || (top_precedence == precedence
+                                && op == Op::Implies)
+                        {
+                            break;
+                        }
+                        let _ = self.ops.pop();
+                        self.apply(top, offset)?;
+                    }
+                    self.push_op(op, offset)?;
+                    expect_operand = true;
+                },
+            }
+        }
+
+        if expect_operand {
+            return Err(ParseError {
+                offset: self.src.len(),
+                kind: ParseErrorKind::UnexpectedEnd,
+            });
+        }
+        while let Some(op) = self.ops.pop() {
+            if let Op::Open(offset) = op {
+                return Err(ParseError {
+                    offset,
+                    kind: ParseErrorKind::UnclosedParen,
+                });
+            }
+            self.apply(op, self.src.len())?;
+        }
+        Ok(self.operands.pop().expect("parsed expression").0)
+    }
+
+    /// Applies an operator to the topmost operands, returning an error at the
+    /// given offset if the result would be too deep.
+    fn apply(&mut self, op: Op, offset: usize) -> Result<(), ParseError> {
+        self.pending -= 1;
+        let (b, b_depth) = self.operands.pop().expect("operand");
+        let b = Box::new(b);
+        if op == Op::Not {
+            self.check_depth(b_depth + 1, offset)?;
+            self.operands.push((Expr::Not(b), b_depth + 1));
+            return Ok(());
+        }
+
+        let (a, a_depth) = self.operands.pop().expect("left operand");
+        // The left operand of an implication is negated.
+        let a_depth = if op == Op::Implies { a_depth + 1 } else { a_depth };
+        let depth = a_depth.max(b_depth) + 1;
+        self.check_depth(depth, offset)?;
+        let expr = match op {
+            Op::And     => Expr::And(Box::new(a), b),
+            Op::Or      => Expr::Or(Box::new(a), b),
+            Op::Implies => Expr::Or(Box::new(Expr::Not(Box::new(a))), b),
+            Op::Not     |
+            Op::Open(_) => unreachable!("operator is not binary"),
+        };
+        self.operands.push((expr, depth));
+        Ok(())
+    }
+
+    /// Returns the next token and its offset, or `None` at the end of the
+    /// input.
+    fn next_token(&mut self) -> Result<Option<(usize, Token)>, ParseError> {
+        let rest = &self.src[self.pos..];
+        let trimmed = rest.trim_start();
+        self.pos += rest.len() - trimmed.len();
+        let start = self.pos;
+
+        let first = match trimmed.chars().next() {
+            Some(c) => c,
+            None    => return Ok(None),
+        };
+        if let Some((len, token)) = self.dialect.symbol(trimmed) {
+            self.pos += len;
+            return Ok(Some((start, token)));
+        }
+        let token = match first {
+            '(' => { self.pos += 1; Token::Open },
+            ')' => { self.pos += 1; Token::Close },
+            '-' if self.dialect.minus_is_not => { self.pos += 1; Token::Not },
+            '"' => {
+                let mut text = String::new();
+                let mut escaped = false;
+                let mut len = None;
+                for (idx, c) in trimmed.char_indices().skip(1) {
+                    match (escaped, c) {
+                        (false, '\\') => escaped = true,
+                        (false, '"')  => { len = Some(idx + 1); break; },
+                        _             => { text.push(c); escaped = false; },
+                    }
+                }
+                match len {
+                    Some(len) => self.pos += len,
+                    None      => return Err(ParseError {
+                        offset: start,
+                        kind: ParseErrorKind::UnterminatedQuote,
+                    }),
+                }
+                Token::Var(text)
+            },
+            _   => {
+                let len = trimmed.char_indices()
+                    .skip(1)
+                    .find(|&(idx, c)| c.is_whitespace()
+                        || "()\"".contains(c)
+                        || self.dialect.symbol(&trimmed[idx..]).is_some())
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(trimmed.len());
+                self.pos += len;
+                let text = &trimmed[..len];
+                match self.dialect.keyword(text) {
+                    Some(token) => token,
+                    None        => Token::Var(text.to_string()),
+                }
+            },
+        };
+        Ok(Some((start, token)))
+    }
+}
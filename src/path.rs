@@ -0,0 +1,142 @@
+
+// Internal library imports.
+use crate::Expr;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::iter::FromIterator;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PathStep
+////////////////////////////////////////////////////////////////////////////////
+/// A single step from an expression to one of its operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PathStep {
+    /// The left operand of an `And` or `Or` expression.
+    Left,
+    /// The right operand of an `And` or `Or` expression.
+    Right,
+    /// The operand of a `Not` expression.
+    Child,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ExprPath
+////////////////////////////////////////////////////////////////////////////////
+/// The location of a subexpression within an [`Expr`], given as a sequence of
+/// steps from the root. The empty path refers to the root itself.
+///
+/// [`Expr`]: crate::Expr
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ExprPath(Vec<PathStep>);
+
+impl ExprPath {
+    /// Returns the path to the root expression.
+    pub fn root() -> Self {
+        ExprPath(Vec::new())
+    }
+
+    /// Returns the steps of the path.
+    pub fn steps(&self) -> &[PathStep] {
+        &self.0
+    }
+
+    /// Appends a step to the end of the path.
+    pub fn push(&mut self, step: PathStep) {
+        self.0.push(step);
+    }
+
+    /// Removes the last step of the path, returning it.
+    pub fn pop(&mut self) -> Option<PathStep> {
+        self.0.pop()
+    }
+
+    /// Returns the path extended by the given step.
+    pub fn child(&self, step: PathStep) -> Self {
+        let mut path = self.clone();
+        path.push(step);
+        path
+    }
+
+    /// Returns the number of steps in the path.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the path refers to the root expression.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<PathStep>> for ExprPath {
+    fn from(steps: Vec<PathStep>) -> Self {
+        ExprPath(steps)
+    }
+}
+
+impl FromIterator<PathStep> for ExprPath {
+    fn from_iter<I>(iter: I) -> Self
+        where I: IntoIterator<Item=PathStep>
+    {
+        ExprPath(iter.into_iter().collect())
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Path access
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> {
+    /// Returns a reference to the subexpression at the given path, or `None`
+    /// if the path does not exist in the expression.
+    pub fn get_path(&self, path: &ExprPath) -> Option<&Expr<V>> {
+        use Expr::*;
+        use PathStep::*;
+        let mut expr = self;
+        for step in path.steps() {
+            expr = match (expr, step) {
+                (Not(p), Child)                        => p,
+                (Or(a, _), Left)  | (And(a, _), Left)  => a,
+                (Or(_, b), Right) | (And(_, b), Right) => b,
+                _ => return None,
+            };
+        }
+        Some(expr)
+    }
+
+    /// Returns a mutable reference to the subexpression at the given path, or
+    /// `None` if the path does not exist in the expression.
+    pub fn get_path_mut(&mut self, path: &ExprPath) -> Option<&mut Expr<V>> {
+        use Expr::*;
+        use PathStep::*;
+        let mut expr = self;
+        for step in path.steps() {
+            expr = match (expr, step) {
+                (Not(p), Child)                        => p,
+                (Or(a, _), Left)  | (And(a, _), Left)  => a,
+                (Or(_, b), Right) | (And(_, b), Right) => b,
+                _ => return None,
+            };
+        }
+        Some(expr)
+    }
+
+    /// Replaces the subexpression at the given path, returning the replaced
+    /// subexpression. Returns `None` and leaves the expression unchanged if the
+    /// path does not exist in the expression.
+    pub fn replace_at(&mut self, path: &ExprPath, new: Expr<V>)
+        -> Option<Expr<V>>
+    {
+        self.get_path_mut(path)
+            .map(|expr| std::mem::replace(expr, new))
+    }
+}
@@ -0,0 +1,168 @@
+
+// Internal library imports.
+use crate::CnfVec;
+use crate::DnfVec;
+use crate::Eval;
+use crate::IncrementalSolver;
+use crate::truth::distinct_vars;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Decision
+////////////////////////////////////////////////////////////////////////////////
+/// The result of evaluating a [`Policy`].
+///
+/// [`Policy`]: crate::Policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Decision {
+    /// The allow rules matched and the deny rules did not.
+    Allow,
+    /// The deny rules matched.
+    Deny,
+    /// Neither the allow rules nor the deny rules matched.
+    NotApplicable,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Policy
+////////////////////////////////////////////////////////////////////////////////
+/// An access policy combining a set of allow rules and a set of deny rules.
+///
+/// Each rule set is a [`DnfVec`], so the policy allows (or denies) if any of
+/// its allow (or deny) clauses are satisfied. Deny rules take precedence over
+/// allow rules.
+///
+/// [`DnfVec`]: crate::DnfVec
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Policy<V> where V: Eval + PartialEq {
+    /// The rules which allow access.
+    allow: DnfVec<V>,
+    /// The rules which deny access.
+    deny: DnfVec<V>,
+}
+
+impl<V> Policy<V> where V: Eval + PartialEq {
+    /// Constructs a new `Policy` from the given allow and deny rules.
+    pub fn new(allow: DnfVec<V>, deny: DnfVec<V>) -> Self {
+        Policy { allow, deny }
+    }
+
+    /// Returns a reference to the allow rules.
+    pub fn allow(&self) -> &DnfVec<V> {
+        &self.allow
+    }
+
+    /// Returns a reference to the deny rules.
+    pub fn deny(&self) -> &DnfVec<V> {
+        &self.deny
+    }
+
+    /// Evaluates the policy in the given context. If any deny rule matches,
+    /// the result is `Deny`, regardless of the allow rules.
+    pub fn evaluate(&self, data: &V::Context) -> Decision {
+        if self.deny.eval(data) {
+            Decision::Deny
+        } else if self.allow.eval(data) {
+            Decision::Allow
+        } else {
+            Decision::NotApplicable
+        }
+    }
+
+    /// Returns an assignment of the variables of the policy which matches
+    /// both an allow rule and a deny rule, or `None` if the rules do not
+    /// overlap. See [`conflicts`].
+    ///
+    /// [`conflicts`]: crate::conflicts
+    pub fn conflicts(&self) -> Option<HashMap<V, bool>>
+        where V: Eq + Hash + Clone
+    {
+        conflicts(&self.allow, &self.deny)
+    }
+}
+
+impl<V> PartialEq for Policy<V> where V: Eval + PartialEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.allow == other.allow && self.deny == other.deny
+    }
+}
+
+impl<V> Default for Policy<V> where V: Eval + PartialEq {
+    fn default() -> Self {
+        Policy {
+            allow: DnfVec::default(),
+            deny: DnfVec::default(),
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Conflict detection
+////////////////////////////////////////////////////////////////////////////////
+/// Returns an assignment of the variables of both rule sets which matches a
+/// rule of each, or `None` if no context can match both.
+///
+/// Each pair of rules is converted into conjunctive normal form and solved in
+/// turn with an [`IncrementalSolver`], which may take time exponential in the
+/// number of variables of the pair. Variables which do not affect the matched
+/// rules are assigned false.
+///
+/// [`IncrementalSolver`]: crate::IncrementalSolver
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// use simple_predicates::conflicts;
+/// use simple_predicates::DnfVec;
+/// use simple_predicates::Expr;
+/// use Expr::Var;
+///
+/// let allow = DnfVec::from(Var(Contains(1)).and(Var(Contains(2))));
+/// let deny = DnfVec::from(Var(Contains(3))
+///     .or(Var(Contains(2)).and(!Var(Contains(1)))));
+/// let model = conflicts(&allow, &deny).unwrap();
+/// assert!(model[&Contains(1)] && model[&Contains(2)] && model[&Contains(3)]);
+///
+/// let deny = DnfVec::from(!Var(Contains(2)));
+/// assert_eq!(conflicts(&allow, &deny), None);
+/// ```
+pub fn conflicts<V>(allow: &DnfVec<V>, deny: &DnfVec<V>)
+    -> Option<HashMap<V, bool>>
+    where V: Eval + Eq + Hash + Clone
+{
+    for a in allow.as_ref() {
+        for d in deny.as_ref() {
+            let cnf = CnfVec::from(a.clone().and(d.clone()));
+            let mut solver = IncrementalSolver::new(&cnf)
+                .expect("normal form clauses");
+            if let Some(mut model) = solver.solve() {
+                for var in distinct_vars(allow.as_ref().iter()
+                    .chain(deny.as_ref()))
+                {
+                    let _ = model.entry(var.clone()).or_insert(false);
+                }
+                return Some(model);
+            }
+        }
+    }
+    None
+}
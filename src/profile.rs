@@ -0,0 +1,159 @@
+
+// Internal library imports.
+use crate::EvalObserver;
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// VarStats
+////////////////////////////////////////////////////////////////////////////////
+/// Observed evaluation counts for a single variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct VarStats {
+    /// The number of times the variable was evaluated.
+    pub evals: u64,
+    /// The number of evaluations which returned true.
+    pub trues: u64,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EvalStats
+////////////////////////////////////////////////////////////////////////////////
+/// An evaluation profile recording how often each variable is true, and
+/// optionally how expensive it is to evaluate.
+///
+/// `EvalStats` implements [`EvalObserver`], so it can be populated by
+/// observed evaluations of a representative workload, and then used by
+/// [`Expr::optimize_with_profile`].
+///
+/// [`EvalObserver`]: crate::EvalObserver
+/// [`Expr::optimize_with_profile`]: crate::Expr::optimize_with_profile
+#[derive(Debug, Clone)]
+pub struct EvalStats<V> {
+    /// The evaluation counts of each variable.
+    vars: HashMap<V, VarStats>,
+    /// The relative cost of evaluating each variable.
+    costs: HashMap<V, f64>,
+}
+
+impl<V> EvalStats<V> where V: Eq + Hash {
+    /// Constructs a new `EvalStats` with no observations.
+    pub fn new() -> Self {
+        EvalStats { vars: HashMap::new(), costs: HashMap::new() }
+    }
+
+    /// Records an evaluation of the variable.
+    pub fn record(&mut self, var: V, result: bool) {
+        let stats = self.vars.entry(var).or_default();
+        stats.evals += 1;
+        if result { stats.trues += 1; }
+    }
+
+    /// Sets the relative cost of evaluating the variable. Variables without a
+    /// cost have a cost of 1.
+    pub fn set_cost(&mut self, var: V, cost: f64) {
+        let _ = self.costs.insert(var, cost);
+    }
+
+    /// Returns the evaluation counts of the variable, if it has been
+    /// observed.
+    pub fn var_stats(&self, var: &V) -> Option<&VarStats> {
+        self.vars.get(var)
+    }
+
+    /// Returns the observed probability of the variable being true, or 0.5 if
+    /// it has not been observed.
+    pub fn selectivity(&self, var: &V) -> f64 {
+        match self.vars.get(var) {
+            Some(stats) if stats.evals > 0 => {
+                stats.trues as f64 / stats.evals as f64
+            },
+            _ => 0.5,
+        }
+    }
+
+    /// Returns the relative cost of evaluating the variable.
+    pub fn cost(&self, var: &V) -> f64 {
+        self.costs.get(var).copied().unwrap_or(1.0)
+    }
+}
+
+impl<V> Default for EvalStats<V> where V: Eq + Hash {
+    fn default() -> Self {
+        EvalStats::new()
+    }
+}
+
+impl<V> EvalObserver<V> for EvalStats<V> where V: Eq + Hash + Clone {
+    fn on_var_eval(&mut self, var: &V, result: bool) {
+        self.record(var.clone(), result);
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Profile-guided optimization
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Returns an equivalent expression whose chains of `And` and `Or`
+    /// operands are reordered using the given profile, so that evaluation
+    /// short-circuits as early and cheaply as possible.
+    ///
+    /// Operands are assumed to be independent. `And` operands are ordered by
+    /// increasing ratio of expected cost to probability of being false, and
+    /// `Or` operands by increasing ratio of expected cost to probability of
+    /// being true, which minimizes the expected cost of evaluating each
+    /// chain. Operands with equal ratios keep their relative order.
+    pub fn optimize_with_profile(&self, stats: &EvalStats<V>) -> Expr<V> {
+        self.optimize_inner(stats).0
+    }
+
+    /// Optimizes the expression, returning it along with its probability of
+    /// being true and its expected evaluation cost.
+    fn optimize_inner(&self, stats: &EvalStats<V>) -> (Expr<V>, f64, f64) {
+        use Expr::*;
+        guard(|| match self {
+            Var(v) => (Var(v.clone()), stats.selectivity(v), stats.cost(v)),
+            Not(p) => {
+                let (p, prob, cost) = p.optimize_inner(stats);
+                (Not(Box::new(p)), 1.0 - prob, cost)
+            },
+            Or(..) | And(..) => {
+                let conjunctive = matches!(self, And(..));
+                let mut operands: Vec<_> = self.operands()
+                    .into_iter()
+                    .map(|operand| operand.optimize_inner(stats))
+                    .collect();
+                // The probability of an operand continuing the chain.
+                let cont = |prob: f64| if conjunctive { prob } else { 1.0 - prob };
+                operands.sort_by(|a, b| {
+                    let rank = |(_, prob, cost): &(Expr<V>, f64, f64)|
+                        cost / (1.0 - cont(*prob));
+                    rank(a).total_cmp(&rank(b))
+                });
+
+                let mut reach = 1.0;
+                let mut expected = 0.0;
+                let mut exprs = operands.into_iter().map(|(e, prob, cost)| {
+                    expected += reach * cost;
+                    reach *= cont(prob);
+                    e
+                });
+                let first = exprs.next().expect("chain has operands");
+                let expr = exprs.fold(first, |acc, e| if conjunctive {
+                    acc.and(e)
+                } else {
+                    acc.or(e)
+                });
+                let prob = if conjunctive { reach } else { 1.0 - reach };
+                (expr, prob, expected)
+            },
+        })
+    }
+}
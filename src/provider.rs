@@ -0,0 +1,108 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::fasthash::FastHashMap;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// VarProvider
+////////////////////////////////////////////////////////////////////////////////
+/// Provides the values of variables on demand during evaluation.
+///
+/// This is implemented for any `FnMut(&V) -> bool`, so closures can be used
+/// directly as providers.
+pub trait VarProvider<V> {
+    /// Returns the truth value of the given variable.
+    fn value(&mut self, var: &V) -> bool;
+}
+
+impl<V, F> VarProvider<V> for F where F: FnMut(&V) -> bool {
+    fn value(&mut self, var: &V) -> bool {
+        (self)(var)
+    }
+}
+
+impl<V> Expr<V> where V: Eq + Hash {
+    /// Evaluates the expression, querying the provider for the value of each
+    /// variable reached.
+    ///
+    /// Variables skipped by short-circuiting are never queried, and each
+    /// distinct variable is queried at most once per evaluation.
+    pub fn eval_with_provider<P>(&self, provider: &mut P) -> bool
+        where P: VarProvider<V>
+    {
+        let mut cache = FastHashMap::default();
+        self.eval_cached(provider, &mut cache)
+    }
+
+    /// Evaluates the expression, caching the provided variable values.
+    fn eval_cached<'a, P>(
+        &'a self,
+        provider: &mut P,
+        cache: &mut FastHashMap<&'a V, bool>)
+        -> bool
+        where P: VarProvider<V>
+    {
+        use Expr::*;
+        match self {
+            Var(p) => *cache.entry(p).or_insert_with(|| provider.value(p)),
+            Not(p)    => !p.eval_cached(provider, cache),
+            Or(a, b)  => a.eval_cached(provider, cache) ||
+                b.eval_cached(provider, cache),
+            And(a, b) => a.eval_cached(provider, cache) &&
+                b.eval_cached(provider, cache),
+        }
+    }
+}
+
+impl<V> Expr<V> where V: Eval + Eq + Hash {
+    /// Evaluates the expression, using the given values for the overridden
+    /// variables instead of evaluating them in the context.
+    ///
+    /// ```rust
+    /// # use simple_predicates::Eval;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::Expr;
+    /// use std::collections::HashMap;
+    ///
+    /// let expr = Expr::Var(Contains(1)).and(!Expr::Var(Contains(2)));
+    /// let data = vec![1, 2];
+    /// let mut overrides = HashMap::new();
+    /// overrides.insert(Contains(2), false);
+    ///
+    /// assert!(!expr.eval(&data));
+    /// assert!(expr.eval_with_overrides(&data, &overrides));
+    /// ```
+    pub fn eval_with_overrides(
+        &self,
+        data: &V::Context,
+        overrides: &HashMap<V, bool>)
+        -> bool
+    {
+        use Expr::*;
+        guard(|| match self {
+            Var(p) => match overrides.get(p) {
+                Some(value) => *value,
+                None        => p.eval(data),
+            },
+            Not(p)    => !p.eval_with_overrides(data, overrides),
+            Or(a, b)  => a.eval_with_overrides(data, overrides) ||
+                b.eval_with_overrides(data, overrides),
+            And(a, b) => a.eval_with_overrides(data, overrides) &&
+                b.eval_with_overrides(data, overrides),
+        })
+    }
+}
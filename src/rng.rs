@@ -0,0 +1,47 @@
+
+////////////////////////////////////////////////////////////////////////////////
+// Rng
+////////////////////////////////////////////////////////////////////////////////
+/// A small deterministic pseudo-random number generator, using the SplitMix64
+/// algorithm. This is not suitable for cryptographic use, but is fast and
+/// reproducible from its seed on every platform.
+#[derive(Debug, Clone)]
+pub (in crate) struct Rng(u64);
+
+impl Rng {
+    /// Constructs a new `Rng` from the given seed.
+    pub (in crate) fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    /// Returns the next pseudo-random `u64`.
+    pub (in crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random `f64` in the range `[0, 1)`.
+    pub (in crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a pseudo-random index less than `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub (in crate) fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Shuffles the elements of the slice.
+    pub (in crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
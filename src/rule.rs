@@ -0,0 +1,191 @@
+
+// Internal library imports.
+use crate::Expr;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Rule
+////////////////////////////////////////////////////////////////////////////////
+/// A Horn-style inference rule: if the premise holds, the conclusion is a
+/// fact.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rule<V> {
+    /// The condition under which the rule fires.
+    pub premise: Expr<V>,
+    /// The fact derived when the rule fires.
+    pub conclusion: V,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RuleEngine
+////////////////////////////////////////////////////////////////////////////////
+/// A forward-chaining inference engine over a set of [`Rule`]s.
+///
+/// Premises are evaluated by treating a variable as true if and only if it is
+/// a known fact. Rules are applied repeatedly until no new facts are derived.
+/// If premises contain negations, the derived facts may depend on the order in
+/// which the rules were added.
+///
+/// [`Rule`]: crate::Rule
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RuleEngine<V> {
+    /// The inference rules, in the order they were added.
+    rules: Vec<Rule<V>>,
+}
+
+impl<V> RuleEngine<V> {
+    /// Constructs a new `RuleEngine` with no rules.
+    pub fn new() -> Self {
+        RuleEngine { rules: Vec::new() }
+    }
+
+    /// Adds a rule deriving the conclusion whenever the premise holds. Returns
+    /// the index of the rule.
+    pub fn add_rule(&mut self, premise: Expr<V>, conclusion: V) -> usize {
+        self.rules.push(Rule { premise, conclusion });
+        self.rules.len() - 1
+    }
+
+    /// Returns the rules of the engine.
+    pub fn rules(&self) -> &[Rule<V>] {
+        &self.rules
+    }
+}
+
+impl<V> RuleEngine<V> where V: Eq + Hash + Clone {
+    /// Computes the closure of the given facts under the rules.
+    pub fn infer<I>(&self, facts: I) -> Derivation<V>
+        where I: IntoIterator<Item=V>
+    {
+        let mut known: HashMap<V, Justification<V>> = facts
+            .into_iter()
+            .map(|fact| (fact, Justification::Given))
+            .collect();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (idx, rule) in self.rules.iter().enumerate() {
+                if known.contains_key(&rule.conclusion) { continue; }
+                if holds(&rule.premise, &known) {
+                    let mut support = Vec::new();
+                    collect_support(&rule.premise, &known, &mut support);
+                    let _ = known.insert(
+                        rule.conclusion.clone(),
+                        Justification::Derived { rule: idx, support });
+                    changed = true;
+                }
+            }
+        }
+
+        Derivation { facts: known }
+    }
+}
+
+impl<V> Default for RuleEngine<V> {
+    fn default() -> Self {
+        RuleEngine::new()
+    }
+}
+
+/// Evaluates the premise, treating known facts as true.
+fn holds<V, J>(expr: &Expr<V>, known: &HashMap<V, J>) -> bool
+    where V: Eq + Hash
+{
+    use Expr::*;
+    match expr {
+        Var(v)    => known.contains_key(v),
+        Not(p)    => !holds(p, known),
+        Or(a, b)  => holds(a, known) || holds(b, known),
+        And(a, b) => holds(a, known) && holds(b, known),
+    }
+}
+
+/// Collects the known facts referenced by the premise.
+fn collect_support<V, J>(expr: &Expr<V>, known: &HashMap<V, J>, out: &mut Vec<V>)
+    where V: Eq + Hash + Clone
+{
+    use Expr::*;
+    match expr {
+        Var(v)    => if known.contains_key(v) && !out.contains(v) {
+            out.push(v.clone());
+        },
+        Not(p)    => collect_support(p, known, out),
+        Or(a, b)  |
+        And(a, b) => {
+            collect_support(a, known, out);
+            collect_support(b, known, out);
+        },
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Justification
+////////////////////////////////////////////////////////////////////////////////
+/// The reason a fact is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Justification<V> {
+    /// The fact was provided as input.
+    Given,
+    /// The fact was derived by a rule.
+    Derived {
+        /// The index of the rule which derived the fact.
+        rule: usize,
+        /// The known facts referenced by the rule's premise.
+        support: Vec<V>,
+    },
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Derivation
+////////////////////////////////////////////////////////////////////////////////
+/// The closure of a set of facts under a [`RuleEngine`].
+///
+/// [`RuleEngine`]: crate::RuleEngine
+#[derive(Debug, Clone)]
+pub struct Derivation<V> where V: Eq + Hash {
+    /// The known facts and their justifications.
+    facts: HashMap<V, Justification<V>>,
+}
+
+impl<V> Derivation<V> where V: Eq + Hash {
+    /// Returns true if the given fact is known.
+    pub fn contains(&self, fact: &V) -> bool {
+        self.facts.contains_key(fact)
+    }
+
+    /// Returns the justification for the given fact, or `None` if the fact is
+    /// not known.
+    pub fn explain(&self, fact: &V) -> Option<&Justification<V>> {
+        self.facts.get(fact)
+    }
+
+    /// Returns an iterator over the known facts.
+    pub fn facts(&self) -> impl Iterator<Item=&V> {
+        self.facts.keys()
+    }
+
+    /// Returns the number of known facts.
+    pub fn len(&self) -> usize {
+        self.facts.len()
+    }
+
+    /// Returns true if there are no known facts.
+    pub fn is_empty(&self) -> bool {
+        self.facts.is_empty()
+    }
+}
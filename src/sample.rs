@@ -0,0 +1,126 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::rng::Rng;
+use crate::truth::distinct_vars;
+use crate::truth::eval_partial;
+use crate::truth::truth_probability;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Constants
+////////////////////////////////////////////////////////////////////////////////
+/// The largest number of variables for which truth probabilities are computed
+/// exactly rather than estimated by sampling.
+const EXACT_MAX_VARS: usize = 16;
+
+/// The seed used when sampling to estimate truth probabilities.
+const ESTIMATE_SEED: u64 = 0x5EED;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Model sampling
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Returns `n` pseudo-random assignments of the variables of the
+    /// expression for which it is true, or no assignments if it is
+    /// unsatisfiable. The same seed always produces the same assignments.
+    ///
+    /// Each assignment is drawn independently and uniformly from the
+    /// satisfying assignments, so assignments may repeat. Each variable is
+    /// chosen in turn by weighting its values by the fraction of the
+    /// remaining assignments which satisfy the expression, which may take
+    /// time exponential in the number of variables.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(3));
+    /// for model in expr.sample_models(10, 7) {
+    ///     assert!(model[&1] && (model[&2] || model[&3]));
+    /// }
+    /// ```
+    pub fn sample_models(&self, n: usize, seed: u64) -> Vec<HashMap<V, bool>> {
+        let vars = distinct_vars(std::iter::once(self));
+        let probs = vec![0.5; vars.len()];
+        let mut values = vec![None; vars.len()];
+        if truth_probability(self, &vars, &probs, &mut values, 0) == 0.0 {
+            return Vec::new();
+        }
+
+        let mut rng = Rng::new(seed);
+        let mut models = Vec::with_capacity(n);
+        for _ in 0..n {
+            for idx in 0..vars.len() {
+                let value = if eval_partial(self, &vars, &values).is_some() {
+                    // Every extension of the assignment satisfies the
+                    // expression, so the remaining variables are free.
+                    rng.next_u64() & 1 == 1
+                } else {
+                    values[idx] = Some(true);
+                    let t = truth_probability(
+                        self, &vars, &probs, &mut values, idx + 1);
+                    values[idx] = Some(false);
+                    let f = truth_probability(
+                        self, &vars, &probs, &mut values, idx + 1);
+                    rng.next_f64() * (t + f) < t
+                };
+                values[idx] = Some(value);
+            }
+            models.push(vars.iter()
+                .zip(values.iter_mut())
+                .map(|(var, value)| {
+                    ((*var).clone(), value.take().expect("assigned variable"))
+                })
+                .collect());
+        }
+        models
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Probability estimation
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: PartialEq {
+    /// Returns the probability that the expression is true when each variable
+    /// is independently true with the probability given by `var_prob`.
+    ///
+    /// The probability is computed exactly for expressions of at most 16
+    /// variables, or if `samples` is zero. Otherwise it is estimated from the
+    /// given number of pseudo-random assignments, which are the same on every
+    /// call.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(3));
+    /// let p = expr.estimate_truth_probability(|_| 0.5, 1000);
+    /// assert_eq!(p, 0.375);
+    /// ```
+    pub fn estimate_truth_probability<F>(&self, var_prob: F, samples: usize)
+        -> f64
+        where F: Fn(&V) -> f64
+    {
+        let vars = distinct_vars(std::iter::once(self));
+        let probs: Vec<f64> = vars.iter().map(|var| var_prob(var)).collect();
+        let mut values = vec![None; vars.len()];
+        if vars.len() <= EXACT_MAX_VARS || samples == 0 {
+            return truth_probability(self, &vars, &probs, &mut values, 0);
+        }
+
+        let mut rng = Rng::new(ESTIMATE_SEED);
+        let mut trues = 0;
+        for _ in 0..samples {
+            for (value, p) in values.iter_mut().zip(&probs) {
+                *value = Some(rng.next_f64() < *p);
+            }
+            if eval_partial(self, &vars, &values) == Some(true) { trues += 1; }
+        }
+        trues as f64 / samples as f64
+    }
+}
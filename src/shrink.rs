@@ -0,0 +1,81 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::ExprPath;
+use crate::PathStep;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Shrinking
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Clone {
+    /// Returns a smaller expression which still satisfies the given property,
+    /// for minimizing failing reproductions.
+    ///
+    /// Each step replaces an operator with one of its operands, trying the
+    /// outermost operators first, and keeps the first replacement for which
+    /// the property still holds. Shrinking stops when no single replacement
+    /// preserves the property, so the result is locally minimal. If the
+    /// property does not hold for the expression itself, it is returned
+    /// unchanged.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(2).or(!Expr::from(3).and(4));
+    ///
+    /// // Find a smaller expression which still mentions variable 3.
+    /// let shrunk = expr.shrink(|e| e.free_vars().contains(&3));
+    ///
+    /// assert_eq!(shrunk, Expr::from(3));
+    /// ```
+    pub fn shrink<F>(self, mut property: F) -> Expr<V>
+        where F: FnMut(&Expr<V>) -> bool
+    {
+        if !property(&self) { return self; }
+
+        let mut current = self;
+        'shrinking: loop {
+            for (path, step) in shrink_candidates(&current) {
+                let replacement = current
+                    .get_path(&path.child(step))
+                    .expect("operand path")
+                    .clone();
+                let mut candidate = current.clone();
+                let _ = candidate.replace_at(&path, replacement);
+                if property(&candidate) {
+                    current = candidate;
+                    continue 'shrinking;
+                }
+            }
+            return current;
+        }
+    }
+}
+
+/// Returns the path of each operator of the expression paired with the step
+/// to each of its operands, in pre-order.
+///
+/// Only paths are returned, so an operand is cloned only when its replacement
+/// is tried.
+fn shrink_candidates<V>(expr: &Expr<V>) -> Vec<(ExprPath, PathStep)> {
+    use Expr::*;
+    let mut candidates = Vec::new();
+    let mut stack = vec![(expr, ExprPath::root())];
+    while let Some((sub, path)) = stack.pop() {
+        match sub {
+            Var(_) => (),
+            Not(p) => {
+                candidates.push((path.clone(), PathStep::Child));
+                stack.push((p, path.child(PathStep::Child)));
+            },
+            Or(a, b) | And(a, b) => {
+                candidates.push((path.clone(), PathStep::Left));
+                candidates.push((path.clone(), PathStep::Right));
+                stack.push((b, path.child(PathStep::Right)));
+                stack.push((a, path.child(PathStep::Left)));
+            },
+        }
+    }
+    candidates
+}
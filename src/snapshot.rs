@@ -0,0 +1,130 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::stack::guard;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SnapshotEval
+////////////////////////////////////////////////////////////////////////////////
+/// Provides a snapshot of the contextual data a variable is evaluated
+/// against, so that evaluations can be logged and reproduced later.
+///
+/// The snapshot type is chosen by the implementor; to serialize an
+/// [`EvalSnapshot`] with the "serde" feature, it must implement `Serialize`.
+///
+/// [`EvalSnapshot`]: crate::EvalSnapshot
+pub trait SnapshotEval: Eval {
+    /// The recorded view of the contextual data.
+    type Snapshot;
+
+    /// Returns the part of the contextual data which determines the value of
+    /// the variable.
+    fn snapshot(&self, data: &Self::Context) -> Self::Snapshot;
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// VarSnapshot
+////////////////////////////////////////////////////////////////////////////////
+/// A variable evaluated during a snapshot evaluation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VarSnapshot<V, S> {
+    /// The evaluated variable.
+    pub var: V,
+    /// The value of the variable.
+    pub value: bool,
+    /// The snapshot of the data the variable was evaluated against.
+    pub snapshot: S,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// EvalSnapshot
+////////////////////////////////////////////////////////////////////////////////
+/// The record of a snapshot evaluation: its result, and every variable
+/// evaluated along with the data it saw.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EvalSnapshot<V, S> {
+    /// The result of the evaluation.
+    pub result: bool,
+    /// The evaluated variables, in evaluation order.
+    pub vars: Vec<VarSnapshot<V, S>>,
+}
+
+impl<V> Expr<V> where V: SnapshotEval + Clone {
+    /// Evaluates the expression, recording a snapshot of the data seen by each
+    /// evaluated variable.
+    ///
+    /// Variables skipped by short-circuiting are not evaluated and do not
+    /// appear in the record. A variable evaluated more than once is recorded
+    /// each time.
+    ///
+    /// ```rust
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl simple_predicates::Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::Expr::Var;
+    /// use simple_predicates::SnapshotEval;
+    ///
+    /// impl SnapshotEval for Contains {
+    ///     type Snapshot = usize;
+    ///
+    ///     fn snapshot(&self, data: &Vec<u32>) -> usize {
+    ///         data.len()
+    ///     }
+    /// }
+    ///
+    /// let expr = Var(Contains(1)).or(Var(Contains(2)));
+    /// let record = expr.eval_snapshot(&vec![1, 3]);
+    ///
+    /// assert!(record.result);
+    /// assert_eq!(record.vars.len(), 1);
+    /// assert_eq!(record.vars[0].snapshot, 2);
+    /// ```
+    pub fn eval_snapshot(&self, data: &V::Context)
+        -> EvalSnapshot<V, V::Snapshot>
+    {
+        let mut vars = Vec::new();
+        let result = self.eval_snapshot_inner(data, &mut vars);
+        EvalSnapshot { result, vars }
+    }
+
+    /// Evaluates the expression, appending the variable snapshots to `vars`.
+    fn eval_snapshot_inner(
+        &self,
+        data: &V::Context,
+        vars: &mut Vec<VarSnapshot<V, V::Snapshot>>)
+        -> bool
+    {
+        use Expr::*;
+        guard(|| match self {
+            Var(p) => {
+                let value = p.eval(data);
+                vars.push(VarSnapshot {
+                    var: p.clone(),
+                    value,
+                    snapshot: p.snapshot(data),
+                });
+                value
+            },
+            Not(p)    => !p.eval_snapshot_inner(data, vars),
+            Or(a, b)  => a.eval_snapshot_inner(data, vars) ||
+                b.eval_snapshot_inner(data, vars),
+            And(a, b) => a.eval_snapshot_inner(data, vars) &&
+                b.eval_snapshot_inner(data, vars),
+        })
+    }
+}
@@ -0,0 +1,256 @@
+
+// Internal library imports.
+use crate::ClauseError;
+use crate::CnfVec;
+use crate::Eval;
+use crate::Literal;
+use crate::CountedCnf;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// IncrementalSolver
+////////////////////////////////////////////////////////////////////////////////
+/// A satisfiability solver for a fixed expression in [Conjunctive Normal
+/// Form], answering repeated queries under varying assumptions.
+///
+/// The solver keeps what it learns between queries: the literals implied by
+/// the clauses alone are assigned once when it is constructed, sets of
+/// assumptions found to be unsatisfiable are remembered and reused for any
+/// later assumptions which include them, and the most recent model is
+/// returned again if it satisfies the new assumptions, or otherwise used to
+/// choose the first value tried for each variable.
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// use simple_predicates::CnfVec;
+/// use simple_predicates::Expr::Var;
+/// use simple_predicates::IncrementalSolver;
+/// use simple_predicates::Literal;
+///
+/// // (1 | 2) & (!1 | 3)
+/// let cnf = CnfVec::from(vec![
+///     Var(Contains(1)).or(Var(Contains(2))),
+///     (!Var(Contains(1))).or(Var(Contains(3))),
+/// ]);
+/// let mut solver = IncrementalSolver::new(&cnf).unwrap();
+///
+/// let model = solver.solve_under_assumptions(&[
+///     Literal::negative(Contains(3)),
+/// ]).unwrap();
+/// assert!(!model[&Contains(1)] && model[&Contains(2)]);
+///
+/// assert_eq!(solver.solve_under_assumptions(&[
+///     Literal::negative(Contains(2)),
+///     Literal::negative(Contains(3)),
+/// ]), None);
+/// ```
+///
+/// [Conjunctive Normal Form]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
+#[derive(Debug, Clone)]
+pub struct IncrementalSolver<V> {
+    /// The clauses, with the literals implied by the clauses alone assigned.
+    cnf: CountedCnf<V>,
+    /// Whether the clauses alone are unsatisfiable.
+    unsatisfiable: bool,
+    /// The sets of assumptions found to be unsatisfiable.
+    nogoods: Vec<Vec<Literal<V>>>,
+    /// The most recent model of the clauses.
+    model: Option<HashMap<V, bool>>,
+}
+
+impl<V> IncrementalSolver<V> where V: Eq + Hash + Clone {
+    /// Constructs a new `IncrementalSolver` for the clauses of the given
+    /// expression.
+    ///
+    /// Returns an error if a clause is not a disjunction of literals.
+    pub fn new(cnf: &CnfVec<V>) -> Result<Self, ClauseError>
+        where V: Eval
+    {
+        let mut solver = IncrementalSolver {
+            cnf: CountedCnf::new(cnf)?,
+            unsatisfiable: false,
+            nogoods: Vec::new(),
+            model: None,
+        };
+        // Unit clauses are never reported by assignments, so propagate them
+        // first. The implied literals are never unassigned.
+        let units = (0..solver.cnf.clause_count()).collect();
+        solver.unsatisfiable = !solver.propagate(units, &mut Vec::new());
+        Ok(solver)
+    }
+
+    /// Returns an assignment of the variables of the expression for which it
+    /// is true, or `None` if it is unsatisfiable.
+    pub fn solve(&mut self) -> Option<HashMap<V, bool>> {
+        self.solve_under_assumptions(&[])
+    }
+
+    /// Returns an assignment of the variables of the expression and the
+    /// assumptions for which the expression and every assumption are true, or
+    /// `None` if there is no such assignment.
+    pub fn solve_under_assumptions(&mut self, assumptions: &[Literal<V>])
+        -> Option<HashMap<V, bool>>
+    {
+        if self.unsatisfiable { return None; }
+        if assumptions.iter().any(|a| assumptions.iter()
+            .any(|b| a.var == b.var && a.polarity != b.polarity))
+        {
+            return None;
+        }
+        if self.nogoods.iter()
+            .any(|nogood| nogood.iter().all(|l| assumptions.contains(l)))
+        {
+            return None;
+        }
+
+        let reusable = match &self.model {
+            Some(model) => assumptions.iter()
+                .all(|a| model.get(&a.var) != Some(&!a.polarity)),
+            None        => false,
+        };
+        if !reusable {
+            let mut trail = Vec::new();
+            let satisfied = self.assume(assumptions, &mut trail)
+                && self.search(&mut trail);
+            if satisfied {
+                let model = self.cnf.vars()
+                    .iter()
+                    .map(|var| {
+                        let value = self.cnf.var_value(var).unwrap_or(false);
+                        (var.clone(), value)
+                    })
+                    .collect();
+                self.model = Some(model);
+            }
+            self.backtrack(&mut trail, 0);
+            if !satisfied {
+                self.nogoods.push(assumptions.to_vec());
+                return None;
+            }
+        }
+
+        let mut model = self.model.clone().expect("model of clauses");
+        for a in assumptions {
+            let _ = model.entry(a.var.clone()).or_insert(a.polarity);
+        }
+        Some(model)
+    }
+
+    /// Returns the literals of the variables of the expression which are true
+    /// in every assignment satisfying the expression and the assumptions,
+    /// excluding the assumptions themselves, in order of first occurrence of
+    /// their variables. Returns no literals if the assumptions are
+    /// unsatisfiable.
+    pub fn implied_literals(&mut self, assumptions: &[Literal<V>])
+        -> Vec<Literal<V>>
+    {
+        let model = match self.solve_under_assumptions(assumptions) {
+            Some(model) => model,
+            None        => return Vec::new(),
+        };
+        let mut query = assumptions.to_vec();
+        let mut implied = Vec::new();
+        for var in self.cnf.vars().to_vec() {
+            if assumptions.iter().any(|a| a.var == var) { continue; }
+            let value = model[&var];
+            let literal = Literal::new(var, value);
+            query.push(literal.clone().negated());
+            if self.solve_under_assumptions(&query).is_none() {
+                implied.push(literal);
+            }
+            let _ = query.pop();
+        }
+        implied
+    }
+
+    /// Assigns the assumed literals and propagates their implications,
+    /// returning false on a conflict.
+    fn assume(&mut self, assumptions: &[Literal<V>], trail: &mut Vec<V>)
+        -> bool
+    {
+        for a in assumptions {
+            match self.cnf.var_value(&a.var) {
+                Some(value) if value != a.polarity => return false,
+                Some(_) => (),
+                None    => if !self.decide(&a.var, a.polarity, trail) {
+                    return false;
+                },
+            }
+        }
+        true
+    }
+
+    /// Searches for an extension of the current assignment satisfying the
+    /// clauses, leaving it assigned if one is found.
+    fn search(&mut self, trail: &mut Vec<V>) -> bool {
+        guard(|| {
+            if let Some(value) = self.cnf.value() { return value; }
+            let var = match self.cnf.vars()
+                .iter()
+                .find(|var| self.cnf.var_value(var).is_none())
+            {
+                Some(var) => var.clone(),
+                None      => return false,
+            };
+            let phase = self.model.as_ref()
+                .and_then(|model| model.get(&var).copied())
+                .unwrap_or(false);
+
+            for &value in &[phase, !phase] {
+                let mark = trail.len();
+                if self.decide(&var, value, trail) && self.search(trail) {
+                    return true;
+                }
+                self.backtrack(trail, mark);
+            }
+            false
+        })
+    }
+
+    /// Assigns a value to an unassigned variable and propagates its
+    /// implications, returning false on a conflict.
+    fn decide(&mut self, var: &V, value: bool, trail: &mut Vec<V>) -> bool {
+        trail.push(var.clone());
+        let propagation = self.cnf.assign(var, value);
+        propagation.conflicts.is_empty()
+            && self.propagate(propagation.units, trail)
+    }
+
+    /// Assigns the unassigned literals of the given unit clauses and any
+    /// clauses made unit as a result, returning false on a conflict.
+    fn propagate(&mut self, mut units: Vec<usize>, trail: &mut Vec<V>)
+        -> bool
+    {
+        while let Some(clause) = units.pop() {
+            let (var, value) = match self.cnf.unit_literal(clause) {
+                Some((var, value)) => (var.clone(), value),
+                None               => continue,
+            };
+            trail.push(var.clone());
+            let propagation = self.cnf.assign(&var, value);
+            if !propagation.conflicts.is_empty() { return false; }
+            units.extend(propagation.units);
+        }
+        self.cnf.value() != Some(false)
+    }
+
+    /// Unassigns the variables assigned after the given trail position.
+    fn backtrack(&mut self, trail: &mut Vec<V>, mark: usize) {
+        for var in trail.drain(mark..).rev() {
+            self.cnf.unassign(&var);
+        }
+    }
+}
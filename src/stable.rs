@@ -0,0 +1,80 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::fnv::fnv1a;
+use crate::fnv::fnv1a_128;
+use crate::fnv::FNV_OFFSET_BASIS;
+use crate::fnv::FNV_OFFSET_BASIS_128;
+use crate::stack::guard;
+
+// Standard library imports
+use std::fmt::Display;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Stable encoding
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Display {
+    /// Returns a canonical textual encoding of the expression, intended for
+    /// hashing, cache keys, and snapshot tests.
+    ///
+    /// Variables are written as double-quoted strings of their `Display`
+    /// output, with `"` and `\` escaped by a preceding `\`. Chains of `And` and
+    /// `Or` expressions are flattened and their operands are sorted, so
+    /// expressions differing only in the order or grouping of their operands
+    /// have the same encoding. Every operator is parenthesized:
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(3).and(Expr::from(1).or(2)).and(!Expr::from(2));
+    ///
+    /// assert_eq!(expr.to_stable_string(), r#"(!("2")&"3"&("1"|"2"))"#);
+    /// ```
+    pub fn to_stable_string(&self) -> String {
+        use Expr::*;
+        guard(|| match self {
+            Var(v) => {
+                let mut s = String::from("\"");
+                for c in v.to_string().chars() {
+                    if c == '"' || c == '\\' { s.push('\\'); }
+                    s.push(c);
+                }
+                s.push('"');
+                s
+            },
+            Not(p) => format!("!({})", p.to_stable_string()),
+            Or(..) | And(..) => {
+                let sep = if let Or(..) = self { "|" } else { "&" };
+                let mut operands: Vec<String> = self.operands()
+                    .into_iter()
+                    .map(Expr::to_stable_string)
+                    .collect();
+                operands.sort_unstable();
+                format!("({})", operands.join(sep))
+            },
+        })
+    }
+
+    /// Returns a 64-bit hash of the expression's [stable encoding], suitable
+    /// for use as a cache key across processes.
+    ///
+    /// Unlike the [`Hash`] trait, the result does not depend on the hasher or
+    /// the platform, and is unchanged by serialization round-trips as long as
+    /// the `Display` output of the variables is.
+    ///
+    /// [stable encoding]: crate::Expr::to_stable_string
+    /// [`Hash`]: std::hash::Hash
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET_BASIS, self.to_stable_string().as_bytes())
+    }
+
+    /// Returns a 128-bit hash of the expression's [stable encoding]. See
+    /// [`content_hash`] for details.
+    ///
+    /// [stable encoding]: crate::Expr::to_stable_string
+    /// [`content_hash`]: crate::Expr::content_hash
+    pub fn content_hash_128(&self) -> u128 {
+        fnv1a_128(FNV_OFFSET_BASIS_128, self.to_stable_string().as_bytes())
+    }
+}
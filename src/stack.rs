@@ -0,0 +1,29 @@
+
+// NOTE: `Expr` does not implement `Drop`, and the derived serde impls are not
+// guarded, so dropping or (de)serializing a very deep expression still
+// recurses on the native stack regardless of the "stacker" feature.
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Stack growth
+////////////////////////////////////////////////////////////////////////////////
+/// The amount of remaining stack space below which a new segment is allocated.
+#[cfg(feature = "stacker")]
+const RED_ZONE: usize = 64 * 1024;
+
+/// The size of each newly allocated stack segment.
+#[cfg(feature = "stacker")]
+const STACK_SEGMENT_SIZE: usize = 1024 * 1024;
+
+/// Calls the given function, first growing the stack if the "stacker" feature
+/// is enabled and the remaining stack space is low. Recursive operations on
+/// expressions should wrap each recursive step with this function.
+#[inline(always)]
+pub (in crate) fn guard<R, F>(f: F) -> R
+    where F: FnOnce() -> R
+{
+    #[cfg(feature = "stacker")]
+    { stacker::maybe_grow(RED_ZONE, STACK_SEGMENT_SIZE, f) }
+    #[cfg(not(feature = "stacker"))]
+    { f() }
+}
@@ -0,0 +1,158 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::stack::guard;
+
+// Standard library imports
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SimplifyRule
+////////////////////////////////////////////////////////////////////////////////
+/// A transformation applied while simplifying an expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimplifyRule {
+    /// A double negation was removed: `!!a` becomes `a`.
+    DoubleNegation,
+    /// A negation was moved inside a conjunction or disjunction: `!(a & b)`
+    /// becomes `!a | !b`, and `!(a | b)` becomes `!a & !b`.
+    DeMorgan,
+    /// A conjunction or disjunction of equal operands was replaced by the
+    /// operand: `a & a` and `a | a` become `a`.
+    Idempotence,
+}
+
+impl Display for SimplifyRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimplifyRule::DoubleNegation => write!(f, "double negation"),
+            SimplifyRule::DeMorgan       => write!(f, "De Morgan's law"),
+            SimplifyRule::Idempotence    => write!(f, "idempotence"),
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SimplifyStep
+////////////////////////////////////////////////////////////////////////////////
+/// A single transformation applied while simplifying an expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimplifyStep<V> {
+    /// The rule which was applied.
+    pub rule: SimplifyRule,
+    /// The subexpression the rule was applied to.
+    pub before: Expr<V>,
+    /// The subexpression produced by the rule.
+    pub after: Expr<V>,
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Traced simplification
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Clone + PartialEq {
+    /// Simplifies the expression by removing double negations and equal
+    /// operands, and converts it into negation normal form, returning the
+    /// steps which were applied in order.
+    ///
+    /// The result is the same expression produced when converting to a normal
+    /// form, before it is split into clauses.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    /// use simple_predicates::SimplifyRule;
+    ///
+    /// let expr = !(Expr::from(1).and(!Expr::from(2)));
+    /// let (simplified, steps) = expr.simplify_traced();
+    ///
+    /// assert_eq!(simplified, (!Expr::from(1)).or(2));
+    /// assert_eq!(steps[0].rule, SimplifyRule::DeMorgan);
+    /// assert_eq!(steps[1].rule, SimplifyRule::DoubleNegation);
+    /// ```
+    pub fn simplify_traced(self) -> (Expr<V>, Vec<SimplifyStep<V>>) {
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        let original = self.clone();
+
+        let mut steps = Vec::new();
+        let simplified = simplify_traced_inner(self, &mut steps);
+
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        crate::truth::verify_transform("simplification", &original,
+            &simplified);
+        (simplified, steps)
+    }
+}
+
+/// Simplifies the expression, recording each applied rule.
+fn simplify_traced_inner<V>(expr: Expr<V>, steps: &mut Vec<SimplifyStep<V>>)
+    -> Expr<V>
+    where V: Clone + PartialEq
+{
+    use Expr::*;
+    guard(|| match expr {
+        Var(_) => expr,
+        Not(p) => match *p {
+            Var(v)    => Not(Box::new(Var(v))),
+            Not(q)    => {
+                let after = (*q).clone();
+                record(steps, SimplifyRule::DoubleNegation,
+                    Not(Box::new(Not(q))),
+                    &after);
+                simplify_traced_inner(after, steps)
+            },
+            And(a, b) => {
+                let after = Or(Box::new(Not(a.clone())), Box::new(Not(b.clone())));
+                record(steps, SimplifyRule::DeMorgan, Not(Box::new(And(a, b))),
+                    &after);
+                simplify_traced_inner(after, steps)
+            },
+            Or(a, b)  => {
+                let after = And(Box::new(Not(a.clone())), Box::new(Not(b.clone())));
+                record(steps, SimplifyRule::DeMorgan, Not(Box::new(Or(a, b))),
+                    &after);
+                simplify_traced_inner(after, steps)
+            },
+        },
+        And(a, b) => {
+            let a = simplify_traced_inner(*a, steps);
+            let b = simplify_traced_inner(*b, steps);
+            join_traced(And(Box::new(a), Box::new(b)), steps)
+        },
+        Or(a, b) => {
+            let a = simplify_traced_inner(*a, steps);
+            let b = simplify_traced_inner(*b, steps);
+            join_traced(Or(Box::new(a), Box::new(b)), steps)
+        },
+    })
+}
+
+/// Replaces a conjunction or disjunction of equal operands with the operand,
+/// recording the step.
+fn join_traced<V>(expr: Expr<V>, steps: &mut Vec<SimplifyStep<V>>) -> Expr<V>
+    where V: Clone + PartialEq
+{
+    use Expr::*;
+    match &expr {
+        And(a, b) |
+        Or(a, b) if a == b => {
+            let after = (**a).clone();
+            record(steps, SimplifyRule::Idempotence, expr, &after);
+            after
+        },
+        _ => expr,
+    }
+}
+
+/// Records an applied rule.
+fn record<V>(
+    steps: &mut Vec<SimplifyStep<V>>,
+    rule: SimplifyRule,
+    before: Expr<V>,
+    after: &Expr<V>)
+    where V: Clone
+{
+    steps.push(SimplifyStep { rule, before, after: after.clone() });
+}
@@ -0,0 +1,226 @@
+
+// Internal library imports.
+use crate::CompileError;
+use crate::CompileOptions;
+use crate::CompiledPredicate;
+use crate::Eval;
+use crate::Predicate;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Deserialize;
+#[cfg(feature = "serde")] use serde::Deserializer;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RuleStoreError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that a [`RuleStore`] could not be reloaded. The store
+/// is left unchanged.
+///
+/// [`RuleStore`]: crate::RuleStore
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RuleStoreError {
+    /// A rule could not be compiled.
+    Compile {
+        /// The name of the rule.
+        name: String,
+        /// The compilation error.
+        error: CompileError,
+    },
+    /// The rule sources could not be deserialized.
+    Deserialize(String),
+}
+
+impl Display for RuleStoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleStoreError::Compile { name, error } => write!(f,
+                "rule {:?}: {}", name, error),
+            RuleStoreError::Deserialize(msg) => write!(f,
+                "invalid rule sources: {}", msg),
+        }
+    }
+}
+
+impl Error for RuleStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RuleStoreError::Compile { error, .. } => Some(error),
+            RuleStoreError::Deserialize(_)        => None,
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RuleSnapshot
+////////////////////////////////////////////////////////////////////////////////
+/// An immutable version of the rules of a [`RuleStore`].
+///
+/// [`RuleStore`]: crate::RuleStore
+#[derive(Debug)]
+pub struct RuleSnapshot<V> where V: Eval + PartialEq {
+    /// The version of the rules, incremented on each reload.
+    version: u64,
+    /// The compiled rules, by name.
+    rules: HashMap<String, Arc<CompiledPredicate<V>>>,
+}
+
+impl<V> RuleSnapshot<V> where V: Eval + PartialEq {
+    /// Returns the version of the rules. The initial empty rules have version
+    /// 0.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the rule with the given name.
+    pub fn get(&self, name: &str) -> Option<&Arc<CompiledPredicate<V>>> {
+        self.rules.get(name)
+    }
+
+    /// Returns an iterator over the names of the rules.
+    pub fn names(&self) -> impl Iterator<Item=&str> + '_ {
+        self.rules.keys().map(String::as_str)
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns true if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RuleStore
+////////////////////////////////////////////////////////////////////////////////
+/// A named collection of compiled predicates which can be replaced while it is
+/// in use.
+///
+/// Lookups return shared handles to the current [`RuleSnapshot`], so
+/// evaluations in progress during a reload finish with the rules they
+/// started with. A reload compiles every rule before replacing the snapshot,
+/// and leaves the store unchanged if any rule fails to compile.
+///
+/// [`RuleSnapshot`]: crate::RuleSnapshot
+#[derive(Debug)]
+pub struct RuleStore<V> where V: Eval + PartialEq {
+    /// The options used to compile the rules.
+    options: CompileOptions,
+    /// The current rules.
+    current: RwLock<Arc<RuleSnapshot<V>>>,
+}
+
+impl<V> RuleStore<V> where V: Eval + PartialEq {
+    /// Constructs a new `RuleStore` with no rules, which compiles rules using
+    /// the given options.
+    pub fn new(options: CompileOptions) -> Self {
+        RuleStore {
+            options,
+            current: RwLock::new(Arc::new(RuleSnapshot {
+                version: 0,
+                rules: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns the options used to compile the rules.
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    /// Returns the current rules.
+    pub fn snapshot(&self) -> Arc<RuleSnapshot<V>> {
+        match self.current.read() {
+            Ok(current) => current.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Returns the current version of the rules.
+    pub fn version(&self) -> u64 {
+        self.snapshot().version
+    }
+
+    /// Returns the current version of the rule with the given name, along
+    /// with the version of the rules it belongs to.
+    pub fn get(&self, name: &str) -> Option<(u64, Arc<CompiledPredicate<V>>)> {
+        let snapshot = self.snapshot();
+        snapshot.get(name).map(|rule| (snapshot.version, rule.clone()))
+    }
+
+    /// Replaces the current rules with the given compiled rules, returning the
+    /// new version.
+    fn swap(&self, rules: HashMap<String, Arc<CompiledPredicate<V>>>) -> u64 {
+        let mut current = match self.current.write() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let version = current.version + 1;
+        *current = Arc::new(RuleSnapshot { version, rules });
+        version
+    }
+}
+
+impl<V> RuleStore<V>
+    where
+        V: Eval + FromStr + Eq + Hash + Clone,
+        V::Err: Display,
+{
+    /// Compiles the given named rule sources and replaces the current rules
+    /// with them, returning the new version.
+    ///
+    /// ### Errors
+    ///
+    /// Returns a [`RuleStoreError`] if any of the rules fails to compile, in
+    /// which case the current rules are kept.
+    ///
+    /// [`RuleStoreError`]: crate::RuleStoreError
+    pub fn reload<I, K, S>(&self, sources: I) -> Result<u64, RuleStoreError>
+        where
+            I: IntoIterator<Item=(K, S)>,
+            K: Into<String>,
+            S: AsRef<str>,
+    {
+        let mut rules = HashMap::new();
+        for (name, src) in sources {
+            let name = name.into();
+            match Predicate::compile(src.as_ref(), &self.options) {
+                Ok(rule) => { let _ = rules.insert(name, Arc::new(rule)); },
+                Err(error) => return Err(RuleStoreError::Compile {
+                    name,
+                    error,
+                }),
+            }
+        }
+        Ok(self.swap(rules))
+    }
+
+    /// Deserializes a map from rule names to sources, and reloads the rules
+    /// from it as with [`RuleStore::reload`].
+    ///
+    /// [`RuleStore::reload`]: crate::RuleStore::reload
+    #[cfg(feature = "serde")]
+    pub fn reload_from<'de, D>(&self, deserializer: D)
+        -> Result<u64, RuleStoreError>
+        where D: Deserializer<'de>
+    {
+        let sources = HashMap::<String, String>::deserialize(deserializer)
+            .map_err(|e| RuleStoreError::Deserialize(e.to_string()))?;
+        self.reload(sources)
+    }
+}
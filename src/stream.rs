@@ -0,0 +1,56 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Streaming evaluation
+////////////////////////////////////////////////////////////////////////////////
+/// Evaluates the disjunction of terms read from a fallible stream, such as
+/// the lines of a file, without collecting them.
+///
+/// Terms are read and evaluated in order until one is true, and the rest of
+/// the stream is not read. Returns the first error read before a true term is
+/// found.
+///
+/// ```rust
+/// # use simple_predicates::Eval;
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # struct Contains(pub u32);
+/// # impl Eval for Contains {
+/// #     type Context = Vec<u32>;
+/// #     fn eval(&self, data: &Self::Context) -> bool {
+/// #         data.contains(&self.0)
+/// #     }
+/// # }
+/// # impl std::str::FromStr for Contains {
+/// #     type Err = std::num::ParseIntError;
+/// #     fn from_str(s: &str) -> Result<Self, Self::Err> {
+/// #         s.parse().map(Contains)
+/// #     }
+/// # }
+/// use simple_predicates::eval_dnf_stream;
+/// use simple_predicates::Expr;
+/// use std::io::BufRead;
+///
+/// let file = std::io::Cursor::new("1 & 2\n3 & !4\n5 & 6\n");
+/// let terms = file.lines().map(|line| {
+///     let line = line?;
+///     Expr::<Contains>::parse(&line).map_err(|e| {
+///         std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+///     })
+/// });
+///
+/// assert!(eval_dnf_stream(terms, &vec![3]).unwrap());
+/// ```
+pub fn eval_dnf_stream<V, I, E>(terms: I, data: &V::Context) -> Result<bool, E>
+    where
+        V: Eval,
+        I: IntoIterator<Item=Result<Expr<V>, E>>,
+{
+    for term in terms {
+        if term?.eval(data) { return Ok(true); }
+    }
+    Ok(false)
+}
@@ -0,0 +1,99 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+use crate::fnv::fnv1a;
+use crate::fnv::FNV_OFFSET_BASIS;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FlagRule
+////////////////////////////////////////////////////////////////////////////////
+/// A feature-flag targeting rule, combining a predicate with a percentage
+/// rollout.
+///
+/// Bucketing is deterministic: the same hash key and bucket key always fall
+/// into the same bucket, across processes and platforms.
+///
+/// A deserialized rollout percentage is clamped to 100, as in
+/// [`FlagRule::new`].
+///
+/// [`FlagRule::new`]: crate::FlagRule::new
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "FlagRuleData<V>"))]
+pub struct FlagRule<V> {
+    /// The predicate which must be satisfied for the rule to apply.
+    expr: Expr<V>,
+    /// The percentage of buckets the rule is rolled out to.
+    rollout: u8,
+    /// The key used to seed the bucketing hash.
+    hash_key: String,
+}
+
+impl<V> FlagRule<V> {
+    /// Constructs a new `FlagRule`. The rollout percentage is clamped to 100.
+    pub fn new<S>(expr: Expr<V>, rollout: u8, hash_key: S) -> Self
+        where S: Into<String>
+    {
+        FlagRule {
+            expr,
+            rollout: rollout.min(100),
+            hash_key: hash_key.into(),
+        }
+    }
+
+    /// Returns a reference to the rule's predicate.
+    pub fn expr(&self) -> &Expr<V> {
+        &self.expr
+    }
+
+    /// Returns the rollout percentage.
+    pub fn rollout(&self) -> u8 {
+        self.rollout
+    }
+
+    /// Returns the key used to seed the bucketing hash.
+    pub fn hash_key(&self) -> &str {
+        &self.hash_key
+    }
+
+    /// Returns the bucket, in the range `0..100`, that the given bucket key
+    /// falls into for this rule.
+    pub fn bucket(&self, bucket_key: &str) -> u8 {
+        let mut hash = FNV_OFFSET_BASIS;
+        hash = fnv1a(hash, self.hash_key.as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, bucket_key.as_bytes());
+        (hash % 100) as u8
+    }
+}
+
+/// The deserialized form of a [`FlagRule`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct FlagRuleData<V> {
+    expr: Expr<V>,
+    rollout: u8,
+    hash_key: String,
+}
+
+#[cfg(feature = "serde")]
+impl<V> From<FlagRuleData<V>> for FlagRule<V> {
+    fn from(data: FlagRuleData<V>) -> Self {
+        FlagRule::new(data.expr, data.rollout, data.hash_key)
+    }
+}
+
+impl<V> FlagRule<V> where V: Eval {
+    /// Returns true if the predicate is satisfied in the given context and
+    /// the bucket key falls within the rollout percentage.
+    pub fn evaluate(&self, data: &V::Context, bucket_key: &str) -> bool {
+        self.bucket(bucket_key) < self.rollout && self.expr.eval(data)
+    }
+}
+
@@ -0,0 +1,123 @@
+
+// Internal library imports.
+use crate::DnfVec;
+use crate::Eval;
+use crate::Expr;
+use crate::Literal;
+use crate::clausal::literal;
+
+// External library imports
+#[cfg(feature = "serde")] use serde::Serialize;
+#[cfg(feature = "serde")] use serde::Deserialize;
+
+// Standard library imports
+use std::collections::HashSet;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LiteralDnf
+////////////////////////////////////////////////////////////////////////////////
+/// A boolean expression in [Disjunctive Normal Form], stored as a list of
+/// terms, each of which is a list of literals.
+///
+/// A `LiteralDnf` is true if all literals of any term are true. A term with no
+/// literals is always true, and a `LiteralDnf` with no terms is always false.
+///
+/// [Disjunctive Normal Form]: https://en.wikipedia.org/wiki/Disjunctive_normal_form
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct LiteralDnf<V>(Vec<Vec<Literal<V>>>);
+
+impl<V> LiteralDnf<V> {
+    /// Constructs a new `LiteralDnf` from the literals of each term.
+    pub fn from_terms<I, T>(terms: I) -> Self
+        where
+            I: IntoIterator<Item=T>,
+            T: IntoIterator<Item=Literal<V>>,
+    {
+        LiteralDnf(terms.into_iter()
+            .map(|term| term.into_iter().collect())
+            .collect())
+    }
+
+    /// Returns an iterator over the literals of each term.
+    pub fn terms(&self) -> impl Iterator<Item=&[Literal<V>]> + '_ {
+        self.0.iter().map(Vec::as_slice)
+    }
+
+    /// Returns the number of terms.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the boolean expression contains no terms.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Converts the terms into `Expr` conjunctions of their literals, returning
+    /// `None` if any term is empty and so cannot be represented as an `Expr`.
+    pub fn into_dnf(self) -> Option<DnfVec<V>>
+        where V: Eval + PartialEq
+    {
+        let mut terms = Vec::with_capacity(self.0.len());
+        for term in self.0 {
+            terms.push(term.into_iter()
+                .map(Expr::from)
+                .reduce(Expr::and)?);
+        }
+        Some(DnfVec::from(terms))
+    }
+}
+
+impl<V> Eval for LiteralDnf<V> where V: Eval {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        self.0.iter().any(|term| term
+            .iter()
+            .all(|lit| lit.var.eval(data) == lit.polarity))
+    }
+}
+
+impl<V> Default for LiteralDnf<V> {
+    fn default() -> Self {
+        LiteralDnf(Vec::new())
+    }
+}
+
+/// Returns the literals of a conjunction of literals.
+///
+/// # Panics
+///
+/// Panics if the term is not a conjunction of literals.
+pub (in crate) fn term_literals<V>(term: &Expr<V>) -> Vec<Literal<V>>
+    where V: Clone
+{
+    term.operands()
+        .into_iter()
+        .map(|expr| {
+            let (var, polarity) = literal(expr).expect("literal in term");
+            Literal::new(var.clone(), polarity)
+        })
+        .collect()
+}
+
+/// Returns the variables which must be true for the term to be true, which
+/// are the unnegated variables among its top-level conjuncts.
+pub (in crate) fn required_vars<V>(term: &Expr<V>) -> HashSet<V>
+    where V: Eq + Hash + Clone
+{
+    let conjuncts = match term {
+        Expr::And(..) => term.operands(),
+        _             => vec![term],
+    };
+    conjuncts.into_iter()
+        .filter_map(|expr| match expr {
+            Expr::Var(var) => Some(var.clone()),
+            _              => None,
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+////////////////////////////////////////////////////////////////////////////////
+// Simple Boolean Predicates
+////////////////////////////////////////////////////////////////////////////////
+// This code is dual licenced using the MIT or Apache 2 license.
+// See licence-mit.md and licence-apache.md for details.
+////////////////////////////////////////////////////////////////////////////////
+//! Exhaustive test assertions.
+//!
+//! The assertions in this module evaluate expressions under every assignment
+//! of a given set of variables, and are intended for testing custom rewrites
+//! of small expressions:
+//!
+//! ```rust
+//! use simple_predicates::Expr;
+//! use simple_predicates::testing::assert_equiv_exhaustive;
+//!
+//! let expr = !(Expr::from(1).or(2));
+//! let rewritten = (!Expr::from(1)).and(!Expr::from(2));
+//! assert_equiv_exhaustive(&expr, &rewritten, &[1, 2]);
+//! ```
+////////////////////////////////////////////////////////////////////////////////
+
+// Internal library imports.
+use crate::Expr;
+use crate::TraversalOrder;
+use crate::truth::counterexample;
+use crate::truth::eval_assignment;
+
+// Standard library imports
+use std::fmt::Debug;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Assertions
+////////////////////////////////////////////////////////////////////////////////
+/// The largest number of variables accepted by the exhaustive assertions.
+pub const MAX_EXHAUSTIVE_VARS: usize = 24;
+
+/// Asserts that the expressions have the same value under every assignment of
+/// the given variables.
+///
+/// # Panics
+///
+/// Panics with the first assignment for which the expressions differ. Also
+/// panics if either expression contains a variable not in `vars`, or if
+/// `vars` has more than [`MAX_EXHAUSTIVE_VARS`] elements.
+#[track_caller]
+pub fn assert_equiv_exhaustive<V>(a: &Expr<V>, b: &Expr<V>, vars: &[V])
+    where V: PartialEq + Debug
+{
+    let vars = check_vars(&[a, b], vars);
+    if let Some(assignment) = counterexample(vars.len(),
+        |assignment| eval_assignment(a, &vars, assignment),
+        |assignment| eval_assignment(b, &vars, assignment))
+    {
+        panic!("expressions are not equivalent\n  \
+                left:  {:?}\n  \
+                right: {:?}\n  \
+                under assignment {}: left is {}, right is {}",
+            a,
+            b,
+            format_assignment(&vars, assignment),
+            eval_assignment(a, &vars, assignment),
+            eval_assignment(b, &vars, assignment));
+    }
+}
+
+/// Asserts that the `consequent` is true under every assignment of the given
+/// variables for which the `antecedent` is true.
+///
+/// # Panics
+///
+/// Panics with the first assignment for which the antecedent is true and the
+/// consequent is false. Also panics if either expression contains a variable
+/// not in `vars`, or if `vars` has more than [`MAX_EXHAUSTIVE_VARS`]
+/// elements.
+#[track_caller]
+pub fn assert_implies_exhaustive<V>(
+    antecedent: &Expr<V>,
+    consequent: &Expr<V>,
+    vars: &[V])
+    where V: PartialEq + Debug
+{
+    let vars = check_vars(&[antecedent, consequent], vars);
+    if let Some(assignment) = counterexample(vars.len(),
+        |assignment| eval_assignment(antecedent, &vars, assignment),
+        |assignment| eval_assignment(antecedent, &vars, assignment)
+            && eval_assignment(consequent, &vars, assignment))
+    {
+        panic!("antecedent does not imply consequent\n  \
+                antecedent: {:?}\n  \
+                consequent: {:?}\n  \
+                under assignment {}: antecedent is true, consequent is false",
+            antecedent,
+            consequent,
+            format_assignment(&vars, assignment));
+    }
+}
+
+/// Checks that the expressions only contain the given variables, and that
+/// there are few enough variables to enumerate.
+#[track_caller]
+fn check_vars<'a, V>(exprs: &[&Expr<V>], vars: &'a [V]) -> Vec<&'a V>
+    where V: PartialEq + Debug
+{
+    assert!(vars.len() <= MAX_EXHAUSTIVE_VARS,
+        "{} variables given, but at most {} can be enumerated",
+        vars.len(),
+        MAX_EXHAUSTIVE_VARS);
+    for expr in exprs {
+        for expr in expr.iter_subexprs(TraversalOrder::PreOrder) {
+            if let Expr::Var(v) = expr {
+                assert!(vars.contains(v),
+                    "variable {:?} is not in the given variables", v);
+            }
+        }
+    }
+    vars.iter().collect()
+}
+
+/// Formats an assignment of the given variables as a list of `var = value`
+/// pairs.
+fn format_assignment<V>(vars: &[&V], assignment: u64) -> String
+    where V: Debug
+{
+    let pairs: Vec<String> = vars.iter()
+        .enumerate()
+        .map(|(idx, var)| format!("{:?} = {}", var, assignment & (1 << idx) != 0))
+        .collect();
+    format!("{{{}}}", pairs.join(", "))
+}
@@ -164,23 +164,23 @@ fn three_level_cnf_vec() {
     let res = CnfVec::from(expr);
     let cnf = CnfVec::from(vec![
         Or(
-            Box::new(Var(7)),
-            Box::new(Var(8))),
-        Or(
-            Box::new(Var(5)),
-            Box::new(Var(6))),
+            Box::new(Var(3)),
+            Box::new(Var(1))),
         Or(
-            Box::new(Var(4)),
+            Box::new(Var(3)),
             Box::new(Var(2))),
         Or(
             Box::new(Var(4)),
             Box::new(Var(1))),
         Or(
-            Box::new(Var(3)),
+            Box::new(Var(4)),
             Box::new(Var(2))),
         Or(
-            Box::new(Var(3)),
-            Box::new(Var(1))),
+            Box::new(Var(5)),
+            Box::new(Var(6))),
+        Or(
+            Box::new(Var(7)),
+            Box::new(Var(8))),
     ]);
 
     // println!("{:?}", res);
@@ -212,29 +212,18 @@ fn three_level_dnf_hash() {
                 Box::new(Var(8)))))));
 
     let res = DnfHashSet::from(expr);
+    let term = |a, b, c, d| And(
+        Box::new(And(Box::new(Var(a)), Box::new(Var(b)))),
+        Box::new(And(Box::new(Var(c)), Box::new(Var(d)))));
     let dnf = DnfHashSet::from(vec![
-        And(
-            Box::new(And(
-                Box::new(Or(
-                    Box::new(Var(5)),
-                    Box::new(Var(6)))),
-                Box::new(Or(
-                    Box::new(Var(7)),
-                    Box::new(Var(8)))))), 
-            Box::new(And(
-                Box::new(Var(3)),
-                Box::new(Var(4))))),
-        And(
-            Box::new(And(
-                Box::new(Or(
-                    Box::new(Var(5)),
-                    Box::new(Var(6)))),
-                Box::new(Or(
-                    Box::new(Var(7)),
-                    Box::new(Var(8)))))),
-            Box::new(And(
-                Box::new(Var(1)),
-                Box::new(Var(2))))),
+        term(7, 5, 1, 2),
+        term(7, 5, 3, 4),
+        term(7, 6, 1, 2),
+        term(7, 6, 3, 4),
+        term(8, 5, 1, 2),
+        term(8, 5, 3, 4),
+        term(8, 6, 1, 2),
+        term(8, 6, 3, 4),
     ]);
 
     // println!("{:?}", res);
@@ -264,29 +253,18 @@ fn three_level_dnf_vec() {
                 Box::new(Var(8)))))));
 
     let res = DnfVec::from(expr);
+    let term = |a, b, c, d| And(
+        Box::new(And(Box::new(Var(a)), Box::new(Var(b)))),
+        Box::new(And(Box::new(Var(c)), Box::new(Var(d)))));
     let dnf = DnfVec::from(vec![
-        And(
-            Box::new(And(
-                Box::new(Or(
-                    Box::new(Var(5)),
-                    Box::new(Var(6)))),
-                Box::new(Or(
-                    Box::new(Var(7)),
-                    Box::new(Var(8)))))), 
-            Box::new(And(
-                Box::new(Var(3)),
-                Box::new(Var(4))))),
-        And(
-            Box::new(And(
-                Box::new(Or(
-                    Box::new(Var(5)),
-                    Box::new(Var(6)))),
-                Box::new(Or(
-                    Box::new(Var(7)),
-                    Box::new(Var(8)))))),
-            Box::new(And(
-                Box::new(Var(1)),
-                Box::new(Var(2))))),
+        term(7, 5, 1, 2),
+        term(7, 5, 3, 4),
+        term(7, 6, 1, 2),
+        term(7, 6, 3, 4),
+        term(8, 5, 1, 2),
+        term(8, 5, 3, 4),
+        term(8, 6, 1, 2),
+        term(8, 6, 3, 4),
     ]);
 
     // println!("{:?}", res);
@@ -296,45 +274,2757 @@ fn three_level_dnf_vec() {
 }
 
 
-#[cfg(not(feature = "serde"))]
 #[test]
-fn serialize_tests() {
-    panic!("Enable \"serde\" (--all-features) feature to run serde tests.")
+fn policy_deny_overrides() {
+    use Expr::*;
+    let policy = Policy::new(
+        DnfVec::from(vec![Var(1), Var(2)]),
+        DnfVec::from(vec![And(Box::new(Var(2)), Box::new(Var(3)))]));
+
+    assert_eq!(policy.evaluate(&vec![1]), Decision::Allow);
+    assert_eq!(policy.evaluate(&vec![2, 3]), Decision::Deny);
+    assert_eq!(policy.evaluate(&vec![3, 4]), Decision::NotApplicable);
+    assert_eq!(Policy::<u32>::default().evaluate(&vec![1]),
+        Decision::NotApplicable);
 }
 
-#[cfg(feature = "serde")]
+
 #[test]
-fn serialize_expr_ron() {
+fn flag_rule_rollout() {
+    use Expr::*;
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    let none = FlagRule::new(Var(4), 0, "flag");
+    let all = FlagRule::new(Var(4), 150, "flag");
+    let half = FlagRule::new(Var(4), 50, "flag");
+    assert_eq!(all.rollout(), 100);
+
+    let keys: Vec<String> = (0..1000).map(|i| format!("user{}", i)).collect();
+    assert!(keys.iter().all(|k| !none.evaluate(&items, k)));
+    assert!(keys.iter().all(|k| all.evaluate(&items, k)));
+    assert!(keys.iter().all(|k| !all.evaluate(&vec![], k)));
+
+    let enabled = keys.iter().filter(|k| half.evaluate(&items, k)).count();
+    assert!(enabled > 400 && enabled < 600);
+
+    // Bucketing is stable.
+    assert_eq!(half.bucket("user1"), half.bucket("user1"));
+    assert_eq!(half.bucket("user1"), 93);
+}
+
+
+#[test]
+fn rule_engine_closure() {
+    use Expr::*;
+    let mut engine = RuleEngine::new();
+    let r0 = engine.add_rule(And(Box::new(Var(1)), Box::new(Var(2))), 3);
+    let r1 = engine.add_rule(Or(Box::new(Var(3)), Box::new(Var(9))), 4);
+    let _ = engine.add_rule(Var(5), 6);
+
+    let res = engine.infer(vec![2, 1]);
+    assert_eq!(res.len(), 4);
+    assert!(res.contains(&4));
+    assert!(!res.contains(&6));
+    assert_eq!(res.explain(&1), Some(&Justification::Given));
+    assert_eq!(res.explain(&4), Some(&Justification::Derived {
+        rule: r1,
+        support: vec![3],
+    }));
+    match res.explain(&3) {
+        Some(Justification::Derived { rule, support }) => {
+            assert_eq!(*rule, r0);
+            assert_eq!(support, &vec![1, 2]);
+        },
+        other => panic!("unexpected justification {:?}", other),
+    }
+}
+
+
+#[cfg(feature = "tracing")]
+#[test]
+fn traced_expr_eval() {
+    use Expr::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use tracing::field::Field;
+    use tracing::field::Visit;
+    use tracing::span;
+
+    /// Records each span name and event, omitting timings.
+    #[derive(Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    #[derive(Default)]
+    struct Fields { message: String, fields: Vec<String> }
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields.push(format!("{}={}", field.name(), value));
+        }
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            match field.name() {
+                "message"    => self.message = format!("{:?}", value),
+                "elapsed_ns" => (),
+                name         => self.fields.push(format!("{}={:?}", name, value)),
+            }
+        }
+    }
+
+    impl tracing::Subscriber for Capture {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool { true }
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            self.0.lock().unwrap().push(format!("span {}", span.metadata().name()));
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(format!("{} {}",
+                fields.message,
+                fields.fields.join(" ")));
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+    let capture = Capture::default();
+    let events = capture.0.clone();
+
+    tracing::subscriber::with_default(capture, || {
+        let expr = TracedExpr::from(Or(
+            Box::new(And(Box::new(Var(3)), Box::new(Var(4)))),
+            Box::new(Not(Box::new(Var(6))))));
+        assert!(expr.eval(&items));
+
+        let expr = TracedExpr::from(And(Box::new(Var(4)), Box::new(Var(6))));
+        assert!(!expr.eval(&items));
+    });
+
+    // Short-circuited operands emit no events.
+    assert_eq!(*events.lock().unwrap(), vec![
+        "span eval",
+        "var depth=2 var=3 result=false",
+        "clause depth=1 op=and result=false",
+        "var depth=2 var=6 result=false",
+        "clause depth=1 op=not result=true",
+        "clause depth=0 op=or result=true",
+        "span eval",
+        "var depth=1 var=4 result=true",
+        "var depth=1 var=6 result=false",
+        "clause depth=0 op=and result=false",
+    ]);
+}
+
+
+#[test]
+fn observed_cnf_eval() {
+    use Expr::*;
+    #[derive(Default)]
+    struct Counter {
+        vars: Vec<(u32, bool)>,
+        clauses: Vec<(usize, bool)>,
+        complete: Option<bool>,
+    }
+    impl EvalObserver<u32> for Counter {
+        fn on_var_eval(&mut self, var: &u32, result: bool) {
+            self.vars.push((*var, result));
+        }
+        fn on_clause_result(&mut self, clause: usize, result: bool) {
+            self.clauses.push((clause, result));
+        }
+        fn on_complete(&mut self, result: bool) {
+            self.complete = Some(result);
+        }
+    }
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    let cnf = CnfVec::from(vec![
+        Or(Box::new(Var(3)), Box::new(Var(4))),
+        Var(6),
+        Var(7),
+    ]);
+    let mut counter = Counter::default();
+    assert!(!cnf.eval_observed(&items, &mut counter));
+    assert_eq!(counter.vars, vec![(3, false), (4, true), (6, false)]);
+    assert_eq!(counter.clauses, vec![(0, true), (1, false)]);
+    assert_eq!(counter.complete, Some(false));
+
+    let mut counter = Counter::default();
+    assert!(Var(7).eval_observed(&items, &mut counter));
+    assert!(counter.clauses.is_empty());
+    assert_eq!(counter.complete, Some(true));
+}
+
+
+#[test]
+fn budgeted_eval() {
+    use Expr::*;
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    // And(Var(4), Not(Var(6))) has four nodes.
+    let expr = And(Box::new(Var(4)), Box::new(Not(Box::new(Var(6)))));
+    assert_eq!(expr.eval_with_budget(&items, 4), Ok(true));
+    assert_eq!(expr.eval_with_budget(&items, 3),
+        Err(BudgetExceeded { fuel: 3 }));
+
+    // Short-circuited nodes are not charged.
+    let expr = And(Box::new(Var(3)), Box::new(Not(Box::new(Var(6)))));
+    assert_eq!(expr.eval_with_budget(&items, 2), Ok(false));
+}
+
+
+#[test]
+fn deadline_eval() {
+    use Expr::*;
+    use std::time::Duration;
+    use std::time::Instant;
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    let expr = And(Box::new(Var(4)), Box::new(Or(
+        Box::new(Var(3)),
+        Box::new(Var(5)))));
+
+    let later = Instant::now() + Duration::from_secs(60);
+    assert_eq!(expr.eval_with_deadline(&items, later), Ok(true));
+
+    let past = Instant::now();
+    let res = expr.eval_with_deadline(&items, past).unwrap_err();
+    assert_eq!(res.residual, expr);
+    assert!(res.residual.eval(&items));
+}
+
+
+#[test]
+fn on_error_policies() {
     use Expr::*;
+    #[derive(Debug, Clone, PartialEq)]
+    struct Lookup(u32);
+    impl TryEval for Lookup {
+        type Context = Vec<Option<bool>>;
+        type Error = u32;
+        fn try_eval(&self, data: &Self::Context) -> Result<bool, u32> {
+            data.get(self.0 as usize).copied().flatten().ok_or(self.0)
+        }
+    }
+    let data = vec![Some(true), None, Some(false)];
+
     let expr = And(
-        Box::new(Or(
-            Box::new(Not(Box::new(Var(1)))),
-            Box::new(Var(2)))),
-        Box::new(Or(
-            Box::new(Not(Box::new(Var(3)))),
-            Box::new(Var(4)))));
+        Box::new(Var(OnError::propagate(Lookup(0)))),
+        Box::new(Var(OnError::treat_as_true(Lookup(1)))));
+    assert_eq!(expr.try_eval(&data), Ok(true));
 
-    let s = ron::ser::to_string(&expr).unwrap();
-    assert_eq!(s, "And(Or(Not(Var(1)),Var(2)),Or(Not(Var(3)),Var(4)))");
+    let expr = Or(
+        Box::new(Var(OnError::treat_as_false(Lookup(1)))),
+        Box::new(Var(OnError::propagate(Lookup(5)))));
+    assert_eq!(expr.try_eval(&data), Err(5));
 }
 
-#[cfg(feature = "serde")]
+
 #[test]
-fn deserialize_expr_ron() {
+fn provider_eval_cached() {
     use Expr::*;
+    let mut queried = Vec::new();
+    let mut provider = |v: &u32| { queried.push(*v); *v != 3 };
+
+    // (2 | 3) & !(3 & 2) & 2
     let expr = And(
-        Box::new(Or(
-            Box::new(Not(Box::new(Var(1)))),
-            Box::new(Var(2)))),
-        Box::new(Or(
-            Box::new(Not(Box::new(Var(3)))),
-            Box::new(Var(4)))));
-    let s = "And(Or(Not(Var(1)),Var(2)),Or(Not(Var(3)),Var(4)))";
+        Box::new(And(
+            Box::new(Or(Box::new(Var(2)), Box::new(Var(3)))),
+            Box::new(Not(Box::new(And(Box::new(Var(3)), Box::new(Var(2)))))))),
+        Box::new(Var(2)));
+    assert!(expr.eval_with_provider(&mut provider));
+    assert_eq!(queried, vec![2, 3]);
+}
 
-    use ron::de::Deserializer;
-    let mut d = Deserializer::from_str(&s).unwrap();
-    let res = Expr::deserialize(&mut d).unwrap();
-    d.end().unwrap();
 
-    assert_eq!(res, expr);
+#[test]
+fn eval_without_clone() {
+    use Expr::*;
+    // Evaluation does not require `Clone` or `PartialEq`.
+    struct Even;
+    impl Eval for Even {
+        type Context = u32;
+        fn eval(&self, data: &u32) -> bool { data & 1 == 0 }
+    }
+    let expr = Or(Box::new(Var(Even)), Box::new(Not(Box::new(Var(Even)))));
+    assert!(expr.eval(&3));
+    assert!(Var(Even).eval(&4));
+}
+
+
+#[test]
+fn builder_methods() {
+    use Expr::*;
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    let expr = Expr::from(4).and(!Expr::from(6)).or(3);
+    assert_eq!(expr, Or(
+        Box::new(And(
+            Box::new(Var(4)),
+            Box::new(Not(Box::new(Var(6)))))),
+        Box::new(Var(3))));
+    assert!(expr.eval(&items));
+}
+
+
+#[test]
+fn conjoin_balanced() {
+    use Expr::*;
+    let items: Vec<u32> = vec![1, 2, 4, 5, 7, 9, 10];
+
+    assert_eq!(Expr::<u32>::conjoin(vec![]), None);
+    assert_eq!(Expr::conjoin(vec![Var(1)]), Some(Var(1)));
+
+    let expr = Expr::conjoin((1..=5).map(Var)).unwrap();
+    assert_eq!(expr, And(
+        Box::new(And(
+            Box::new(And(Box::new(Var(1)), Box::new(Var(2)))),
+            Box::new(And(Box::new(Var(3)), Box::new(Var(4)))))),
+        Box::new(Var(5))));
+    assert!(!expr.eval(&items));
+
+    let expr = Expr::disjoin((1..=5).map(Var)).unwrap();
+    assert!(expr.eval(&items));
+}
+
+
+#[test]
+fn subexpr_traversal() {
+    use Expr::*;
+    let expr = And(
+        Box::new(Not(Box::new(Var(1)))),
+        Box::new(Or(Box::new(Var(2)), Box::new(Not(Box::new(Var(3)))))));
+
+    let pre: Vec<_> = expr.iter_subexprs(TraversalOrder::PreOrder)
+        .filter_map(|e| match e { Var(v) => Some(*v), _ => None })
+        .collect();
+    assert_eq!(pre, vec![1, 2, 3]);
+    assert_eq!(expr.iter_subexprs(TraversalOrder::PreOrder).next(),
+        Some(&expr));
+    assert_eq!(expr.iter_subexprs(TraversalOrder::PostOrder).last(),
+        Some(&expr));
+    assert_eq!(expr.iter_subexprs(TraversalOrder::PostOrder).next(),
+        Some(&Var(1)));
+
+    // Find all negated variables.
+    let negated: Vec<_> = expr.iter_subexprs(TraversalOrder::PreOrder)
+        .filter_map(|e| match e {
+            Not(p) => match &**p { Var(v) => Some(*v), _ => None },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(negated, vec![1, 3]);
+}
+
+
+#[test]
+fn replace_by_path() {
+    use Expr::*;
+    use PathStep::*;
+    let mut expr = And(
+        Box::new(Not(Box::new(Var(1)))),
+        Box::new(Or(Box::new(Var(2)), Box::new(Var(3)))));
+
+    let path = ExprPath::from(vec![Right, Left]);
+    assert_eq!(expr.get_path(&path), Some(&Var(2)));
+    assert_eq!(expr.get_path(&ExprPath::from(vec![Left, Left])), None);
+    assert_eq!(expr.get_path(&ExprPath::root()), Some(&expr));
+
+    assert_eq!(expr.replace_at(&path, Var(4)), Some(Var(2)));
+    assert_eq!(expr.replace_at(&ExprPath::from(vec![Right, Child]), Var(5)),
+        None);
+    assert_eq!(expr, And(
+        Box::new(Not(Box::new(Var(1)))),
+        Box::new(Or(Box::new(Var(4)), Box::new(Var(3))))));
+
+    let path = ExprPath::root().child(Left).child(Child);
+    assert_eq!(expr.replace_at(&path, Var(6)), Some(Var(1)));
+}
+
+
+#[test]
+fn nnf_equality() {
+    use Expr::*;
+    // !(1 | 2) vs !1 & !2
+    let a = Not(Box::new(Or(Box::new(Var(1)), Box::new(Var(2)))));
+    let b = And(Box::new(Not(Box::new(Var(1)))), Box::new(Not(Box::new(Var(2)))));
+    assert!(a != b);
+    assert!(a.nnf_eq(&b));
+
+    // (1 & 2) & 3 vs 3 & (2 & !!1)
+    let a = Var(1).and(Var(2)).and(Var(3));
+    let b = Var(3).and(Var(2).and(!!Var(1)));
+    assert!(a.nnf_eq(&b));
+
+    assert!(!Var(1).and(Var(2)).nnf_eq(&Var(1).or(Var(2))));
+    assert!(!Var(1).and(Var(2)).nnf_eq(&Var(1).and(Var(3))));
+
+    // Repeated operands are ignored, even when only one remains.
+    assert!(Var(1).and(Var(2)).and(Var(1)).nnf_eq(&Var(2).and(Var(1))));
+    assert!(Var(1).and(Var(1)).nnf_eq(&Var(1)));
+    assert!((!Var(1).or(Var(1))).nnf_eq(&!Var(1)));
+    assert!(!Var(1).and(Var(1)).nnf_eq(&Var(2)));
+    assert!(!Var(1).and(Var(1)).nnf_eq(&!Var(1)));
+}
+
+
+#[test]
+fn stable_string_canonical() {
+    use Expr::*;
+    let a = Var(1).and(Var(2).and(Var(3))).or(!Var(4));
+    let b = (!Var(4)).or(Var(3).and(Var(1)).and(Var(2)));
+    assert_eq!(a.to_stable_string(), r#"(!("4")|("1"&"2"&"3"))"#);
+    assert_eq!(a.to_stable_string(), b.to_stable_string());
+
+    let quoted = Var("a\"|\"b".to_string()).or(Var("c".to_string()));
+    assert_eq!(quoted.to_stable_string(), r#"("a\"|\"b"|"c")"#);
+}
+
+
+#[test]
+fn content_hash_stable() {
+    use Expr::*;
+    let a = Var(1).and(Var(2)).or(!Var(3));
+    let b = (!Var(3)).or(Var(2).and(Var(1)));
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_eq!(a.content_hash_128(), b.content_hash_128());
+    assert!(a.content_hash() != Var(1).and(Var(2)).content_hash());
+
+    // FNV-1a of the empty-string variable encoding `""`.
+    assert_eq!(Var("").content_hash(), 0x07cc_7607_b494_9e25);
+}
+
+
+#[test]
+fn clausal_validation() {
+    use Expr::*;
+    use PathStep::*;
+    let cnf = CnfVec::from(Var(1).and(Var(2).or(!Var(3))));
+    assert_eq!(cnf.check_clausal(), Ok(()));
+
+    let cnf = CnfVec::from(vec![
+        Var(1).or(!Var(2)),
+        Var(3).or(Var(4).or(!(Var(5).and(Var(6))))),
+    ]);
+    assert_eq!(cnf.check_clausal(), Err(ClauseError {
+        clause: 1,
+        path: ExprPath::from(vec![Right, Right]),
+        violation: ClauseViolation::NegatedNonVariable,
+    }));
+
+    let dnf = DnfHashSet::from(vec![Var(1).and(Var(2).or(Var(3)))]);
+    assert_eq!(dnf.check_clausal(), Err(ClauseError {
+        clause: 0,
+        path: ExprPath::from(vec![Right]),
+        violation: ClauseViolation::UnexpectedOr,
+    }));
+}
+
+
+#[test]
+fn cnf_remove_tautologies() {
+    use Expr::*;
+    let mut cnf = CnfVec::from(vec![
+        Var(1).or(!Var(1)),
+        Var(1).or(Var(2)),
+        Var(2).or(Var(3).or(!Var(2))),
+        !Var(3),
+    ]);
+    cnf.remove_tautologies();
+    assert_eq!(cnf.into_vec(), vec![Var(1).or(Var(2)), !Var(3)]);
+
+    let mut cnf = CnfHashSet::from(vec![Var(1).or(!Var(1)), Var(2)]);
+    cnf.remove_tautologies();
+    assert_eq!(cnf.into_vec(), vec![Var(2)]);
+
+    // A clause which is not a disjunction is not a tautology.
+    let mut cnf = CnfVec::from(vec![Var(1).and(!Var(1)), Var(2)]);
+    cnf.remove_tautologies();
+    assert_eq!(cnf.into_vec(), vec![Var(1).and(!Var(1)), Var(2)]);
+}
+
+
+#[test]
+fn dnf_absorb() {
+    use Expr::*;
+    let mut dnf = DnfVec::from(vec![
+        Var(1).and(Var(2)).and(Var(3)),
+        Var(2).and(Var(1)),
+        Var(4).or(Var(5)),
+        Var(4),
+        Var(1).and(Var(2)),
+    ]);
+    dnf.absorb();
+    assert_eq!(dnf.into_vec(), vec![
+        Var(2).and(Var(1)),
+        Var(4).or(Var(5)),
+        Var(4),
+    ]);
+
+    let mut dnf = DnfHashSet::from(vec![Var(1).and(!Var(2)), Var(1)]);
+    dnf.absorb();
+    assert_eq!(dnf.into_vec(), vec![Var(1)]);
+}
+
+
+#[test]
+fn normal_form_set_operations() {
+    use Expr::*;
+    let base = CnfVec::from(vec![Var(1), Var(2).or(Var(3))]);
+    let over = CnfVec::from(vec![Var(3).or(Var(2)), !Var(4)]);
+
+    assert_eq!(base.clone().union(over.clone()).into_vec(),
+        vec![Var(1), Var(2).or(Var(3)), !Var(4)]);
+    assert_eq!(base.clone().intersection(&over).into_vec(),
+        vec![Var(2).or(Var(3))]);
+    assert_eq!(base.difference(&over).into_vec(), vec![Var(1)]);
+
+    let base = DnfHashSet::from(vec![Var(1), Var(2)]);
+    let over = DnfHashSet::from(vec![Var(2), Var(3)]);
+    assert_eq!(base.clone().union(over.clone()),
+        DnfHashSet::from(vec![Var(1), Var(2), Var(3)]));
+    assert_eq!(base.clone().intersection(&over).into_vec(), vec![Var(2)]);
+    assert_eq!(base.difference(&over).into_vec(), vec![Var(1)]);
+}
+
+
+#[test]
+fn normal_form_assign_ops() {
+    use Expr::*;
+    let mut cnf = CnfVec::default();
+    for rule in [Var(1), Var(2).or(Var(3)), Var(1)] {
+        cnf &= rule;
+    }
+    cnf &= CnfVec::from(vec![!Var(4)]);
+    assert_eq!(cnf.into_vec(), vec![Var(1), Var(2).or(Var(3)), !Var(4)]);
+
+    let mut dnf = DnfHashSet::default();
+    dnf |= Var(1).or(Var(2).and(Var(3)));
+    dnf |= Var(4);
+    assert_eq!(dnf, DnfHashSet::from(vec![
+        Var(1),
+        Var(2).and(Var(3)),
+        Var(4),
+    ]));
+}
+
+
+#[test]
+fn optional_filters() {
+    use Expr::*;
+    assert_eq!(Var(1).and_option(None), Var(1));
+    assert_eq!(Var(1).and_option(Some(Var(2))), Var(1).and(2));
+
+    assert_eq!(combine_all(Some(Vec::<Expr<u32>>::new())), None);
+    assert_eq!(combine_all(Some([Var(1), Var(2), Var(3)])),
+        Some(Var(1).and(2).and(3)));
+}
+
+
+#[test]
+fn cnf_conversion_nested_not() {
+    use Expr::*;
+    let expr = (!(Var(1).and(Var(2)))).or(Var(3)).and(!(!Var(4)));
+    let cnf = CnfVec::from(expr);
+    assert_eq!(cnf.check_clausal(), Ok(()));
+    assert_eq!(cnf.into_vec(), vec![(!Var(1)).or(!Var(2)).or(Var(3)), Var(4)]);
+}
+
+
+#[test]
+fn normal_form_nested_distribution() {
+    use Expr::*;
+    use crate::clausal::is_normal_form;
+    let exprs = vec![
+        Var(1).or(Var(2).and(Var(3))).or(Var(4)),
+        Var(1).or(Var(2).or(Var(3).and(Var(4)))),
+        (Var(1).and(Var(2))).or(Var(3)).or(Var(4).and(!Var(5))),
+        Var(1).and(Var(2).or(Var(3))).and(Var(4)).or(Var(5)),
+        Var(1).and(Var(2).and(Var(3).or(Var(4)))),
+    ];
+    let data: Vec<Vec<u32>> = (0..32u32)
+        .map(|bits| (1..=5).filter(|i| bits & (1 << (i - 1)) != 0).collect())
+        .collect();
+    for expr in exprs {
+        let cnf = CnfVec::from(expr.clone());
+        assert_eq!(cnf.check_clausal(), Ok(()));
+        assert!(cnf.as_ref().iter().all(|clause| is_normal_form(clause, true)));
+        assert!(CountedCnf::new(&cnf).is_ok());
+
+        let dnf = DnfVec::from(expr.clone());
+        assert_eq!(dnf.check_clausal(), Ok(()));
+        assert!(dnf.as_ref().iter().all(|term| is_normal_form(term, false)));
+        assert!(dnf.to_literal_dnf().is_ok());
+
+        for data in &data {
+            assert_eq!(cnf.eval(data), expr.eval(data));
+            assert_eq!(dnf.eval(data), expr.eval(data));
+        }
+    }
+}
+
+
+#[test]
+fn stack_expr_eval() {
+    use Expr::*;
+    use std::convert::TryFrom;
+    let expr = Var(1).and(!Var(2)).or(Var(3));
+    let small = StackExpr::<u32, 6>::try_from(expr.clone()).unwrap();
+    assert_eq!(small.len(), 6);
+    assert_eq!(small.nodes().last(), Some(&Node::Or(3, 4)));
+    assert!(small.eval(&vec![1]));
+    assert!(!small.eval(&vec![1, 2]));
+    assert_eq!(small.to_expr(), expr);
+
+    assert_eq!(StackExpr::<u32, 5>::try_from(expr),
+        Err(CapacityError { capacity: 5, required: 6 }));
+}
+
+
+#[test]
+fn static_expr_eval() {
+    use Expr::*;
+    const RULE: StaticExpr<u32> = StaticExpr::new(&[
+        Node::Var(1),
+        Node::Var(2),
+        Node::Var(3),
+        Node::Or(1, 2),
+        Node::And(0, 3),
+    ]);
+    assert_eq!(RULE.len(), 5);
+    assert!(RULE.eval(&vec![1, 3]));
+    assert!(!RULE.eval(&vec![2, 3]));
+    assert_eq!(RULE.to_expr(), Var(1).and(Var(2).or(Var(3))));
+}
+
+
+#[test]
+fn nand_nor() {
+    use Expr::*;
+    assert_eq!(Expr::nand(Var(1), Var(2)), !(Var(1).and(2)));
+    assert_eq!(Var(1).nor(2), !(Var(1).or(2)));
+
+    let data = vec![1];
+    assert!(Var(1).nand(2).eval(&data));
+    assert!(!Var(1).nand(1).eval(&data));
+    assert!(Var(2).nor(3).eval(&data));
+    assert!(!Var(1).nor(3).eval(&data));
+}
+
+
+#[test]
+fn rename_vars_collisions() {
+    use Expr::*;
+    use std::collections::HashMap;
+    let expr = Var(1).and(Var(2).or(!Var(3)));
+
+    let map: HashMap<u32, u32> = [(1, 10), (2, 3), (3, 2)]
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(expr.rename_vars(&map),
+        Ok(Var(10).and(Var(3).or(!Var(2)))));
+
+    let map: HashMap<u32, u32> = [(1, 3)].iter().copied().collect();
+    assert_eq!(expr.rename_vars(&map), Err(RenameError {
+        target: 3,
+        first: 1,
+        second: 3,
+    }));
+
+    let map: HashMap<u32, u32> = [(1, 5), (2, 5)].iter().copied().collect();
+    assert_eq!(expr.rename_vars(&map), Err(RenameError {
+        target: 5,
+        first: 1,
+        second: 2,
+    }));
+}
+
+
+#[test]
+fn free_vars_extraction() {
+    use Expr::*;
+    use std::collections::HashSet;
+    let expr = Var(1).and(Var(2).or(!Var(1))).or(Var(3));
+    let expected: HashSet<u32> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(expr.free_vars(), expected);
+    assert_eq!(CnfVec::from(expr.clone()).vars(), expected);
+    assert_eq!(DnfHashSet::from(expr).vars(), expected);
+}
+
+
+#[test]
+fn split_independent_components() {
+    use Expr::*;
+    use std::collections::HashSet;
+    // (1 | 2) & 3 & (!4 | 1) & (5 | 6) & !6
+    let expr = Var(1).or(Var(2))
+        .and(Var(3))
+        .and((!Var(4)).or(Var(1)))
+        .and(Var(5).or(Var(6)))
+        .and(!Var(6));
+
+    let components = expr.var_components();
+    let expected: Vec<HashSet<u32>> = vec![
+        [1, 2, 4].iter().copied().collect(),
+        [3].iter().copied().collect(),
+        [5, 6].iter().copied().collect(),
+    ];
+    assert_eq!(components, expected);
+
+    let parts = expr.split_independent();
+    assert_eq!(parts.len(), 3);
+    assert_eq!(format!("{:?}", parts[0]),
+        format!("{:?}", Var(1).or(Var(2)).and((!Var(4)).or(Var(1)))));
+    assert_eq!(format!("{:?}", parts[1]), format!("{:?}", Var(3)));
+    assert_eq!(format!("{:?}", parts[2]),
+        format!("{:?}", Var(5).or(Var(6)).and(!Var(6))));
+}
+
+
+#[test]
+fn cnf_pure_literal_elimination() {
+    use Expr::*;
+    // (1 | !2) & (!1 | 3) & (2 | 3) & (!3 | 4 | 1)
+    let mut cnf = CnfVec::from(vec![
+        Var(1).or(!Var(2)),
+        (!Var(1)).or(Var(3)),
+        Var(2).or(Var(3)),
+        (!Var(3)).or(Var(4)).or(Var(1)),
+    ]);
+    let polarities = cnf.classify_vars();
+    assert_eq!(polarities[&1], Polarity::Both);
+    assert_eq!(polarities[&2], Polarity::Both);
+    assert_eq!(polarities[&3], Polarity::Both);
+    assert_eq!(polarities[&4], Polarity::Positive);
+
+    // Removing the clause with 4 makes 3 pure, and then 1 and 2.
+    let assignment = cnf.eliminate_pure_literals();
+    assert!(cnf.is_empty());
+    assert!(assignment[&4]);
+    assert!(assignment[&3]);
+}
+
+
+#[test]
+fn simplify_traced_steps() {
+    use Expr::*;
+    // !(!!1 | (2 & 2))
+    let expr = !((!!Var(1)).or(Var(2).and(Var(2))));
+    let (simplified, steps) = expr.clone().simplify_traced();
+
+    assert_eq!(simplified, expr.simplify());
+    let rules: Vec<_> = steps.iter().map(|step| step.rule).collect();
+    assert_eq!(rules, vec![
+        SimplifyRule::DeMorgan,
+        SimplifyRule::DoubleNegation,
+        SimplifyRule::DeMorgan,
+        SimplifyRule::Idempotence,
+    ]);
+    assert_eq!(steps[3].before, (!Var(2)).or(!Var(2)));
+    assert_eq!(steps[3].after, !Var(2));
+}
+
+
+#[test]
+fn mutate_expression() {
+    use Expr::*;
+    use crate::mutate::*;
+    // (1 & !2)
+    let expr = Var(1).and(!Var(2));
+    let all = mutants(&expr);
+    let kinds: Vec<_> = all.iter().map(|m| m.kind).collect();
+    assert_eq!(kinds, vec![
+        MutationKind::SwapOperator,
+        MutationKind::InsertNegation,
+        MutationKind::InsertNegation,
+        MutationKind::SwapVariable,
+        MutationKind::RemoveNegation,
+        MutationKind::InsertNegation,
+        MutationKind::SwapVariable,
+    ]);
+    assert_eq!(all[0].expr, Var(1).or(!Var(2)));
+    assert_eq!(all[4].expr, Var(1).and(Var(2)));
+    assert_eq!(all[6].expr, Var(1).and(!Var(1)));
+    assert_eq!(all[6].path,
+        ExprPath::from(vec![PathStep::Right, PathStep::Child]));
+
+    let sample = sample_mutants(&expr, 3, 7);
+    assert_eq!(sample.len(), 3);
+    assert_eq!(sample, sample_mutants(&expr, 3, 7));
+    assert!(sample.iter().all(|m| all.contains(m)));
+}
+
+
+#[test]
+fn distinguishing_model_search() {
+    use Expr::*;
+    // De Morgan's law holds.
+    let a = !(Var(1).and(Var(2)));
+    let b = (!Var(1)).or(!Var(2));
+    assert_eq!(a.distinguishing_model(&b), None);
+
+    // A common mistake in applying it does not.
+    let c = (!Var(1)).and(!Var(2));
+    let model = a.distinguishing_model(&c).unwrap();
+    assert_eq!(model.len(), 2);
+    let items: Vec<u32> = model.iter()
+        .filter_map(|(var, value)| if *value { Some(*var) } else { None })
+        .collect();
+    assert_ne!(a.eval(&items), c.eval(&items));
+
+    // Extra variables must be assigned to tell these apart.
+    let d = Var(1).or(Var(2).and(Var(3)).and(!Var(4)));
+    let model = Var(1).distinguishing_model(&d).unwrap();
+    assert!(!model[&1] && model[&2] && model[&3] && !model[&4]);
+}
+
+
+#[test]
+fn exhaustive_assertions() {
+    use Expr::*;
+    use crate::testing::*;
+    let a = Var(1).and(Var(2).or(Var(3)));
+    let b = Var(1).and(Var(2)).or(Var(1).and(Var(3)));
+    assert_equiv_exhaustive(&a, &b, &[1, 2, 3]);
+    assert_implies_exhaustive(&Var(1).and(Var(2)), &a, &[1, 2, 3]);
+
+    let failure = std::panic::catch_unwind(|| {
+        assert_equiv_exhaustive(&a, &Var(1), &[1, 2, 3]);
+    }).unwrap_err();
+    let message = failure.downcast_ref::<String>().unwrap();
+    assert!(message.contains(
+        "under assignment {1 = true, 2 = false, 3 = false}: \
+        left is false, right is true"), "{}", message);
+
+    let failure = std::panic::catch_unwind(|| {
+        assert_implies_exhaustive(&Var(1), &a, &[1, 2]);
+    }).unwrap_err();
+    let message = failure.downcast_ref::<String>().unwrap();
+    assert!(message.contains("variable 3 is not in the given variables"));
+}
+
+
+#[test]
+fn stack_expr_display_shared() {
+    use Expr::*;
+    use std::convert::TryFrom;
+    // A tree has no shared nodes.
+    let expr = Var(1).and(!Var(2));
+    let small = StackExpr::<u32, 4>::try_from(expr).unwrap();
+    assert_eq!(small.display_shared().to_string(), "(1 & !2)");
+
+    static RULE: StaticExpr<u32> = StaticExpr::new(&[
+        Node::Var(1),
+        Node::Var(2),
+        Node::Or(0, 1),
+        Node::Not(2),
+        Node::And(2, 3),
+        Node::And(4, 4),
+        Node::Or(4, 5),
+    ]);
+    assert_eq!(RULE.display_shared().to_string(),
+        "#1 = (1 | 2)\n#2 = (#1 & !#1)\n(#2 | (#2 & #2))");
+}
+
+
+#[test]
+fn indexed_cnf_stays_in_sync() {
+    use Expr::*;
+    let mut indexed = CnfVec::from(vec![
+        Var(1).or(Var(2)),
+        Var(2).or(!Var(3)),
+        Var(3).or(Var(4)),
+    ]).index_by_var();
+    assert_eq!(indexed.clauses_with(&3), &[1, 2]);
+
+    // Removing the first clause moves the last into its place.
+    assert_eq!(indexed.remove(0), Var(1).or(Var(2)));
+    assert!(indexed.clauses_with(&1).is_empty());
+    assert_eq!(indexed.clauses_with(&2), &[1]);
+    assert_eq!(indexed.clauses_with(&4), &[0]);
+
+    let _ = indexed.replace(1, Var(4).or(Var(5)));
+    assert!(indexed.clauses_with(&2).is_empty());
+    let mut with_4 = indexed.clauses_with(&4).to_vec();
+    with_4.sort_unstable();
+    assert_eq!(with_4, vec![0, 1]);
+
+    assert_eq!(indexed.push(Var(1)), 2);
+    assert_eq!(indexed.clauses_with(&1), &[2]);
+    assert!(indexed.eval(&vec![1, 4]));
+    assert!(!indexed.eval(&vec![1, 3]));
+}
+
+
+#[test]
+fn counted_cnf_matches_naive_evaluation() {
+    use Expr::*;
+    let mut rng = rng::Rng::new(1449);
+    let clauses: Vec<Vec<(u32, bool)>> = (0..12)
+        .map(|_| (0..1 + rng.below(4))
+            .map(|_| (1 + rng.below(6) as u32, rng.below(2) == 0))
+            .collect())
+        .collect();
+    let cnf = CnfVec::from(clauses.iter()
+        .map(|lits| Expr::disjoin(lits.iter()
+            .map(|&(v, pos)| if pos { Var(v) } else { !Var(v) }))
+            .unwrap())
+        .collect::<Vec<_>>());
+    let mut counted = CountedCnf::new(&cnf).unwrap();
+    let mut values: [Option<bool>; 7] = [None; 7];
+
+    for _ in 0..2000 {
+        let var = 1 + rng.below(6) as u32;
+        let old = values[var as usize];
+        let new = if rng.below(3) == 0 { None } else { Some(rng.below(2) == 0) };
+        values[var as usize] = new;
+        let propagation = match new {
+            Some(value) => counted.assign(&var, value),
+            None        => { counted.unassign(&var); Propagation::default() },
+        };
+
+        let lit = |&(v, pos): &(u32, bool)| values[v as usize].map(|x| x == pos);
+        let mut expected = Propagation::default();
+        if let (Some(value), true) = (new, new != old) {
+            for (idx, lits) in clauses.iter().enumerate() {
+                if !lits.contains(&(var, !value)) { continue; }
+                if lits.iter().any(|l| lit(l) == Some(true)) { continue; }
+                let mut open: Vec<_> = lits.iter()
+                    .filter(|l| lit(l).is_none())
+                    .collect();
+                open.dedup();
+                match open.len() {
+                    0 => expected.conflicts.push(idx),
+                    1 => expected.units.push(idx),
+                    _ => (),
+                }
+            }
+        }
+        let mut actual = propagation;
+        actual.units.sort_unstable();
+        actual.conflicts.sort_unstable();
+        assert_eq!(actual, expected);
+
+        let naive = if clauses.iter()
+            .any(|lits| lits.iter().all(|l| lit(l) == Some(false)))
+        {
+            Some(false)
+        } else if clauses.iter()
+            .all(|lits| lits.iter().any(|l| lit(l) == Some(true)))
+        {
+            Some(true)
+        } else {
+            None
+        };
+        assert_eq!(counted.value(), naive);
+    }
+}
+
+
+#[test]
+fn complexity_limits() {
+    use Expr::*;
+    // (1 & !(2 | 1)) | 3
+    let expr = Var(1).and(!Var(2).or(Var(1))).or(Var(3));
+    assert_eq!(expr.check_limits(&ComplexityLimit::default()), Ok(()));
+    let fits = ComplexityLimit {
+        max_depth: Some(5),
+        max_nodes: Some(8),
+        max_vars: Some(3),
+    };
+    assert_eq!(expr.check_limits(&fits), Ok(()));
+
+    assert_eq!(
+        expr.check_limits(&ComplexityLimit { max_depth: Some(3), ..fits }),
+        Err(LimitViolation::Depth {
+            limit: 3,
+            path: ExprPath::from(vec![
+                PathStep::Left,
+                PathStep::Right,
+                PathStep::Child,
+            ]),
+        }));
+    assert_eq!(
+        expr.check_limits(&ComplexityLimit { max_nodes: Some(7), ..fits }),
+        Err(LimitViolation::Nodes { limit: 7, actual: 8 }));
+    assert_eq!(
+        expr.check_limits(&ComplexityLimit { max_vars: Some(2), ..fits }),
+        Err(LimitViolation::Vars { limit: 2, actual: 3 }));
+}
+
+
+#[test]
+fn parse_expressions() {
+    use Expr::*;
+    let expr: Expr<u32> = "1 & !2 | 3 && (4 || !!5)".parse().unwrap();
+    assert_eq!(format!("{:?}", expr), format!("{:?}", Var(1).and(!Var(2))
+        .or(Var(3).and(Var(4).or(!!Var(5))))));
+
+    // Deep nesting is parsed without recursion.
+    let deep = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+    assert_eq!(Expr::<u32>::parse(&deep), Ok(Var(1)));
+
+    let err = |src: &str| Expr::<u32>::parse(src).unwrap_err();
+    assert_eq!(err("1 &"), ParseError {
+        offset: 3,
+        kind: ParseErrorKind::UnexpectedEnd,
+    });
+    assert_eq!(err("1 2"), ParseError {
+        offset: 2,
+        kind: ParseErrorKind::UnexpectedToken("2".into()),
+    });
+    assert_eq!(err("(1 | 2"), ParseError {
+        offset: 0,
+        kind: ParseErrorKind::UnclosedParen,
+    });
+    assert_eq!(err("1)"), ParseError {
+        offset: 1,
+        kind: ParseErrorKind::UnmatchedParen,
+    });
+    assert_eq!(err("1 & \"2"), ParseError {
+        offset: 4,
+        kind: ParseErrorKind::UnterminatedQuote,
+    });
+    assert_eq!(err("1 & x").to_string(),
+        "invalid variable \"x\": invalid digit found in string at offset 4");
+}
+
+#[test]
+fn compile_predicate() {
+    use Expr::*;
+    let options = CompileOptions {
+        limit: ComplexityLimit { max_vars: Some(2), ..Default::default() },
+        cnf: true,
+    };
+    let predicate = Predicate::compile::<u32>("!(1 & !2)", &options).unwrap();
+    assert_eq!(predicate.expr(), &(!Var(1)).or(Var(2)));
+    assert!(predicate.cnf().is_some());
+    assert!(predicate.eval(&vec![2]));
+    assert!(!predicate.eval(&vec![1]));
+
+    assert_eq!(
+        Predicate::compile::<u32>("1 | 2 | 3", &options).unwrap_err(),
+        CompileError::Limit(LimitViolation::Vars { limit: 2, actual: 3 }));
+    assert!(matches!(
+        Predicate::compile::<u32>("1 |", &options).unwrap_err(),
+        CompileError::Parse(_)));
+
+    // Input deeper than the limit is rejected before it is built.
+    let options = CompileOptions {
+        limit: ComplexityLimit { max_depth: Some(64), ..Default::default() },
+        cnf: false,
+    };
+    let deep = format!("{}1", "!".repeat(100_000));
+    assert_eq!(
+        Predicate::compile::<u32>(&deep, &options).unwrap_err(),
+        CompileError::Parse(ParseError {
+            offset: 63,
+            kind: ParseErrorKind::TooDeep { limit: 64 },
+        }));
+}
+
+#[test]
+fn parse_with_max_depth() {
+    use Expr::*;
+    let parse = |src: &str, max| Expr::<u32>::parse_with_max_depth(src, max);
+    assert_eq!(parse("1 & !2", 3), Ok(Var(1).and(!Var(2))));
+    assert_eq!(parse("((1))", 1), Ok(Var(1)));
+
+    let too_deep = |offset| Err(ParseError {
+        offset,
+        kind: ParseErrorKind::TooDeep { limit: 3 },
+    });
+    assert_eq!(parse("1 & !!2", 3), too_deep(5));
+    assert_eq!(parse("1 & 2 & 3 & 4", 3), too_deep(13));
+    assert_eq!(parse("(1 | !2) & !3", 3), too_deep(13));
+    assert_eq!(parse("(1 | !2) & 3", 3), too_deep(12));
+}
+
+
+#[test]
+fn eval_borrowed_slice_context() {
+    use Expr::*;
+    struct AtLeast(u32);
+    impl EvalBorrowed for AtLeast {
+        type Context<'a> = &'a [u32];
+        fn eval_borrowed(&self, data: &&[u32]) -> bool {
+            data.iter().any(|x| *x >= self.0)
+        }
+    }
+
+    let expr = Var(AtLeast(5)).and(!Var(AtLeast(10)));
+    let items = [1, 2, 7];
+    assert!(expr.eval_borrowed(&&items[..]));
+    assert!(!expr.eval_borrowed(&&items[..2]));
+}
+
+
+#[test]
+fn fn_var_labels() {
+    use Expr::*;
+    use std::collections::HashSet;
+    let threshold = 10;
+    let big = FnVar::new("big", move |x: &u32| *x > threshold);
+    let small = FnVar::new("small", |x: &u32| *x < 5);
+    let expr = Var(big.clone()).or(Var(small.clone()));
+    assert!(expr.eval(&11));
+    assert!(expr.eval(&4));
+    assert!(!expr.eval(&7));
+
+    // Equality and hashing use only the label.
+    let other_small = FnVar::new("small", |x: &u32| *x < 2);
+    assert_eq!(small, other_small);
+    let labels: HashSet<_> = vec![big, small, other_small].into_iter().collect();
+    assert_eq!(labels.len(), 2);
+
+    let cnf = CnfHashSet::from(expr);
+    assert!(cnf.eval(&12));
+    assert_eq!(format!("{:?}", FnVar::new("a", |_: &u32| true)),
+        "FnVar(\"a\")");
+}
+
+
+#[test]
+fn rule_store_reload() {
+    let store = RuleStore::<u32>::new(CompileOptions::default());
+    assert_eq!(store.version(), 0);
+    assert!(store.get("a").is_none());
+
+    let v1 = store.reload(vec![("a", "1 & 2"), ("b", "!3")]).unwrap();
+    assert_eq!(v1, 1);
+    let old = store.snapshot();
+    let (version, a) = store.get("a").unwrap();
+    assert_eq!(version, 1);
+    assert!(a.eval(&vec![1, 2]));
+
+    // A failed reload keeps the current rules.
+    let err = store.reload(vec![("a", "1"), ("c", "1 &")]).unwrap_err();
+    assert!(matches!(&err,
+        RuleStoreError::Compile { name, .. } if name == "c"));
+    assert_eq!(store.version(), 1);
+
+    assert_eq!(store.reload(vec![("a", "1 | 2")]).unwrap(), 2);
+    assert!(store.get("a").unwrap().1.eval(&vec![1]));
+    assert!(store.get("b").is_none());
+
+    // Handles taken before the reload still see the old rules.
+    assert_eq!(old.version(), 1);
+    assert_eq!(old.len(), 2);
+    assert!(!old.get("a").unwrap().eval(&vec![1]));
+}
+
+
+#[test]
+fn eval_cache_by_context_lru() {
+    use Expr::*;
+    #[derive(Hash)]
+    struct Keyed(u32);
+    impl Eval for Keyed {
+        type Context = (u64, Vec<u32>);
+        fn eval(&self, data: &Self::Context) -> bool {
+            data.1.contains(&self.0)
+        }
+    }
+
+    let mut cache = EvalCacheByContext::new(2, |data: &(u64, Vec<u32>)| data.0);
+    let a = Var(Keyed(1));
+    let b = Var(Keyed(2));
+    let ctx1 = (1, vec![1]);
+    let ctx2 = (2, vec![2]);
+
+    assert!(cache.eval(&a, &ctx1));
+    assert!(!cache.eval(&a, &ctx2));
+    assert!(cache.eval(&a, &ctx1));
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+    // Evicts (a, ctx2), the least recently used.
+    assert!(cache.eval(&b, &ctx2));
+    assert_eq!(cache.len(), 2);
+    assert!(cache.eval(&a, &ctx1));
+    assert_eq!((cache.hits(), cache.misses()), (2, 3));
+    assert!(!cache.eval(&a, &ctx2));
+    assert_eq!((cache.hits(), cache.misses()), (2, 4));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+
+#[test]
+fn cnf_eval_many() {
+    use Expr::*;
+    let expr = Var(1).or(Var(2)).and(!Var(3));
+    let ctxs = vec![vec![1], vec![2, 3], vec![], vec![1, 2]];
+    let expected: Vec<bool> = ctxs.iter().map(|c| expr.eval(c)).collect();
+    assert_eq!(expected, vec![true, false, false, true]);
+
+    assert_eq!(CnfVec::from(expr.clone()).eval_many(&ctxs), expected);
+    assert_eq!(CnfHashSet::from(expr).eval_many(&ctxs), expected);
+    assert_eq!(CnfVec::<u32>::default().eval_many(&ctxs), vec![true; 4]);
+}
+
+
+#[test]
+fn optimize_with_profile_reorders_operands() {
+    use Expr::*;
+    let mut stats = EvalStats::new();
+    for i in 0..10 {
+        stats.record(1, i < 9);
+        stats.record(2, i < 1);
+    }
+    assert_eq!(stats.var_stats(&1), Some(&VarStats { evals: 10, trues: 9 }));
+
+    // Rarely true operands go first in `And` chains and last in `Or` chains.
+    let expr = Var(1).and(Var(2)).or(Var(2).or(Var(1)));
+    let optimized = expr.optimize_with_profile(&stats);
+    assert_eq!(optimized, Var(1).or(Var(2)).or(Var(2).and(Var(1))));
+    testing::assert_equiv_exhaustive(&expr, &optimized, &[1, 2]);
+
+    // Cheap operands go first when selectivities are equal.
+    stats.set_cost(3, 5.0);
+    let expr = Var(3).and(!Var(4));
+    assert_eq!(expr.optimize_with_profile(&stats), (!Var(4)).and(Var(3)));
+
+    // Statistics can be gathered from observed evaluations.
+    let mut observed = EvalStats::new();
+    let _ = Var(5).and(Var(6)).eval_observed(&vec![6], &mut observed);
+    assert_eq!(observed.selectivity(&5), 0.0);
+    assert_eq!(observed.var_stats(&6), None);
+}
+
+
+#[test]
+fn normal_form_fast_path() {
+    use Expr::*;
+    let clauses = vec![
+        Var(1).or(!Var(2)),
+        Var(3),
+        (!Var(4)).or(Var(5)).or(Var(6)),
+    ];
+    let cnf = Expr::conjoin(clauses.clone()).unwrap();
+    assert_eq!(cnf.clone().split_top_and(), clauses);
+    assert_eq!(Var(1).split_top_and(), vec![Var(1)]);
+    assert_eq!(Var(1).or(Var(2).and(Var(3))).split_top_or(),
+        vec![Var(1), Var(2).and(Var(3))]);
+
+    // Input already in normal form converts to the same clauses.
+    let from_cnf: Vec<_> = CnfVec::from(cnf.clone()).into();
+    assert_eq!(from_cnf, clauses);
+    assert!(CnfHashSet::from(cnf).check_clausal().is_ok());
+
+    let dnf = Var(1).and(!Var(2)).or(Var(3));
+    let terms: Vec<_> = DnfVec::from(dnf).into();
+    assert_eq!(terms, vec![Var(1).and(!Var(2)), Var(3)]);
+
+    // Input not in normal form is still converted.
+    let expr = (!Var(1).and(Var(2))).and(Var(3).or(Var(4).and(Var(5))));
+    let cnf = CnfVec::from(expr);
+    assert!(cnf.check_clausal().is_ok());
+    assert_eq!(cnf.into_vec().len(), 3);
+
+    // Repeated operands are merged whichever path is taken.
+    let from_repeated: Vec<_> = CnfVec::from(Var(1).and(Var(1))).into();
+    assert_eq!(from_repeated, vec![Var(1)]);
+    let from_repeated: Vec<_> = CnfVec::from(Var(1).or(Var(1)).and(Var(2)))
+        .into();
+    let from_negated: Vec<_> = CnfVec::from(Var(1).or(Var(1)).and(!!Var(2)))
+        .into();
+    assert_eq!(from_repeated, from_negated);
+    assert_eq!(from_repeated, vec![Var(1), Var(2)]);
+}
+
+
+#[test]
+fn deciding_clauses() {
+    use Expr::*;
+    let dnf = DnfVec::from(vec![Var(1).and(Var(2)), Var(3), !Var(4)]);
+    assert_eq!(dnf.first_matching_clause(&vec![3, 4]), Some(&Var(3)));
+    assert_eq!(dnf.first_matching_clause(&vec![1, 2, 3]),
+        Some(&Var(1).and(Var(2))));
+    assert_eq!(dnf.first_matching_clause(&vec![1, 4]), None);
+    let dnf = DnfHashSet::from(vec![Var(3)]);
+    assert_eq!(dnf.first_matching_clause(&vec![3]), Some(&Var(3)));
+
+    let cnf = CnfVec::from(vec![Var(1).or(Var(2)), !Var(3)]);
+    assert_eq!(cnf.first_failing_clause(&vec![2, 3]), Some(&!Var(3)));
+    assert_eq!(cnf.first_failing_clause(&vec![3]), Some(&Var(1).or(Var(2))));
+    assert_eq!(cnf.first_failing_clause(&vec![1]), None);
+    let cnf = CnfHashSet::from(vec![!Var(3)]);
+    assert_eq!(cnf.first_failing_clause(&vec![3]), Some(&!Var(3)));
+}
+
+
+#[test]
+fn crate_error_conversions() {
+    use Expr::*;
+    use std::error::Error as _;
+    fn compile(src: &str) -> Result<CnfVec<u32>, Error> {
+        let options = CompileOptions {
+            limit: ComplexityLimit { max_nodes: Some(4), ..Default::default() },
+            cnf: true,
+        };
+        let predicate = Predicate::compile::<u32>(src, &options)?;
+        Ok(predicate.cnf().cloned().unwrap_or_default())
+    }
+    assert!(compile("1 | !2").is_ok());
+    assert!(matches!(compile("(1"), Err(Error::Parse(_))));
+    let err = compile("1 & 2 & 3").unwrap_err();
+    assert!(matches!(err, Error::Limit(LimitViolation::Nodes { .. })));
+    assert!(err.source().is_some());
+
+    let map = vec![(1, 3), (2, 3)].into_iter().collect();
+    let err = Error::from(Var(1).and(Var(2)).rename_vars(&map).unwrap_err());
+    assert!(matches!(err, Error::Rename(_)));
+    assert_eq!(err.source().map(|e| e.to_string()),
+        Some("renaming would merge variables 1 and 2 into 3".to_string()));
+
+    let deadline = std::time::Instant::now();
+    let err = Error::from(Var(1).and(Var(2).or(Var(3)))
+        .eval_with_deadline(&vec![1], deadline)
+        .unwrap_err());
+    assert_eq!(err, Error::Deadline(DeadlinePassed {
+        deadline,
+        residual_nodes: 5,
+    }));
+    assert!(err.source().is_some());
+    assert_eq!(Error::from(BudgetExceeded { fuel: 2 }).to_string(),
+        "evaluation budget of 2 exceeded");
+    assert_eq!(Error::eval("no such field").to_string(),
+        "evaluation error: no such field");
+}
+
+
+#[test]
+fn satisfied_clause_counts() {
+    use Expr::*;
+    let clauses = vec![Var(1).or(Var(2)), !Var(3), Var(4)];
+    let cnf = CnfVec::from(clauses.clone());
+    assert_eq!(cnf.satisfied_count(&vec![1, 3]), 1);
+    assert_eq!(cnf.satisfied_count(&vec![2, 4]), 3);
+    assert_eq!(CnfHashSet::from(clauses).satisfied_count(&vec![]), 1);
+
+    let terms = vec![Var(1).and(Var(2)), Var(2), !Var(3)];
+    let dnf = DnfVec::from(terms.clone());
+    assert_eq!(dnf.satisfied_count(&vec![1, 2]), 3);
+    assert_eq!(dnf.satisfied_count(&vec![3]), 0);
+    assert_eq!(DnfHashSet::from(terms).satisfied_count(&vec![2, 3]), 1);
+}
+
+
+#[test]
+fn shared_predicate_across_threads() {
+    use Expr::*;
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedPredicate<u32>>();
+
+    let compiled = Predicate::compile::<u32>("1 & !(2 | 3)", &CompileOptions {
+        cnf: true,
+        ..Default::default()
+    }).unwrap();
+    let shared = SharedPredicate::from(compiled);
+    assert!(shared.cnf().is_some());
+    let handles: Vec<_> = (1..=3)
+        .map(|i| {
+            let shared = shared.clone();
+            std::thread::spawn(move || shared.eval(&vec![1, i]))
+        })
+        .collect();
+    let results: Vec<bool> = handles.into_iter()
+        .map(|h| h.join().unwrap())
+        .collect();
+    assert_eq!(results, vec![true, false, false]);
+
+    let shared = SharedPredicate::from(!(Var(1).or(Var(2))));
+    assert_eq!(shared.expr(), &(!Var(1)).and(!Var(2)));
+    assert!(shared.cnf().is_none());
+}
+
+
+#[test]
+fn shrink_preserves_property() {
+    use Expr::*;
+    let expr = Var(1).and(Var(2).or(!Var(3))).and(Var(4).or(Var(5)));
+    let ctx = vec![1, 4];
+
+    // Minimize while still true on the context and false without 4.
+    let shrunk = expr.clone().shrink(|e| e.eval(&ctx) && !e.eval(&vec![1]));
+    assert_eq!(shrunk, Var(4));
+
+    // The result is unchanged if the property does not hold to begin with.
+    assert_eq!(expr.clone().shrink(|e| !e.eval(&ctx)), expr);
+
+    let shrunk = expr.shrink(|e| e.eval(&vec![1, 2]) != e.eval(&vec![1, 2, 5]));
+    assert_eq!(shrunk, Var(5));
+}
+
+
+#[test]
+fn step_through_evaluator() {
+    use Expr::*;
+    let expr = Var(1).or((!Var(2)).and(Var(3)));
+    let data = vec![3];
+    let mut evaluator = Evaluator::new(&expr, &data);
+    let root = ExprPath::root();
+    let right = root.child(PathStep::Right);
+
+    assert_eq!(evaluator.next_step(), Step::Var {
+        path: root.child(PathStep::Left),
+        var: &1,
+        value: false,
+    });
+    assert!(matches!(evaluator.next_step(),
+        Step::Var { var: &2, value: false, .. }));
+    assert!(matches!(evaluator.next_step(),
+        Step::Op { value: true, short_circuit: false, .. }));
+    assert!(matches!(evaluator.next_step(),
+        Step::Var { var: &3, value: true, .. }));
+    assert_eq!(evaluator.result(), None);
+    // Override the result of the conjunction.
+    let step = evaluator.next_step();
+    assert!(matches!(&step,
+        Step::Op { path, value: true, .. } if *path == right));
+    evaluator.override_value(false);
+    assert!(matches!(evaluator.next_step(),
+        Step::Op { value: false, short_circuit: false, .. }));
+    assert_eq!(evaluator.next_step(), Step::Done(false));
+    assert_eq!(evaluator.result(), Some(false));
+
+    // Short-circuiting skips the right operand.
+    let data = vec![1];
+    let mut evaluator = Evaluator::new(&expr, &data);
+    assert!(matches!(evaluator.next_step(), Step::Var { var: &1, .. }));
+    assert!(matches!(evaluator.next_step(),
+        Step::Op { value: true, short_circuit: true, .. }));
+    assert_eq!(evaluator.next_step(), Step::Done(true));
+
+    for data in [vec![], vec![1], vec![2], vec![2, 3], vec![3]].iter() {
+        assert_eq!(Evaluator::new(&expr, data).finish(), expr.eval(data));
+    }
+}
+
+
+#[test]
+fn eval_with_overrides() {
+    use Expr::*;
+    use std::collections::HashMap;
+    let expr = Var(1).or(Var(2)).and(!Var(3));
+    let data = vec![1, 3];
+    assert!(!expr.eval(&data));
+    assert!(!expr.eval_with_overrides(&data, &HashMap::new()));
+
+    let overrides: HashMap<u32, bool> = vec![(3, false)].into_iter().collect();
+    assert!(expr.eval_with_overrides(&data, &overrides));
+    let overrides: HashMap<u32, bool> = vec![(1, false), (3, false)]
+        .into_iter()
+        .collect();
+    assert!(!expr.eval_with_overrides(&data, &overrides));
+    assert!(expr.eval_with_overrides(&vec![2], &overrides));
+}
+
+
+#[test]
+fn eval_delta_between_contexts() {
+    use Expr::*;
+    use std::collections::HashSet;
+    let expr = Var(1).and(Var(2).or(Var(3)));
+    let changed: HashSet<u32> = vec![3].into_iter().collect();
+
+    // Unchanged variables decide the result.
+    assert_eq!(expr.eval_delta(&vec![2], &vec![2, 3], &changed),
+        Delta::Unaffected(false));
+    let delta = expr.eval_delta(&vec![1, 2], &vec![1, 2, 3], &changed);
+    assert_eq!(delta, Delta::Unaffected(true));
+    assert!(!delta.is_changed());
+
+    // The changed variable decides the result.
+    let delta = expr.eval_delta(&vec![1], &vec![1, 3], &changed);
+    assert_eq!(delta, Delta::Reevaluated { old: false, new: true });
+    assert!(delta.is_changed());
+    assert!(delta.value());
+    let delta = expr.eval_delta(&vec![1, 3], &vec![1, 3], &changed);
+    assert!(!delta.is_changed());
+}
+
+
+#[test]
+fn lint_structural_warnings() {
+    use Expr::*;
+    use PathStep::*;
+    let path = |steps: &[PathStep]| steps.iter().copied().collect::<ExprPath>();
+    let lint = |e: &Expr<u32>| e.lint()
+        .into_iter()
+        .map(|w| (w.kind, w.path))
+        .collect::<Vec<_>>();
+
+    assert_eq!(lint(&Var(1).and(!Var(2)).or(Var(3))), vec![]);
+    assert_eq!(lint(&!!Var(1)), vec![(LintKind::DoubleNegation, path(&[]))]);
+    assert_eq!(lint(&Var(1).and(Var(2)).and(Var(1))), vec![
+        (LintKind::DuplicateOperand, path(&[Right])),
+    ]);
+    assert_eq!(lint(&Var(2).or(Var(1).and(!Var(1)))), vec![
+        (LintKind::Contradiction, path(&[Right])),
+    ]);
+    assert_eq!(lint(&(!Var(1)).or(Var(2).or(Var(1)))), vec![
+        (LintKind::Tautology, path(&[])),
+    ]);
+
+    // Nested chains of the same operator are checked once, as a whole.
+    let expr = Var(1).and(Var(2)).and(Var(3).and(Var(2))).or(!!Var(4));
+    assert_eq!(lint(&expr), vec![
+        (LintKind::DuplicateOperand, path(&[Left, Right, Right])),
+        (LintKind::DoubleNegation, path(&[Right])),
+    ]);
+    assert_eq!(expr.lint()[1].to_string(), "double negation at [Right]");
+
+    // Repeated subexpressions are found anywhere in the expression, in any
+    // operand order, and are not reported again as duplicated operands.
+    let expr = Var(1).or(Var(2)).and(Var(3)).or(Var(2).or(Var(1)).and(Var(4)));
+    assert_eq!(lint(&expr), vec![
+        (LintKind::DuplicateSubexpr, path(&[Right, Left])),
+    ]);
+    let repeated = Var(1).or(Var(2)).and(Var(3));
+    let expr = repeated.clone().or(!repeated.clone()).or(repeated);
+    assert_eq!(lint(&expr), vec![
+        (LintKind::DuplicateOperand, path(&[Right])),
+        (LintKind::DuplicateSubexpr, path(&[Left, Right, Child])),
+    ]);
+}
+
+
+#[test]
+fn display_with_de_morgan() {
+    use Expr::*;
+    let show = |e: &Expr<u32>| e.display().to_string();
+    let sugar = |e: &Expr<u32>| e.display().de_morgan(true).to_string();
+
+    let expr = Var(1).or(Var(2).and(!Var(3).or(Var(4))));
+    assert_eq!(show(&expr), "1 | 2 & !(3 | 4)");
+    assert_eq!(Expr::<u32>::parse(&show(&expr)).unwrap(), expr);
+
+    // Gathering requires at least two negated operands, and a strictly
+    // shorter result.
+    let expr = (!Var(1)).and(Var(2));
+    assert_eq!(sugar(&expr), "!1 & 2");
+    let expr = (!Var(1)).and(!Var(2));
+    assert_eq!(sugar(&expr), "!1 & !2");
+    let expr = (!Var(1)).and(!Var(2)).and(!Var(3));
+    assert_eq!(sugar(&expr), "!1 & !2 & !3");
+    let expr = (!Var(1)).or(Var(2)).or(!Var(3).and(Var(4)));
+    assert_eq!(show(&expr), "!1 | 2 | !(3 & 4)");
+    assert_eq!(sugar(&expr), "!(1 & 3 & 4) | 2");
+
+    // The simplified form is shown as written before simplification.
+    let written = !(Var(1).or(Var(2)).or(Var(3)).or(Var(4)));
+    let simplified = written.clone().simplify();
+    assert_eq!(show(&simplified), "!1 & !2 & !3 & !4");
+    assert_eq!(sugar(&simplified), show(&written));
+    let reparsed = Expr::<u32>::parse(&sugar(&simplified)).unwrap();
+    testing::assert_equiv_exhaustive(&reparsed, &simplified, &[1, 2, 3, 4]);
+}
+
+
+#[test]
+fn var_indexer_round_trip() {
+    use Expr::*;
+    let mut indexer = VarIndexer::new();
+    assert_eq!(indexer.index(&30), 0);
+    let expr = Var(10).and(Var(30).or(!Var(20)));
+    let indexed = indexer.index_expr(&expr);
+    assert_eq!(indexed, Var(1).and(Var(0).or(!Var(2))));
+    assert_eq!(indexer.vars(), &[30, 10, 20]);
+    assert_eq!(indexer.get(&20), Some(2));
+    assert_eq!(indexer.get(&40), None);
+    assert_eq!(indexer.var(1), Some(&10));
+    assert_eq!(indexer.resolve_expr(&indexed), Some(expr));
+    assert_eq!(indexer.resolve_expr(&Var(0).or(Var(3))), None);
+}
+
+
+#[test]
+fn bitset_assignment() {
+    use Expr::*;
+    use std::collections::HashMap;
+    let mut assignment = Assignment::new();
+    assert!(assignment.is_empty());
+    assignment.set(3, true);
+    assignment.set(70, false);
+    assignment.set(5, true);
+    assignment.set(5, false);
+    assert_eq!(assignment.get(3), Some(true));
+    assert_eq!(assignment.get(5), Some(false));
+    assert_eq!(assignment.get(4), None);
+    assert_eq!(assignment.get(200), None);
+    assert_eq!(assignment.len(), 3);
+    assert_eq!(assignment.iter().collect::<Vec<_>>(),
+        vec![(3, true), (5, false), (70, false)]);
+    assignment.unset(70);
+    assert_eq!(assignment.get(70), None);
+    let mut expected = Assignment::new();
+    expected.set(5, false);
+    expected.set(3, true);
+    assert_eq!(assignment, expected);
+
+    // Partial evaluation of indexed expressions.
+    assert_eq!(assignment.eval(&Var(3).or(Var(4))), Some(true));
+    assert_eq!(assignment.eval(&Var(5).and(Var(4))), Some(false));
+    assert_eq!(assignment.eval(&Var(3).and(Var(4))), None);
+
+    let mut indexer = VarIndexer::new();
+    let map: HashMap<&str, bool> = vec![("a", true), ("b", false)]
+        .into_iter()
+        .collect();
+    let assignment = Assignment::from_map(&map, &mut indexer);
+    assert_eq!(indexer.len(), 2);
+    assert_eq!(assignment.to_map(&indexer), map);
+    let expr = indexer.index_expr(&Var("a").and(!Var("b")));
+    assert_eq!(assignment.eval(&expr), Some(true));
+}
+
+
+#[test]
+fn normal_form_clause_order() {
+    use Expr::*;
+    // Clauses are produced from left to right.
+    let expr = Var(1).and(Var(2).or(Var(3).and(Var(4)))).and(!Var(5));
+    let clauses: Vec<_> = CnfVec::from(expr.clone()).into();
+    assert_eq!(clauses, vec![
+        Var(1),
+        Var(2).or(Var(3)),
+        Var(2).or(Var(4)),
+        !Var(5),
+    ]);
+
+    // Canonical sorting orders equal expressions identically.
+    let mut a = CnfVec::from(expr);
+    let mut b = CnfVec::from((!Var(5)).and((Var(4).or(Var(2))).and(Var(1)))
+        .and(Var(3).or(Var(2))));
+    assert_ne!(a, b);
+    a.sort_canonical();
+    b.sort_canonical();
+    assert_eq!(format!("{:?}", a), format!("{:?}", b));
+    assert_eq!(a.into_vec(), vec![
+        Var(1),
+        Var(2).or(Var(3)),
+        Var(2).or(Var(4)),
+        !Var(5),
+    ]);
+
+    let mut dnf = DnfVec::from(vec![(!Var(2)).and(Var(1)), Var(2).and(!Var(2))]);
+    dnf.sort_canonical();
+    assert_eq!(dnf.into_vec(), vec![
+        Var(1).and(!Var(2)),
+        Var(2).and(!Var(2)),
+    ]);
+}
+
+
+#[test]
+fn normal_form_equivalence() {
+    use Expr::*;
+    // (1 | 2) & (1 | 3) and (1 | (2 & 3)) & (1 | 2 | 3) factor differently.
+    let a = CnfVec::from(vec![
+        Var(1).or(Var(2)),
+        Var(1).or(Var(3)),
+    ]);
+    let b = CnfVec::from(vec![
+        Var(1).or(Var(2).and(Var(3))),
+        Var(1).or(Var(2)).or(Var(3)),
+    ]);
+    assert!(a != b);
+    assert!(a.equivalent(&b));
+    assert!(a.equivalent_expr(&Var(1).or(Var(2).and(Var(3)))));
+    assert!(!a.equivalent_expr(&Var(1).or(Var(2))));
+
+    let c = CnfHashSet::from(Var(1).or(Var(2)).and(Var(1).or(Var(3))));
+    let d = CnfHashSet::from(vec![Var(1).or(Var(2).and(Var(3)))]);
+    assert!(c.equivalent(&d));
+
+    // An empty conjunction is true and an empty disjunction is false.
+    let taut = Var(1).or(!Var(1));
+    assert!(CnfVec::<u32>::default().equivalent_expr(&taut));
+    assert!(!DnfVec::<u32>::default().equivalent_expr(&taut));
+    assert!(DnfHashSet::<u32>::default().equivalent_expr(&!taut.clone()));
+
+    let e = DnfVec::from(Var(1).and(Var(2).or(Var(3))));
+    let f = DnfVec::from(vec![
+        Var(1).and(Var(2)),
+        Var(1).and(Var(3)),
+        Var(1).and(Var(2)).and(Var(3)),
+    ]);
+    assert!(e.equivalent(&f));
+    assert!(!e.equivalent(&DnfVec::from(vec![Var(1).and(Var(2))])));
+}
+
+
+#[test]
+fn sample_models_uniformly() {
+    use Expr::*;
+    let expr = Var(1).and(Var(2).or(Var(3)));
+    let models = expr.sample_models(300, 11);
+    assert_eq!(models.len(), 300);
+
+    let mut counts = std::collections::HashMap::new();
+    for model in &models {
+        assert_eq!(model.len(), 3);
+        assert!(model[&1] && (model[&2] || model[&3]));
+        *counts.entry((model[&2], model[&3])).or_insert(0) += 1;
+    }
+    // Each of the three models should be drawn about 100 times.
+    assert_eq!(counts.len(), 3);
+    assert!(counts.values().all(|&count| count > 60));
+
+    assert_eq!(models, expr.sample_models(300, 11));
+    assert!(Var(1).and(!Var(1)).sample_models(5, 11).is_empty());
+}
+
+
+#[test]
+fn estimate_truth_probability() {
+    use Expr::*;
+    let expr = Var(1).and(Var(2).or(Var(3)));
+    let p = expr.estimate_truth_probability(|&v| v as f64 / 10.0, 100);
+    assert!((p - 0.1 * (1.0 - 0.8 * 0.7)).abs() < 1e-12);
+
+    // Too many variables to compute exactly, so sample.
+    let wide = (0..20u32)
+        .map(Var)
+        .fold(Var(100), |acc, v| acc.or(v));
+    let p = wide.estimate_truth_probability(
+        |&v| if v == 100 { 0.3 } else { 0.0 },
+        4000);
+    assert!((p - 0.3).abs() < 0.05);
+    assert_eq!(p, wide.estimate_truth_probability(
+        |&v| if v == 100 { 0.3 } else { 0.0 },
+        4000));
+}
+
+
+#[test]
+fn cnf_clause_coverage() {
+    use Expr::*;
+    let cnf = CnfVec::from(vec![
+        Var(1).or(Var(2)),
+        Var(3),
+        Var(4).or(!Var(4)),
+    ]);
+    let ctxs = vec![
+        vec![1, 3],
+        vec![3],
+        vec![2],
+        vec![],
+    ];
+    let report = cnf.coverage(&ctxs);
+    assert_eq!(report.contexts, 4);
+    assert_eq!(report.rejected, 3);
+
+    let counts: Vec<_> = report.clauses.iter()
+        .map(|c| (c.satisfied, c.rejected, c.sole_rejections))
+        .collect();
+    assert_eq!(counts, vec![(2, 2, 1), (2, 2, 1), (4, 0, 0)]);
+    assert_eq!(
+        report.never_mattered().collect::<Vec<_>>(),
+        vec![&Var(4).or(!Var(4))]);
+
+    let set = CnfHashSet::from(cnf.clone().into_vec());
+    assert_eq!(set.coverage(&ctxs).never_mattered().count(), 1);
+}
+
+
+#[test]
+fn cnf_redundant_clauses() {
+    use Expr::*;
+    // 1 & (1 | 2) & (!1 | 3) & (3 | 4)
+    let cnf = CnfVec::from(vec![
+        Var(1),
+        Var(1).or(Var(2)),
+        (!Var(1)).or(Var(3)),
+        Var(3).or(Var(4)),
+    ]);
+    assert_eq!(cnf.redundant_clauses(), vec![
+        &Var(1).or(Var(2)),
+        &Var(3).or(Var(4)),
+    ]);
+
+    let set = CnfHashSet::from(vec![Var(1).or(Var(2)), Var(2).or(Var(3))]);
+    assert!(set.redundant_clauses().is_empty());
+    assert!(CnfVec::<u32>::default().redundant_clauses().is_empty());
+}
+
+
+#[test]
+fn policy_conflicts() {
+    use Expr::*;
+    let allow = DnfVec::from(vec![
+        Var(1).and(Var(2)),
+        Var(3),
+    ]);
+    let deny = DnfVec::from(vec![
+        (!Var(1)).and(Var(4)),
+        Var(2).and(!Var(3)),
+    ]);
+    let model = conflicts(&allow, &deny).unwrap();
+    assert_eq!(model.len(), 4);
+    let ctx: Vec<u32> = model.iter()
+        .filter_map(|(&var, &value)| if value { Some(var) } else { None })
+        .collect();
+    assert!(allow.eval(&ctx) && deny.eval(&ctx));
+
+    let policy = Policy::new(
+        DnfVec::from(Var(1).and(Var(2))),
+        DnfVec::from((!Var(1)).or(!Var(2))));
+    assert_eq!(policy.conflicts(), None);
+    assert_eq!(conflicts(&allow, &DnfVec::default()), None);
+}
+
+
+#[test]
+fn incremental_solver_assumptions() {
+    use Expr::*;
+    // (1 | 2) & (!1 | 3) & (!2 | !3 | 4) & !5
+    let cnf = CnfVec::from(vec![
+        Var(1).or(Var(2)),
+        (!Var(1)).or(Var(3)),
+        (!Var(2)).or(!Var(3)).or(Var(4)),
+        !Var(5),
+    ]);
+    let mut solver = IncrementalSolver::new(&cnf).unwrap();
+
+    // Check every combination of assumptions against a truth table, twice,
+    // so that the second pass reuses the learned state.
+    for _ in 0..2 {
+        for code in 0..243u32 {
+            let assumptions: Vec<_> = (1..=5u32)
+                .filter_map(|var| match code / 3u32.pow(var - 1) % 3 {
+                    0 => None,
+                    1 => Some(Literal::positive(var)),
+                    _ => Some(Literal::negative(var)),
+                })
+                .collect();
+            let expected = (0..32u32).any(|bits| {
+                let ctx: Vec<u32> = (1..=5)
+                    .filter(|var| bits & (1 << (var - 1)) != 0)
+                    .collect();
+                cnf.eval(&ctx) && assumptions.iter()
+                    .all(|a| ctx.contains(&a.var) == a.polarity)
+            });
+
+            match solver.solve_under_assumptions(&assumptions) {
+                Some(model) => {
+                    assert!(expected);
+                    let ctx: Vec<u32> = model.iter()
+                        .filter_map(|(&v, &value)| if value { Some(v) } else { None })
+                        .collect();
+                    assert!(cnf.eval(&ctx));
+                    assert!(assumptions.iter()
+                        .all(|a| model[&a.var] == a.polarity));
+                },
+                None => assert!(!expected, "{:?}", assumptions),
+            }
+        }
+    }
+
+    // Assumptions on variables outside of the expression are kept.
+    let model = solver.solve_under_assumptions(&[Literal::positive(9)])
+        .unwrap();
+    assert!(model[&9]);
+    assert_eq!(
+        solver.solve_under_assumptions(&[
+            Literal::positive(9),
+            Literal::negative(9),
+        ]),
+        None);
+
+    let unsat = CnfVec::from(vec![Var(1), !Var(1)]);
+    assert_eq!(IncrementalSolver::new(&unsat).unwrap().solve(), None);
+}
+
+
+#[test]
+fn cnf_implied_literals() {
+    use Expr::*;
+    // (1 | 2) & (!1 | 3) & (!3 | !4)
+    let cnf = CnfVec::from(vec![
+        Var(1).or(Var(2)),
+        (!Var(1)).or(Var(3)),
+        (!Var(3)).or(!Var(4)),
+    ]);
+    assert_eq!(cnf.implied_literals(&[]).unwrap(), vec![]);
+    assert_eq!(
+        cnf.implied_literals(&[Literal::positive(1)]).unwrap(),
+        vec![Literal::positive(3), Literal::negative(4)]);
+    assert_eq!(
+        cnf.implied_literals(&[Literal::positive(4)]).unwrap(),
+        vec![Literal::negative(1), Literal::positive(2), Literal::negative(3)]);
+    assert_eq!(
+        cnf.implied_literals(&[Literal::positive(1), Literal::positive(4)])
+            .unwrap(),
+        vec![]);
+
+    let set = CnfHashSet::from(cnf.clone().into_vec());
+    let mut implied = set.implied_literals(&[Literal::negative(2)]).unwrap();
+    implied.sort_by_key(|l| l.var);
+    assert_eq!(implied, vec![
+        Literal::positive(1),
+        Literal::positive(3),
+        Literal::negative(4),
+    ]);
+
+    let nested = CnfVec::from(vec![Var(1).or(Var(2).and(Var(3)))]);
+    assert!(nested.implied_literals(&[]).is_err());
+}
+
+
+#[test]
+fn labeled_groups() {
+    use Expr::*;
+    use std::collections::HashSet;
+    let inner = Var(Labeled::Var(3)).and(Var(Labeled::group(
+        "inner",
+        Var(Labeled::Var(4)),
+        true)));
+    let expr = Var(1).map(Labeled::Var)
+        .and(Var(2).labeled("outer", false))
+        .or(Var(Labeled::group("nested", inner, false)));
+    assert_eq!(expr.group_labels(), vec!["outer", "nested", "inner"]);
+
+    let none: HashSet<&str> = HashSet::new();
+    let set = |labels: &[&'static str]| -> HashSet<&'static str> {
+        labels.iter().copied().collect()
+    };
+    assert!(expr.eval_with_disabled(&vec![1, 2], &none));
+    assert!(!expr.eval_with_disabled(&vec![1, 2], &set(&["outer"])));
+    assert!(!expr.eval_with_disabled(&vec![3], &none));
+    assert!(expr.eval_with_disabled(&vec![3], &set(&["inner"])));
+    assert!(!expr.eval_with_disabled(&vec![3], &set(&["inner", "nested"])));
+    assert_eq!(
+        expr.eval(&vec![3, 4]),
+        expr.eval_with_disabled(&vec![3, 4], &none));
+}
+
+
+#[test]
+fn display_redacted() {
+    use Expr::*;
+    let expr = (!Var(1)).and(!Var(22)).or(Var(333).and(Var(1)));
+    let redacted = expr.display_redacted(|v| "*".repeat(v.to_string().len()));
+    assert_eq!(redacted.to_string(), "!* & !** | *** & *");
+    assert_eq!(format!("{:?}", redacted),
+        "RedactedDisplay { expr: !* & !** | *** & *, de_morgan: false }");
+    assert_eq!(
+        expr.display_redacted(|_| "?".to_string()).de_morgan(true).to_string(),
+        "!? & !? | ? & ?");
+
+    // Variables need not implement `Display`.
+    let expr = Var(vec![1, 2]).or(!Var(vec![]));
+    assert_eq!(
+        expr.display_redacted(|v| format!("<{} items>", v.len())).to_string(),
+        "<2 items> | !<0 items>");
+}
+
+
+#[test]
+fn parse_dialects() {
+    use Expr::*;
+    let parse = |src: &str, dialect: &ParserDialect| {
+        Expr::<String>::parse_with_dialect(src, dialect)
+    };
+    let var = |s: &str| Var(s.to_string());
+
+    let expected = var("a").and(!var("b").or(var("c-d")));
+    assert_eq!(
+        parse("a and not (b Or c-d)", &ParserDialect::sql()),
+        Ok(expected.clone()));
+    assert_eq!(
+        parse("a AND -(b || c-d)", &ParserDialect::lucene()),
+        Ok(expected.clone()));
+    assert_eq!(
+        parse("a&&!(b||c-d)", &ParserDialect::c_like()),
+        Ok(expected.clone()));
+    assert_eq!(parse("a&!(b|c-d)", &ParserDialect::default()), Ok(expected));
+
+    // Lucene keywords are case-sensitive, and quoted keywords are variables.
+    assert_eq!(
+        parse("a and \"AND\"", &ParserDialect::lucene()).unwrap_err(),
+        ParseError {
+            offset: 2,
+            kind: ParseErrorKind::UnexpectedToken("and".into()),
+        });
+    assert_eq!(
+        parse("a AND \"AND\"", &ParserDialect::lucene()),
+        Ok(var("a").and(var("AND"))));
+
+    // Implication groups to the right, with configurable precedence.
+    let mut dialect = ParserDialect {
+        implies: OperatorSyntax::new(&["->", "=>"], &["implies"]),
+        ..ParserDialect::default()
+    };
+    assert_eq!(
+        parse("a | b -> c implies d", &dialect),
+        Ok((!var("a").or(var("b"))).or((!var("c")).or(var("d")))));
+    dialect.implies_precedence = ImpliesPrecedence::AboveOr;
+    assert_eq!(
+        parse("a | b => c & d", &dialect),
+        Ok(var("a").or((!var("b")).or(var("c").and(var("d"))))));
+    assert_eq!(parse("a-b", &dialect), Ok(var("a-b")));
+}
+
+
+#[test]
+fn dnf_stream_evaluation() {
+    use Expr::*;
+    let terms = || vec![
+        Ok(Var(1).and(Var(2))),
+        Ok(Var(3)),
+        Err("unreadable term"),
+        Ok(Var(4)),
+    ];
+    assert_eq!(eval_dnf_stream(terms(), &vec![1, 2]), Ok(true));
+    // The error after the first true term is never read.
+    assert_eq!(eval_dnf_stream(terms(), &vec![3]), Ok(true));
+    assert_eq!(eval_dnf_stream(terms(), &vec![4]), Err("unreadable term"));
+
+    let empty: Vec<Result<Expr<u32>, ()>> = Vec::new();
+    assert_eq!(eval_dnf_stream(empty, &vec![1]), Ok(false));
+}
+
+
+#[test]
+fn merkle_tree_diff() {
+    use Expr::*;
+    let a = Var(1).and(!Var(2)).or(Var(3).and(Var(4)));
+    let b = Var(1).and(!Var(5)).or(Var(3).or(Var(4)));
+    assert_eq!(a.merkle_hash(), a.clone().merkle_hash());
+    assert_ne!(a.merkle_hash(), b.merkle_hash());
+    assert_ne!(
+        Var(1).and(Var(2)).merkle_hash(),
+        Var(2).and(Var(1)).merkle_hash());
+
+    let (ta, tb) = (a.merkle_tree(), b.merkle_tree());
+    let diff = ta.diff(&tb);
+    assert_eq!(diff, vec![
+        ExprPath::from(vec![PathStep::Left, PathStep::Right, PathStep::Child]),
+        ExprPath::from(vec![PathStep::Right]),
+    ]);
+    for path in &diff {
+        assert_eq!(
+            ta.get_path(path).unwrap().hash(),
+            a.get_path(path).unwrap().merkle_hash());
+    }
+    assert!(ta.diff(&ta).is_empty());
+    assert_eq!(ta.children().len(), 2);
+    assert_eq!(Var(1).merkle_tree().diff(&ta), vec![ExprPath::root()]);
+}
+
+
+#[test]
+fn fixture_builders() {
+    use Expr::*;
+    use crate::fixtures::*;
+    assert_eq!(chain_and(1), Var(0));
+    assert_eq!(chain_or(3), Var(0).or(Var(1)).or(Var(2)));
+    assert_eq!(alternating(4), Var(0).and(Var(1)).or(Var(2)).and(Var(3)));
+    assert_eq!(nested_not(2), !!Var(0));
+
+    let cnf = random_cnf(6, 20, 7);
+    assert_eq!(cnf, random_cnf(6, 20, 7));
+    assert_ne!(cnf, random_cnf(6, 20, 8));
+    assert!(is_normal_form(&cnf, true));
+    let clauses = cnf.clone().split_top_and();
+    assert_eq!(clauses.len(), 20);
+    assert!(clauses.iter().all(|c| c.operands().len() == 3));
+
+    let dnf = random_dnf(2, 5, 7);
+    assert!(is_normal_form(&dnf, false));
+    assert!(dnf.free_vars().len() <= 2);
+}
+
+
+#[test]
+fn three_valued_bounds() {
+    use Expr::*;
+    use std::cell::RefCell;
+    let expr = Var(1).and(Var(2).or(!Var(3))).or(Var(4));
+    let fetched = RefCell::new(Vec::new());
+    let lookup = |known: &'static [u32], trues: &'static [u32]| {
+        let fetched = &fetched;
+        move |var: &u32| {
+            fetched.borrow_mut().push(*var);
+            if known.contains(var) { Some(trues.contains(var)) } else { None }
+        }
+    };
+
+    // The left operand decides the `And`, so 2 and 3 are never fetched.
+    assert_eq!(expr.bounds(lookup(&[1], &[])), None);
+    assert_eq!(fetched.replace(Vec::new()), vec![1, 4]);
+    assert_eq!(expr.bounds(lookup(&[1, 4], &[4])), Some(true));
+    assert_eq!(expr.bounds(lookup(&[1, 2], &[1, 2])), Some(true));
+    assert_eq!(fetched.replace(Vec::new()), vec![1, 4, 1, 2]);
+    assert_eq!(expr.bounds(lookup(&[1, 3, 4], &[1, 3])), None);
+    assert_eq!(expr.bounds(lookup(&[1, 2, 3, 4], &[1, 3])), Some(false));
+}
+
+
+#[test]
+fn prune_with_invariants() {
+    use Expr::*;
+    // 1 never holds, and 2 always implies 3.
+    let invariants = CnfVec::from(vec![!Var(1), (!Var(2)).or(Var(3))]);
+
+    let expr = Var(1).and(Var(5)).or(Var(4));
+    assert_eq!(expr.prune_with_invariants(&invariants), Var(4));
+
+    let expr = Var(4).and(Var(1).or(Var(5)));
+    assert_eq!(
+        expr.prune_with_invariants(&invariants),
+        Var(4).and(Var(5)));
+
+    let expr = Var(4).and((!Var(2)).or(Var(3)));
+    assert_eq!(expr.prune_with_invariants(&invariants), Var(4));
+
+    let expr = Var(4).or(!(Var(2).and(!Var(3))));
+    assert_eq!(expr.prune_with_invariants(&invariants), expr);
+
+    let expr = Var(2).and(Var(4));
+    assert_eq!(expr.prune_with_invariants(&invariants), expr);
+
+    let unsatisfiable = CnfVec::from(vec![Var(1), !Var(1)]);
+    let expr = Var(1).and(Var(2));
+    assert_eq!(expr.prune_with_invariants(&unsatisfiable), expr);
+}
+
+
+#[test]
+fn cnf_factor_common() {
+    use Expr::*;
+    use std::cell::Cell;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Counted(u32);
+
+    thread_local! {
+        static EVALS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    impl Eval for Counted {
+        type Context = Vec<u32>;
+        fn eval(&self, data: &Self::Context) -> bool {
+            EVALS.with(|evals| evals.set(evals.get() + 1));
+            data.contains(&self.0)
+        }
+    }
+
+    // (!1 | 2) & (!1 | 3) & (!1 | 4 | !2)
+    let cnf = CnfVec::from(vec![
+        (!Var(Counted(1))).or(Var(Counted(2))),
+        (!Var(Counted(1))).or(Var(Counted(3))),
+        (!Var(Counted(1))).or(Var(Counted(4))).or(!Var(Counted(2))),
+    ]);
+    let factored = cnf.factor_common();
+    assert_eq!(factored.len(), 3);
+    assert_eq!(factored.shared_atoms(), &[Counted(1), Counted(2)]);
+    assert_eq!(factored.to_cnf(), cnf);
+
+    for data in &[vec![], vec![1], vec![1, 2], vec![1, 2, 3], vec![1, 3, 4]] {
+        EVALS.with(|evals| evals.set(0));
+        let expected = cnf.eval(data);
+        let unfactored = EVALS.with(|evals| evals.replace(0));
+        assert_eq!(factored.eval(data), expected);
+        assert!(EVALS.with(Cell::get) <= unfactored);
+    }
+
+    EVALS.with(|evals| evals.set(0));
+    assert!(factored.eval(&vec![1, 2, 3, 4]));
+    assert_eq!(EVALS.with(Cell::get), 4);
+
+    assert!(CnfVec::<u32>::default().factor_common().is_empty());
+}
+
+
+#[test]
+fn subexpr_early_exit() {
+    use Expr::*;
+    // 1 & (!2 | 3)
+    let expr = Var(1).and((!Var(2)).or(Var(3)));
+
+    let mut visited = 0;
+    assert_eq!(expr.try_fold(Vec::new(), |mut vars, e| {
+        visited += 1;
+        match e {
+            Not(_)  => Err(vars),
+            Var(v)  => { vars.push(*v); Ok(vars) },
+            _       => Ok(vars),
+        }
+    }), Err(vec![1]));
+    assert_eq!(visited, 4);
+
+    assert_eq!(expr.try_fold::<_, (), _>(0, |n, _| Ok(n + 1)), Ok(6));
+
+    let mut visited = 0;
+    assert!(expr.any_subexpr(|e| { visited += 1; *e == Var(2) }));
+    assert_eq!(visited, 5);
+    assert!(!expr.any_subexpr(|e| *e == Var(4)));
+
+    let mut visited = 0;
+    assert!(!expr.all_subexprs(|e| { visited += 1; !matches!(e, Or(..)) }));
+    assert_eq!(visited, 3);
+    assert!(expr.all_subexprs(|e| !matches!(e, Var(v) if *v > 3)));
+}
+
+
+impl SnapshotEval for u32 {
+    type Snapshot = Option<usize>;
+    fn snapshot(&self, data: &Self::Context) -> Self::Snapshot {
+        data.iter().position(|x| x == self)
+    }
+}
+
+#[test]
+fn eval_snapshot_records_vars() {
+    use Expr::*;
+    // (1 | 2) & !3
+    let expr = Var(1).or(Var(2)).and(!Var(3));
+
+    let record = expr.eval_snapshot(&vec![3, 2]);
+    assert_eq!(record, EvalSnapshot {
+        result: false,
+        vars: vec![
+            VarSnapshot { var: 1, value: false, snapshot: None },
+            VarSnapshot { var: 2, value: true, snapshot: Some(1) },
+            VarSnapshot { var: 3, value: true, snapshot: Some(0) },
+        ],
+    });
+
+    let record = expr.eval_snapshot(&vec![]);
+    assert!(!record.result);
+    assert_eq!(record.vars.len(), 2);
+    assert_eq!(record.result, expr.eval(&vec![]));
+}
+
+
+#[test]
+fn dnf_literal_terms() {
+    use Expr::*;
+    // (1 & !2) | 3 | (!1 & 2 & 4)
+    let dnf = DnfVec::from(vec![
+        Var(1).and(!Var(2)),
+        Var(3),
+        (!Var(1)).and(Var(2)).and(Var(4)),
+    ]);
+    let terms = dnf.to_literal_dnf().unwrap();
+    assert_eq!(terms.len(), 3);
+    assert_eq!(terms.terms().collect::<Vec<_>>(), vec![
+        &[Literal::positive(1), Literal::negative(2)][..],
+        &[Literal::positive(3)][..],
+        &[Literal::negative(1), Literal::positive(2), Literal::positive(4)][..],
+    ]);
+    for data in &[vec![], vec![1], vec![2, 4], vec![1, 2], vec![3]] {
+        assert_eq!(terms.eval(data), dnf.eval(data));
+    }
+    assert_eq!(terms.clone().into_dnf(), Some(dnf.clone()));
+
+    let rebuilt = LiteralDnf::from_terms(vec![
+        vec![Literal::positive(1), Literal::negative(2)],
+        vec![Literal::positive(3)],
+        vec![Literal::negative(1), Literal::positive(2), Literal::positive(4)],
+    ]);
+    assert_eq!(rebuilt, terms);
+
+    let always = LiteralDnf::from_terms(vec![Vec::<Literal<u32>>::new()]);
+    assert!(always.eval(&vec![]));
+    assert_eq!(always.into_dnf(), None);
+    assert!(!LiteralDnf::<u32>::default().eval(&vec![]));
+
+    let set = DnfHashSet::from(vec![Var(1).and(!Var(2))]);
+    assert_eq!(set.to_literal_dnf().unwrap().terms().next(),
+        Some(&[Literal::positive(1), Literal::negative(2)][..]));
+
+    let nested = DnfVec::from(vec![Var(1).and(Var(2).or(Var(3)))]);
+    assert!(nested.to_literal_dnf().is_err());
+}
+
+
+#[test]
+fn dnf_required_vars_per_term() {
+    use Expr::*;
+    use std::collections::HashSet;
+    // (1 & !2 & 3) | !4 | 5 | (6 & (7 | 8))
+    let dnf = DnfVec::from(vec![
+        Var(1).and(!Var(2)).and(Var(3)),
+        !Var(4),
+        Var(5),
+        Var(6).and(Var(7).or(Var(8))),
+    ]);
+    let required = dnf.required_vars_per_term();
+    assert_eq!(required, vec![
+        vec![1, 3].into_iter().collect::<HashSet<_>>(),
+        HashSet::new(),
+        vec![5].into_iter().collect(),
+        vec![6].into_iter().collect(),
+    ]);
+
+    let set = DnfHashSet::from(vec![Var(1).and(Var(2))]);
+    assert_eq!(set.required_vars_per_term(), vec![
+        vec![1, 2].into_iter().collect::<HashSet<_>>(),
+    ]);
+    assert!(DnfVec::<u32>::default().required_vars_per_term().is_empty());
+}
+
+
+#[test]
+fn split_by_supported_vars() {
+    use Expr::*;
+    // 1 & (2 | 13) & !3 & (14 & 4)
+    let expr = Var(1)
+        .and(Var(2).or(Var(13)))
+        .and(!Var(3))
+        .and(Var(14).and(Var(4)));
+    let (pushed, residual) = expr.split_by(|v| *v < 10);
+    assert_eq!(pushed, Some(Var(1).and(!Var(3)).and(Var(4))));
+    assert_eq!(residual, Some(Var(2).or(Var(13)).and(Var(14))));
+
+    let (pushed, residual) = (pushed.unwrap(), residual.unwrap());
+    for data in &[vec![1, 2, 4, 14], vec![1, 4, 13, 14], vec![1, 3, 4, 13, 14]] {
+        assert_eq!(expr.eval(data), pushed.eval(data) && residual.eval(data));
+    }
+
+    assert_eq!(expr.split_by(|_| true), (Some(expr.clone()), None));
+    assert_eq!(expr.split_by(|_| false), (None, Some(expr.clone())));
+
+    let not_or = !(Var(1).or(Var(11)));
+    assert_eq!(not_or.split_by(|v| *v < 10), (None, Some(not_or.clone())));
+}
+
+
+#[test]
+fn normalize_reuses_boxes() {
+    use Expr::*;
+    fn left(expr: &Expr<u32>) -> *const Expr<u32> {
+        match expr {
+            And(a, _) | Or(a, _) => &**a,
+            _                    => panic!("expected a binary operator"),
+        }
+    }
+
+    // (1 & !2) | 3, already normalized.
+    let expr = Var(1).and(!Var(2)).or(Var(3));
+    let ptr = left(&expr);
+
+    let nnf = expr.into_nnf();
+    assert_eq!(nnf, Var(1).and(!Var(2)).or(Var(3)));
+    assert_eq!(left(&nnf), ptr);
+
+    let simplified = nnf.simplify();
+    assert_eq!(simplified, Var(1).and(!Var(2)).or(Var(3)));
+    assert_eq!(left(&simplified), ptr);
+
+    // Negations are still carried down to the variables.
+    assert_eq!((!Var(1).and(!Var(2))).into_nnf(), (!Var(1)).or(Var(2)));
+    assert_eq!((!Var(1).or(Var(1))).simplify(), !Var(1));
+}
+
+
+#[test]
+fn hash_ignores_operand_order() {
+    use Expr::*;
+    use std::collections::HashSet;
+    let mut set = HashSet::new();
+    let _ = set.insert(Var(1).or(Var(2)).and(!Var(3)));
+
+    assert!(set.contains(&Var(2).or(Var(1)).and(!Var(3))));
+    assert!(set.contains(&(!Var(3)).and(Var(1).or(Var(2)))));
+    assert!(!set.contains(&Var(1).and(Var(2)).and(!Var(3))));
+}
+
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn serialize_tests() {
+    panic!("Enable \"serde\" (--all-features) feature to run serde tests.")
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_expr_ron() {
+    use Expr::*;
+    let expr = And(
+        Box::new(Or(
+            Box::new(Not(Box::new(Var(1)))),
+            Box::new(Var(2)))),
+        Box::new(Or(
+            Box::new(Not(Box::new(Var(3)))),
+            Box::new(Var(4)))));
+
+    let s = ron::ser::to_string(&expr).unwrap();
+    assert_eq!(s, "And(Or(Not(Var(1)),Var(2)),Or(Not(Var(3)),Var(4)))");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_expr_ron() {
+    use Expr::*;
+    let expr = And(
+        Box::new(Or(
+            Box::new(Not(Box::new(Var(1)))),
+            Box::new(Var(2)))),
+        Box::new(Or(
+            Box::new(Not(Box::new(Var(3)))),
+            Box::new(Var(4)))));
+    let s = "And(Or(Not(Var(1)),Var(2)),Or(Not(Var(3)),Var(4)))";
+
+    use ron::de::Deserializer;
+    let mut d = Deserializer::from_str(&s).unwrap();
+    let res = Expr::deserialize(&mut d).unwrap();
+    d.end().unwrap();
+
+    assert_eq!(res, expr);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_policy_ron() {
+    use Expr::*;
+    let policy = Policy::new(
+        DnfVec::from(vec![Var(1)]),
+        DnfVec::from(vec![Not(Box::new(Var(2)))]));
+
+    let s = ron::ser::to_string(&policy).unwrap();
+    assert_eq!(s, "(allow:[Var(1)],deny:[Not(Var(2))])");
+
+    let res: Policy<u32> = ron::de::from_str(&s).unwrap();
+    assert_eq!(res, policy);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_round_trip() {
+    use Expr::*;
+    let expr = Or(
+        Box::new(Var("a".to_string())),
+        Box::new(Not(Box::new(Var("b".to_string())))));
+
+    let s = expr.to_json().unwrap();
+    assert_eq!(s, r#"{"Or":[{"Var":"a"},{"Not":{"Var":"b"}}]}"#);
+    assert_eq!(Expr::from_json(&s).unwrap(), expr);
+    assert!(expr.eval_names::<&str>(&[]));
+    assert!(!expr.eval_names(&["b"]));
+
+    fn load(json: &str) -> Result<Expr<String>, Error> {
+        Ok(Expr::from_json(json)?)
+    }
+    let err = load(r#"{"Xor":[]}"#).unwrap_err();
+    assert!(matches!(err, Error::Json(_)));
+    assert!(err.to_string().starts_with("JSON error: unknown variant `Xor`"));
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_parse_eval_free() {
+    use crate::ffi::*;
+    use std::ffi::CStr;
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+    use std::os::raw::c_void;
+    use std::ptr;
+
+    extern "C" fn is_a(var: *const c_char, user_data: *mut c_void) -> bool {
+        let calls = unsafe { &mut *(user_data as *mut u32) };
+        *calls += 1;
+        unsafe { CStr::from_ptr(var) }.to_bytes() == b"a"
+    }
+
+    let json = CString::new(r#"{"And":[{"Var":"a"},{"Not":{"Var":"b"}}]}"#)
+        .unwrap();
+    let invalid = CString::new("{").unwrap();
+    let mut calls = 0u32;
+    unsafe {
+        let expr = sp_expr_parse(json.as_ptr());
+        assert!(!expr.is_null());
+        let user_data: *mut u32 = &mut calls;
+        let user_data = user_data as *mut c_void;
+        assert_eq!(sp_expr_eval_with_callback(expr, Some(is_a), user_data), 1);
+        assert_eq!(sp_expr_eval_with_callback(expr, None, user_data), -1);
+        sp_expr_free(expr);
+
+        assert!(sp_expr_parse(invalid.as_ptr()).is_null());
+        assert_eq!(sp_expr_eval_with_callback(
+            ptr::null(), Some(is_a), ptr::null_mut()), -1);
+    }
+    assert_eq!(calls, 2);
+}
+
+
+#[cfg(feature = "stacker")]
+#[test]
+fn deep_expr_with_stacker() {
+    use Expr::*;
+    use std::hash::Hash;
+    use std::hash::Hasher;
+    let data = vec![1];
+    let mut expr = Var(1);
+    for i in 0..200_000 {
+        expr = if i % 2 == 0 { expr.and(Var(1)) } else { !expr };
+    }
+    assert!(expr.eval(&data));
+    let simplified = expr.simplify();
+    assert!(simplified.eval(&data));
+
+    let cloned = simplified.clone();
+    assert!(cloned == simplified);
+    let hash = |expr: &Expr<u32>| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        expr.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash(&cloned), hash(&simplified));
+    assert!(format!("{:?}", cloned).len() > 200_000);
+
+    // Dropping is not stack-protected, so the expressions are leaked instead.
+    std::mem::forget(cloned);
+    std::mem::forget(simplified);
+}
+
+
+#[cfg(feature = "macros")]
+#[test]
+fn static_expr_macro() {
+    use Expr::*;
+    const A: u32 = 1;
+    static RULE: StaticExpr<u32> = static_expr!{ !(A | !2) || (A & 3 & A) };
+    assert_eq!(RULE.nodes(), &[
+        Node::Var(1),
+        Node::Not(0),
+        Node::Var(2),
+        Node::And(1, 2),
+        Node::Var(3),
+        Node::And(0, 4),
+        Node::Or(3, 5),
+    ]);
+    assert!(RULE.eval(&vec![2]));
+    assert!(!RULE.eval(&vec![1, 2]));
+    assert_eq!(RULE.to_expr(), (!Var(1)).and(2).or(Var(1).and(3)));
+}
+
+
+#[cfg(feature = "macros")]
+#[test]
+fn derive_eval_comparison() {
+    #[derive(Debug)]
+    struct Address { zip: u32 }
+    #[derive(Debug)]
+    struct Person { age: u8, address: Address }
+
+    #[derive(Debug, Eval)]
+    #[eval(context = Person, field = "age", op = ">=")]
+    struct MinAge(u8);
+
+    #[derive(Debug, Eval)]
+    #[eval(context = Person, field = "address.zip", op = "==")]
+    struct Zip { value: u32 }
+
+    let person = Person { age: 30, address: Address { zip: 12345 } };
+    let expr = Expr::Var(MinAge(21)).and(!Expr::Var(MinAge(40)));
+    assert!(expr.eval(&person));
+    assert!(Zip { value: 12345 }.eval(&person));
+    assert!(!Zip { value: 54321 }.eval(&person));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+fn verify_transforms_checks_conversions() {
+    use Expr::*;
+    let expr = (!Var(1).and(Var(2))).or(Var(3).and(!!Var(1)));
+    let _ = CnfVec::from(expr.clone());
+    let _ = DnfHashSet::from(expr.clone());
+    let _ = expr.simplify_traced();
+}
+
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+#[should_panic(expected = "normal form conversion changed the value")]
+fn verify_transforms_detects_inequivalence() {
+    use Expr::*;
+    let expr = Var(1).and(Var(2));
+    truth::verify_clauses(&expr, &[Var(1), Var(2)], false);
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+fn verify_transforms_checks_simplify() {
+    use Expr::*;
+    // !(!1 | !2) & (3 & 3)
+    let expr = (!(!Var(1)).or(!Var(2))).and(Var(3).and(Var(3)));
+    assert_eq!(expr.simplify(), Var(1).and(Var(2)).and(Var(3)));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+fn verify_transforms_checks_nnf() {
+    use Expr::*;
+    // !(1 & !(2 | 1))
+    let expr = !Var(1).and(!Var(2).or(Var(1)));
+    assert_eq!(expr.into_nnf(), (!Var(1)).or(Var(2).or(Var(1))));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+fn verify_transforms_checks_tautology_removal() {
+    use Expr::*;
+    let clauses = vec![Var(1).or(!Var(1)), Var(2).or(!Var(3)), Var(3)];
+    let mut cnf = CnfVec::from(clauses.clone());
+    cnf.remove_tautologies();
+    assert_eq!(cnf, CnfVec::from(clauses[1..].to_vec()));
+    let mut cnf = CnfHashSet::from(clauses.clone());
+    cnf.remove_tautologies();
+    assert_eq!(cnf, CnfHashSet::from(clauses[1..].to_vec()));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+fn verify_transforms_checks_absorption() {
+    use Expr::*;
+    let terms = vec![Var(1).and(Var(2)), Var(1).and(Var(2)).and(Var(3)), Var(4)];
+    let absorbed = vec![terms[0].clone(), terms[2].clone()];
+    let mut dnf = DnfVec::from(terms.clone());
+    dnf.absorb();
+    assert_eq!(dnf, DnfVec::from(absorbed.clone()));
+    let mut dnf = DnfHashSet::from(terms);
+    dnf.absorb();
+    assert_eq!(dnf, DnfHashSet::from(absorbed));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+#[should_panic(expected = "negation normal form conversion changed the value")]
+fn verify_transforms_detects_changed_nnf() {
+    use Expr::*;
+    truth::verify_occurrence_table("negation normal form conversion",
+        truth::occurrence_table(&!Var(1).and(Var(2))),
+        &(!Var(1)).and(!Var(2)));
+}
+
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+#[test]
+#[should_panic(expected = "absorption changed the value")]
+fn verify_transforms_detects_unsound_removal() {
+    use Expr::*;
+    let (a, b) = (Var(1).and(Var(2)), Var(3));
+    truth::verify_removal("absorption", &[&a, &b], &[&a], false);
+}
+
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary_expr_depth_limit() {
+    use arbitrary::Arbitrary;
+    use arbitrary::Unstructured;
+    fn depth(expr: &Expr<u8>) -> usize {
+        match expr {
+            Expr::Var(_)    => 1,
+            Expr::Not(p)    => 1 + depth(p),
+            Expr::Or(a, b)  |
+            Expr::And(a, b) => 1 + depth(a).max(depth(b)),
+        }
+    }
+
+    // Always choosing `Not` builds the deepest possible expression.
+    let data = [1u8; 4096];
+    let mut u = Unstructured::new(&data);
+    let expr = Expr::<u8>::arbitrary(&mut u).unwrap();
+    assert_eq!(depth(&expr), 17);
+}
+
+
+#[cfg(feature = "json")]
+#[test]
+fn rule_store_reload_from_json() {
+    let store = RuleStore::<u32>::new(CompileOptions::default());
+    let mut d = serde_json::Deserializer::from_str(r#"{"a": "1 & !2"}"#);
+    assert_eq!(store.reload_from(&mut d).unwrap(), 1);
+    assert!(store.get("a").unwrap().1.eval(&vec![1]));
+
+    let mut d = serde_json::Deserializer::from_str(r#"["1"]"#);
+    assert!(matches!(store.reload_from(&mut d),
+        Err(RuleStoreError::Deserialize(_))));
+    assert_eq!(store.version(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn compiled_predicate_ron_round_trip() {
+    let options = CompileOptions { cnf: true, ..Default::default() };
+    let predicate = Predicate::compile::<u32>("1 & !(2 | 3)", &options)
+        .unwrap();
+
+    let s = ron::ser::to_string(&predicate).unwrap();
+    assert!(s.starts_with("(version:1,"));
+    let res: CompiledPredicate<u32> = ron::de::from_str(&s).unwrap();
+    assert_eq!(res.expr(), predicate.expr());
+    assert_eq!(res.cnf(), predicate.cnf());
+    assert!(res.eval(&vec![1]));
+
+    let s = s.replacen("version:1", "version:2", 1);
+    assert!(ron::de::from_str::<CompiledPredicate<u32>>(&s).is_err());
+}
+
+#[cfg(feature = "boolean_expression")]
+#[test]
+fn boolean_expression_conversions() {
+    use boolean_expression::Expr as BoolExpr;
+    use std::convert::TryFrom;
+    use Expr::*;
+
+    let expr = Var(1).and((!Var(2)).or(Var(3)));
+    let converted = BoolExpr::from(expr.clone());
+    assert_eq!(converted, BoolExpr::and(
+        BoolExpr::Terminal(1),
+        BoolExpr::or(BoolExpr::not(BoolExpr::Terminal(2)), BoolExpr::Terminal(3))));
+    assert_eq!(Expr::try_from(converted), Ok(expr));
+
+    // (1 & true) | (2 & false)
+    let folded = BoolExpr::or(
+        BoolExpr::and(BoolExpr::Terminal(1), BoolExpr::Const(true)),
+        BoolExpr::and(BoolExpr::Terminal(2), BoolExpr::Const(false)));
+    assert_eq!(Expr::try_from(folded), Ok(Var(1)));
+
+    let constant = BoolExpr::or(
+        BoolExpr::Terminal(1),
+        BoolExpr::not(BoolExpr::Const(false)));
+    assert_eq!(
+        Expr::<u32>::try_from(constant.clone()),
+        Err(ConstantExprError { value: true }));
+
+    let convert = || -> Result<Expr<u32>, Error> {
+        Ok(Expr::try_from(constant.clone())?)
+    };
+    assert_eq!(convert(),
+        Err(Error::Constant(ConstantExprError { value: true })));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn eval_snapshot_ron_round_trip() {
+    let expr = Expr::Var(1).and(Expr::Var(2));
+    let record = expr.eval_snapshot(&vec![2, 1]);
+
+    let s = ron::ser::to_string(&record).unwrap();
+    let res: EvalSnapshot<u32, Option<usize>> = ron::de::from_str(&s).unwrap();
+    assert_eq!(res, record);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn flag_rule_ron_clamps_rollout() {
+    let rule = FlagRule::new(Expr::Var(1), 40, "flag");
+    let s = ron::ser::to_string(&rule).unwrap();
+    let res: FlagRule<u32> = ron::de::from_str(&s).unwrap();
+    assert_eq!(res, rule);
+
+    let s = s.replace("40", "250");
+    let res: FlagRule<u32> = ron::de::from_str(&s).unwrap();
+    assert_eq!(res.rollout(), 100);
 }
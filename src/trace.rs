@@ -0,0 +1,77 @@
+
+// Internal library imports.
+use crate::Eval;
+use crate::Expr;
+
+// External library imports
+use tracing::trace;
+use tracing::debug_span;
+
+// Standard library imports
+use std::fmt::Debug;
+use std::time::Instant;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// TracedExpr
+////////////////////////////////////////////////////////////////////////////////
+/// An [`Expr`] wrapper which emits [`tracing`] spans and events as it is
+/// evaluated.
+///
+/// Each evaluation is wrapped in an `eval` span. Within it, every evaluated
+/// subexpression emits a `TRACE` event recording the operator, its depth, its
+/// result, and the time taken to evaluate it. Operands skipped by
+/// short-circuiting emit no events.
+///
+/// [`Expr`]: crate::Expr
+/// [`tracing`]: https://crates.io/crates/tracing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedExpr<V>(pub Expr<V>);
+
+impl<V> TracedExpr<V> {
+    /// Returns the wrapped expression.
+    pub fn into_inner(self) -> Expr<V> {
+        self.0
+    }
+}
+
+impl<V> From<Expr<V>> for TracedExpr<V> {
+    fn from(expr: Expr<V>) -> Self {
+        TracedExpr(expr)
+    }
+}
+
+impl<V> Eval for TracedExpr<V> where V: Eval + Debug {
+    type Context = V::Context;
+
+    fn eval(&self, data: &Self::Context) -> bool {
+        let span = debug_span!("eval");
+        let _enter = span.enter();
+        eval_traced(&self.0, data, 0)
+    }
+}
+
+/// Evaluates the expression, emitting an event for each evaluated node.
+fn eval_traced<V>(expr: &Expr<V>, data: &V::Context, depth: usize) -> bool
+    where V: Eval + Debug
+{
+    use Expr::*;
+    let start = Instant::now();
+    let (op, result) = match expr {
+        Var(p) => {
+            let result = p.eval(data);
+            trace!(depth, var = ?p, result, "var");
+            return result;
+        },
+        Not(p)    => ("not", !eval_traced(p, data, depth + 1)),
+        Or(a, b)  => ("or",
+            eval_traced(a, data, depth + 1) ||
+            eval_traced(b, data, depth + 1)),
+        And(a, b) => ("and",
+            eval_traced(a, data, depth + 1) &&
+            eval_traced(b, data, depth + 1)),
+    };
+    let elapsed_ns = start.elapsed().as_nanos() as u64;
+    trace!(depth, op, result, elapsed_ns, "clause");
+    result
+}
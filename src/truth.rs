@@ -0,0 +1,478 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::TraversalOrder;
+use crate::stack::guard;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Truth tables
+////////////////////////////////////////////////////////////////////////////////
+/// The largest number of variables for which truth tables are enumerated when
+/// verifying transformations.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+const VERIFY_MAX_VARS: usize = 12;
+
+/// Returns the distinct variables of the given expressions, in order of first
+/// occurrence.
+pub (in crate) fn distinct_vars<'a, V, I>(exprs: I) -> Vec<&'a V>
+    where
+        V: PartialEq + 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+{
+    let mut vars: Vec<&V> = Vec::new();
+    for expr in exprs {
+        for expr in expr.iter_subexprs(TraversalOrder::PreOrder) {
+            if let Expr::Var(var) = expr {
+                if !vars.contains(&var) { vars.push(var); }
+            }
+        }
+    }
+    vars
+}
+
+/// Evaluates the expression under an assignment of the given variables, where
+/// the value of each variable is the bit of `assignment` at its index.
+///
+/// # Panics
+///
+/// Panics if the expression contains a variable which is not in `vars`.
+pub (in crate) fn eval_assignment<V>(expr: &Expr<V>, vars: &[&V], assignment: u64)
+    -> bool
+    where V: PartialEq
+{
+    use Expr::*;
+    guard(|| match expr {
+        Var(v)    => {
+            let idx = vars.iter()
+                .position(|var| *var == v)
+                .expect("variable in truth table");
+            assignment & (1 << idx) != 0
+        },
+        Not(p)    => !eval_assignment(p, vars, assignment),
+        Or(a, b)  => eval_assignment(a, vars, assignment)
+            || eval_assignment(b, vars, assignment),
+        And(a, b) => eval_assignment(a, vars, assignment)
+            && eval_assignment(b, vars, assignment),
+    })
+}
+
+/// Evaluates the expression under a partial assignment of the given
+/// variables, returning `None` if its value depends on an unassigned variable.
+///
+/// # Panics
+///
+/// Panics if the expression contains a variable which is not in `vars`.
+pub (in crate) fn eval_partial<V>(
+    expr: &Expr<V>,
+    vars: &[&V],
+    values: &[Option<bool>])
+    -> Option<bool>
+    where V: PartialEq
+{
+    use Expr::*;
+    guard(|| match expr {
+        Var(v)    => {
+            let idx = vars.iter()
+                .position(|var| *var == v)
+                .expect("variable in truth table");
+            values[idx]
+        },
+        Not(p)    => eval_partial(p, vars, values).map(|x| !x),
+        Or(a, b)  => match eval_partial(a, vars, values) {
+            Some(true)  => Some(true),
+            Some(false) => eval_partial(b, vars, values),
+            None        => match eval_partial(b, vars, values) {
+                Some(true) => Some(true),
+                _          => None,
+            },
+        },
+        And(a, b) => match eval_partial(a, vars, values) {
+            Some(false) => Some(false),
+            Some(true)  => eval_partial(b, vars, values),
+            None        => match eval_partial(b, vars, values) {
+                Some(false) => Some(false),
+                _           => None,
+            },
+        },
+    })
+}
+
+/// Returns the probability that the expression is true when each variable
+/// from `idx` onward is independently true with the probability at its index
+/// in `probs`, and the variables before `idx` have the given values.
+///
+/// # Panics
+///
+/// Panics if the expression contains a variable which is not in `vars`.
+pub (in crate) fn truth_probability<V>(
+    expr: &Expr<V>,
+    vars: &[&V],
+    probs: &[f64],
+    values: &mut Vec<Option<bool>>,
+    idx: usize)
+    -> f64
+    where V: PartialEq
+{
+    guard(|| {
+        match eval_partial(expr, vars, values) {
+            Some(true)  => return 1.0,
+            Some(false) => return 0.0,
+            None        => (),
+        }
+        values[idx] = Some(true);
+        let t = truth_probability(expr, vars, probs, values, idx + 1);
+        values[idx] = Some(false);
+        let f = truth_probability(expr, vars, probs, values, idx + 1);
+        values[idx] = None;
+        probs[idx] * t + (1.0 - probs[idx]) * f
+    })
+}
+
+/// Returns the first assignment of `var_count` variables for which the given
+/// functions differ, or `None` if they agree on every assignment.
+pub (in crate) fn counterexample<F, G>(var_count: usize, f: F, g: G)
+    -> Option<u64>
+    where
+        F: Fn(u64) -> bool,
+        G: Fn(u64) -> bool,
+{
+    (0..(1u64 << var_count)).find(|&assignment| f(assignment) != g(assignment))
+}
+
+/// Panics if the given functions differ on some assignment of `var_count`
+/// variables, naming the transformation which produced `after`. Functions of
+/// too many variables to enumerate are not checked.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+fn verify_equivalent<F, G>(transform: &str, var_count: usize, before: F, after: G)
+    where
+        F: Fn(u64) -> bool,
+        G: Fn(u64) -> bool,
+{
+    if var_count > VERIFY_MAX_VARS { return; }
+    if let Some(assignment) = counterexample(var_count, before, after) {
+        panic!("{} changed the value of the expression under assignment {:#b} \
+            of {} variables",
+            transform,
+            assignment,
+            var_count);
+    }
+}
+
+/// Evaluates the conjunction of the given clauses if `conjunctive` is true, or
+/// their disjunction otherwise, under an assignment of the given variables.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+fn eval_clauses_assignment<'a, V, I>(
+    clauses: I,
+    conjunctive: bool,
+    vars: &[&V],
+    assignment: u64)
+    -> bool
+    where
+        V: PartialEq + 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+{
+    let mut values = clauses.into_iter()
+        .map(|clause| eval_assignment(clause, vars, assignment));
+    if conjunctive {
+        values.all(|value| value)
+    } else {
+        values.any(|value| value)
+    }
+}
+
+/// Panics if the conjunction or disjunction of the given clauses is not
+/// equivalent to the expression. Expressions with too many variables to
+/// enumerate are not checked.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn verify_clauses<V>(
+    expr: &Expr<V>,
+    clauses: &[Expr<V>],
+    conjunctive: bool)
+    where V: PartialEq
+{
+    let vars = distinct_vars(std::iter::once(expr).chain(clauses));
+    verify_equivalent("normal form conversion", vars.len(),
+        |assignment| eval_assignment(expr, &vars, assignment),
+        |assignment| eval_clauses_assignment(clauses, conjunctive, &vars,
+            assignment));
+}
+
+/// Panics if the transformed expression is not equivalent to the original.
+/// Expressions with too many variables to enumerate are not checked.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn verify_transform<V>(
+    transform: &str,
+    before: &Expr<V>,
+    after: &Expr<V>)
+    where V: PartialEq
+{
+    let vars = distinct_vars(vec![before, after]);
+    verify_equivalent(transform, vars.len(),
+        |assignment| eval_assignment(before, &vars, assignment),
+        |assignment| eval_assignment(after, &vars, assignment));
+}
+
+/// Panics if removing clauses from the conjunction of `before` if
+/// `conjunctive` is true, or its disjunction otherwise, leaving `after`,
+/// changed its value. Expressions with too many variables to enumerate are not
+/// checked.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn verify_removal<V>(
+    transform: &str,
+    before: &[&Expr<V>],
+    after: &[&Expr<V>],
+    conjunctive: bool)
+    where V: PartialEq
+{
+    let vars = distinct_vars(before.iter().copied());
+    verify_equivalent(transform, vars.len(),
+        |assignment| eval_clauses_assignment(before.iter().copied(),
+            conjunctive, &vars, assignment),
+        |assignment| eval_clauses_assignment(after.iter().copied(),
+            conjunctive, &vars, assignment));
+}
+
+/// Records the values of the expression under every assignment of its
+/// variables, so that a transformation which consumes the expression can be
+/// checked with [`verify_truth_table`] without keeping a copy of it. Returns
+/// `None` if the expression has too many variables to enumerate.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn truth_table<V>(expr: &Expr<V>) -> Option<(Vec<V>, Vec<bool>)>
+    where V: PartialEq + Clone
+{
+    let vars = distinct_vars(std::iter::once(expr));
+    if vars.len() > VERIFY_MAX_VARS { return None; }
+    let values = (0..(1u64 << vars.len()))
+        .map(|assignment| eval_assignment(expr, &vars, assignment))
+        .collect();
+    Some((vars.into_iter().cloned().collect(), values))
+}
+
+/// Panics if the transformed expression does not have the values recorded by
+/// [`truth_table`] for the original.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn verify_truth_table<V>(
+    transform: &str,
+    table: Option<(Vec<V>, Vec<bool>)>,
+    after: &Expr<V>)
+    where V: PartialEq
+{
+    let (vars, values) = match table {
+        Some(table) => table,
+        None        => return,
+    };
+    let vars: Vec<&V> = vars.iter().collect();
+    verify_equivalent(transform, vars.len(),
+        |assignment| values[assignment as usize],
+        |assignment| eval_assignment(after, &vars, assignment));
+}
+
+/// Evaluates the expression with each variable replaced by the bit of
+/// `assignment` at the index of its occurrence in pre-order, starting from
+/// `next`.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+fn eval_occurrences<V>(expr: &Expr<V>, next: &mut usize, assignment: u64)
+    -> bool
+{
+    use Expr::*;
+    // Both operands are always evaluated so that the occurrences are numbered
+    // the same way under every assignment.
+    guard(|| match expr {
+        Var(_)    => {
+            *next += 1;
+            assignment & (1 << (*next - 1)) != 0
+        },
+        Not(p)    => !eval_occurrences(p, next, assignment),
+        Or(a, b)  => {
+            let a = eval_occurrences(a, next, assignment);
+            eval_occurrences(b, next, assignment) || a
+        },
+        And(a, b) => {
+            let a = eval_occurrences(a, next, assignment);
+            eval_occurrences(b, next, assignment) && a
+        },
+    })
+}
+
+/// Records the values of the expression under every assignment of its
+/// variable occurrences, as [`truth_table`] does for its variables. Returns
+/// `None` if the expression has too many occurrences to enumerate.
+///
+/// Conversion to negation normal form keeps every occurrence of a variable in
+/// order, so the occurrences can stand in for the variables when checking it,
+/// even if they cannot be compared.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn occurrence_table<V>(expr: &Expr<V>) -> Option<Vec<bool>> {
+    let count = expr.iter_subexprs(TraversalOrder::PreOrder)
+        .filter(|expr| matches!(expr, Expr::Var(_)))
+        .count();
+    if count > VERIFY_MAX_VARS { return None; }
+    Some((0..(1u64 << count))
+        .map(|assignment| eval_occurrences(expr, &mut 0, assignment))
+        .collect())
+}
+
+/// Panics if the transformed expression does not have the values recorded by
+/// [`occurrence_table`] for the original.
+#[cfg(all(feature = "verify-transforms", debug_assertions))]
+pub (in crate) fn verify_occurrence_table<V>(
+    transform: &str,
+    table: Option<Vec<bool>>,
+    after: &Expr<V>)
+{
+    let values = match table {
+        Some(values) => values,
+        None         => return,
+    };
+    // The table has one entry per assignment of the original occurrences.
+    let count = values.len().trailing_zeros() as usize;
+    verify_equivalent(transform, count,
+        |assignment| values[assignment as usize],
+        |assignment| eval_occurrences(after, &mut 0, assignment));
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Distinguishing models
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Returns an assignment of the variables of both expressions for which
+    /// they have different values, or `None` if the expressions are
+    /// equivalent.
+    ///
+    /// The search assigns variables in order of first occurrence and abandons
+    /// any partial assignment for which both expressions already have the
+    /// same value, but may still take time exponential in the number of
+    /// variables.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let a = Expr::from(1).and(Expr::from(2).or(3));
+    /// let b = Expr::from(1).and(2).or(Expr::from(1).and(3));
+    /// assert_eq!(a.distinguishing_model(&b), None);
+    ///
+    /// let c = Expr::from(1).and(2).or(3);
+    /// let model = a.distinguishing_model(&c).unwrap();
+    /// assert!(!model[&1] && model[&3]);
+    /// ```
+    pub fn distinguishing_model(&self, other: &Expr<V>)
+        -> Option<HashMap<V, bool>>
+    {
+        let vars = distinct_vars(vec![self, other]);
+        let mut values = vec![None; vars.len()];
+        if !find_difference(
+            (&[self], true),
+            (&[other], true),
+            &vars,
+            &mut values,
+            0)
+        {
+            return None;
+        }
+        Some(vars.into_iter()
+            .zip(values)
+            .map(|(var, value)| (var.clone(), value.unwrap_or(false)))
+            .collect())
+    }
+}
+
+/// Returns true if the conjunctions or disjunctions of the given clauses
+/// have the same value under every assignment of their variables. An
+/// expression may be passed as a single conjunctive clause.
+pub (in crate) fn clauses_equivalent<V>(
+    a: &[&Expr<V>],
+    a_conjunctive: bool,
+    b: &[&Expr<V>],
+    b_conjunctive: bool)
+    -> bool
+    where V: PartialEq
+{
+    let vars = distinct_vars(a.iter().chain(b).copied());
+    let mut values = vec![None; vars.len()];
+    !find_difference(
+        (a, a_conjunctive),
+        (b, b_conjunctive),
+        &vars,
+        &mut values,
+        0)
+}
+
+/// Extends the partial assignment of the variables from `idx` onward until the
+/// conjunction of the clauses is true, returning false if no such extension
+/// exists.
+pub (in crate) fn find_model<V>(
+    clauses: &[&Expr<V>],
+    vars: &[&V],
+    values: &mut Vec<Option<bool>>,
+    idx: usize)
+    -> bool
+    where V: PartialEq
+{
+    guard(|| {
+        if let Some(x) = eval_clauses_partial(clauses, true, vars, values) {
+            return x;
+        }
+        for &value in &[false, true] {
+            values[idx] = Some(value);
+            if find_model(clauses, vars, values, idx + 1) { return true; }
+        }
+        values[idx] = None;
+        false
+    })
+}
+
+/// Evaluates the conjunction or disjunction of the clauses under a partial
+/// assignment of the given variables, returning `None` if its value depends
+/// on an unassigned variable.
+fn eval_clauses_partial<V>(
+    clauses: &[&Expr<V>],
+    conjunctive: bool,
+    vars: &[&V],
+    values: &[Option<bool>])
+    -> Option<bool>
+    where V: PartialEq
+{
+    let mut result = Some(conjunctive);
+    for clause in clauses {
+        match eval_partial(clause, vars, values) {
+            Some(value) if value != conjunctive => return Some(value),
+            Some(_) => (),
+            None    => result = None,
+        }
+    }
+    result
+}
+
+/// Extends the partial assignment of the variables from `idx` onward until the
+/// clause sets have different values, returning false if no such extension
+/// exists.
+fn find_difference<V>(
+    a: (&[&Expr<V>], bool),
+    b: (&[&Expr<V>], bool),
+    vars: &[&V],
+    values: &mut Vec<Option<bool>>,
+    idx: usize)
+    -> bool
+    where V: PartialEq
+{
+    guard(|| {
+        if let (Some(x), Some(y)) = (
+            eval_clauses_partial(a.0, a.1, vars, values),
+            eval_clauses_partial(b.0, b.1, vars, values))
+        {
+            return x != y;
+        }
+        for &value in &[false, true] {
+            values[idx] = Some(value);
+            if find_difference(a, b, vars, values, idx + 1) { return true; }
+        }
+        values[idx] = None;
+        false
+    })
+}
@@ -0,0 +1,331 @@
+
+// Internal library imports.
+use crate::Expr;
+use crate::TraversalOrder;
+use crate::fasthash::FastHashMap;
+use crate::fasthash::FastHashSet;
+
+// Standard library imports
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RenameError
+////////////////////////////////////////////////////////////////////////////////
+/// An error indicating that renaming the variables of an expression would
+/// merge two distinct variables into one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenameError<V> {
+    /// The variable both variables would be renamed to.
+    pub target: V,
+    /// The first variable, in order of occurrence.
+    pub first: V,
+    /// The second variable, in order of occurrence.
+    pub second: V,
+}
+
+impl<V> Display for RenameError<V> where V: Debug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "renaming would merge variables {:?} and {:?} into {:?}",
+            self.first,
+            self.second,
+            self.target)
+    }
+}
+
+impl<V> Error for RenameError<V> where V: Debug {}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RenameErrorText
+////////////////////////////////////////////////////////////////////////////////
+/// A [`RenameError`] with its variables written with `Debug`, which is kept
+/// when it is converted into an [`Error`].
+///
+/// [`RenameError`]: crate::RenameError
+/// [`Error`]: crate::Error
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenameErrorText {
+    /// The variable both variables would be renamed to.
+    pub target: String,
+    /// The first variable, in order of occurrence.
+    pub first: String,
+    /// The second variable, in order of occurrence.
+    pub second: String,
+}
+
+impl Display for RenameErrorText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "renaming would merge variables {} and {} into {}",
+            self.first,
+            self.second,
+            self.target)
+    }
+}
+
+impl Error for RenameErrorText {}
+
+impl<V> From<RenameError<V>> for RenameErrorText where V: Debug {
+    fn from(e: RenameError<V>) -> Self {
+        RenameErrorText {
+            target: format!("{:?}", e.target),
+            first: format!("{:?}", e.first),
+            second: format!("{:?}", e.second),
+        }
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Renaming
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Returns a copy of the expression with each variable in the map replaced
+    /// by its mapped value. Variables not in the map are unchanged.
+    ///
+    /// Returns an error if two distinct variables of the expression would have
+    /// the same name afterward, either because both are renamed to it, or
+    /// because one is renamed to the name of another which is kept.
+    pub fn rename_vars(&self, map: &HashMap<V, V>)
+        -> Result<Expr<V>, RenameError<V>>
+    {
+        let mut sources: FastHashMap<&V, &V> = FastHashMap::default();
+        for expr in self.iter_subexprs(TraversalOrder::PreOrder) {
+            if let Expr::Var(var) = expr {
+                let target = map.get(var).unwrap_or(var);
+                match sources.get(target) {
+                    Some(source) if *source != var => return Err(RenameError {
+                        target: target.clone(),
+                        first: (*source).clone(),
+                        second: var.clone(),
+                    }),
+                    Some(_) => (),
+                    None    => { let _ = sources.insert(target, var); },
+                }
+            }
+        }
+
+        Ok(self.clone().map(|var| match map.get(&var) {
+            Some(target) => target.clone(),
+            None         => var,
+        }))
+    }
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Free variables
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Returns the set of variables appearing in the expression.
+    pub fn free_vars(&self) -> HashSet<V> {
+        let mut vars = HashSet::new();
+        collect_vars(self, &mut vars);
+        vars
+    }
+}
+
+/// Inserts the variables appearing in the expression into the given set.
+fn collect_vars<V, S>(expr: &Expr<V>, vars: &mut HashSet<V, S>)
+    where
+        V: Eq + Hash + Clone,
+        S: BuildHasher,
+{
+    for expr in expr.iter_subexprs(TraversalOrder::PreOrder) {
+        if let Expr::Var(var) = expr {
+            if !vars.contains(var) { let _ = vars.insert(var.clone()); }
+        }
+    }
+}
+
+/// Returns the set of variables appearing in the expression, for internal use.
+pub (in crate) fn expr_vars<V>(expr: &Expr<V>) -> FastHashSet<V>
+    where V: Eq + Hash + Clone
+{
+    let mut vars = FastHashSet::default();
+    collect_vars(expr, &mut vars);
+    vars
+}
+
+/// Returns the set of variables appearing in any of the given clauses.
+pub (in crate) fn clause_vars<'a, V, I>(clauses: I) -> HashSet<V>
+    where
+        V: Eq + Hash + Clone + 'a,
+        I: IntoIterator<Item=&'a Expr<V>>,
+{
+    let mut vars = HashSet::new();
+    for clause in clauses {
+        collect_vars(clause, &mut vars);
+    }
+    vars
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Independent components
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Eq + Hash + Clone {
+    /// Partitions the variables of the expression into groups which are
+    /// connected through its top-level conjuncts. Two variables are in the
+    /// same group if they appear in the same conjunct, or are each connected
+    /// to a third variable in the group.
+    ///
+    /// Groups are returned in order of their first occurrence.
+    pub fn var_components(&self) -> Vec<HashSet<V>> {
+        let conjuncts = top_conjuncts(self);
+        let vars: Vec<HashSet<V>> = conjuncts
+            .iter()
+            .map(|conjunct| conjunct.free_vars())
+            .collect();
+        group_conjuncts(&vars)
+            .into_iter()
+            .map(|group| group
+                .into_iter()
+                .flat_map(|idx| vars[idx].iter().cloned())
+                .collect())
+            .collect()
+    }
+
+    /// Splits the expression into conjuncts over disjoint sets of variables,
+    /// whose conjunction is equivalent to the expression. Each of the results
+    /// can be evaluated or solved independently of the others.
+    ///
+    /// Results are returned in the order of the components given by
+    /// [`var_components`].
+    ///
+    /// [`var_components`]: Expr::var_components
+    pub fn split_independent(self) -> Vec<Expr<V>> {
+        let vars: Vec<HashSet<V>> = top_conjuncts(&self)
+            .iter()
+            .map(|conjunct| conjunct.free_vars())
+            .collect();
+        let groups = group_conjuncts(&vars);
+
+        let mut conjuncts: Vec<Option<Expr<V>>> = self.split_top_and()
+            .into_iter()
+            .map(Some)
+            .collect();
+        groups
+            .into_iter()
+            .filter_map(|group| Expr::conjoin(group
+                .into_iter()
+                .filter_map(|idx| conjuncts[idx].take())))
+            .collect()
+    }
+}
+
+/// Returns the top-level conjuncts of the expression, from left to right.
+fn top_conjuncts<V>(expr: &Expr<V>) -> Vec<&Expr<V>> {
+    let mut conjuncts = Vec::new();
+    let mut stack = vec![expr];
+    while let Some(expr) = stack.pop() {
+        match expr {
+            Expr::And(a, b) => { stack.push(b); stack.push(a); },
+            _               => conjuncts.push(expr),
+        }
+    }
+    conjuncts
+}
+
+/// Groups the indices of conjuncts which are connected through shared
+/// variables, given the variables of each conjunct. Groups are ordered by
+/// their first conjunct.
+fn group_conjuncts<V>(vars: &[HashSet<V>]) -> Vec<Vec<usize>>
+    where V: Eq + Hash
+{
+    // Union-find over conjunct indices.
+    fn find(parents: &mut [usize], mut idx: usize) -> usize {
+        while parents[idx] != idx {
+            parents[idx] = parents[parents[idx]];
+            idx = parents[idx];
+        }
+        idx
+    }
+
+    let mut parents: Vec<usize> = (0..vars.len()).collect();
+    let mut owners: FastHashMap<&V, usize> = FastHashMap::default();
+    for (idx, conjunct_vars) in vars.iter().enumerate() {
+        for var in conjunct_vars {
+            match owners.get(var) {
+                Some(&owner) => {
+                    let a = find(&mut parents, owner);
+                    let b = find(&mut parents, idx);
+                    // Keep the earliest conjunct as the root.
+                    if a < b { parents[b] = a; } else { parents[a] = b; }
+                },
+                None => { let _ = owners.insert(var, idx); },
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root: FastHashMap<usize, usize> =
+        FastHashMap::default();
+    for idx in 0..vars.len() {
+        let root = find(&mut parents, idx);
+        match group_of_root.get(&root) {
+            Some(&group) => groups[group].push(idx),
+            None => {
+                let _ = group_of_root.insert(root, groups.len());
+                groups.push(vec![idx]);
+            },
+        }
+    }
+    groups
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Pushdown
+////////////////////////////////////////////////////////////////////////////////
+impl<V> Expr<V> where V: Clone {
+    /// Splits the expression into a conjunction of a part containing only
+    /// supported variables and a residual part, either of which may be empty.
+    /// The expression is true exactly when both parts are true, treating an
+    /// empty part as true.
+    ///
+    /// The split is made between the top-level conjuncts of the expression,
+    /// with each conjunct going to the supported part if all of its variables
+    /// are supported. Converting the expression to conjunctive normal form
+    /// first may allow more of it to be supported.
+    ///
+    /// This is intended for pushing the supported part of a predicate down to
+    /// an external system, such as a database, and evaluating the residual
+    /// part on the results.
+    ///
+    /// ```rust
+    /// use simple_predicates::Expr;
+    ///
+    /// let expr = Expr::from(1).and(Expr::from(2).or(13)).and(!Expr::from(3));
+    /// let (pushed, residual) = expr.split_by(|v| *v < 10);
+    ///
+    /// assert_eq!(pushed, Some(Expr::from(1).and(!Expr::from(3))));
+    /// assert_eq!(residual, Some(Expr::from(2).or(13)));
+    /// ```
+    pub fn split_by<F>(&self, supported: F)
+        -> (Option<Expr<V>>, Option<Expr<V>>)
+        where F: Fn(&V) -> bool
+    {
+        let (pushed, residual): (Vec<&Expr<V>>, Vec<&Expr<V>>) =
+            top_conjuncts(self)
+                .into_iter()
+                .partition(|conjunct| conjunct.all_subexprs(|expr| match expr {
+                    Expr::Var(var) => supported(var),
+                    _              => true,
+                }));
+
+        if residual.is_empty() { return (Some(self.clone()), None); }
+        if pushed.is_empty() { return (None, Some(self.clone())); }
+        (
+            Expr::conjoin(pushed.into_iter().cloned()),
+            Expr::conjoin(residual.into_iter().cloned()),
+        )
+    }
+}
@@ -2,16 +2,53 @@
 // Internal library imports.
 use crate::Eval;
 use crate::Expr;
+use crate::EvalObserver;
+use crate::observe::eval_clauses_observed;
+use crate::ClauseError;
+use crate::IncrementalSolver;
+use crate::Literal;
+use crate::LiteralDnf;
+use crate::CoverageReport;
+use crate::FactoredCnf;
+use crate::Polarity;
+use crate::clausal::absorb_terms;
+use crate::clausal::check_clauses;
+use crate::coverage::clause_coverage;
+use crate::factor::factor_common;
+use crate::clausal::eval_clauses_many;
+use crate::clausal::is_complementary;
+use crate::clausal::pure_literal;
+use crate::clausal::redundant_clauses;
+use crate::clausal::sort_clauses_canonical;
+use crate::clausal::var_polarities;
+use crate::terms::required_vars;
+use crate::terms::term_literals;
+use crate::truth::clauses_equivalent;
+use crate::vars::clause_vars;
 
 // External library imports
 #[cfg(feature = "serde")] use serde::Serialize;
 #[cfg(feature = "serde")] use serde::Deserialize;
 
+// Standard library imports
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::BitAndAssign;
+use std::ops::BitOrAssign;
+
 ////////////////////////////////////////////////////////////////////////////////
 // CnfVec
 ////////////////////////////////////////////////////////////////////////////////
 /// A boolean expression in [Conjunctive Normal Form], stored as a `Vec`.
 ///
+/// When converted from an [`Expr`], the clauses are ordered from left to right
+/// as they occur in the simplified expression, and the clauses distributed
+/// from a single `And` keep the order of its operands. The conversion of a
+/// given expression always produces the same order. Use `sort_canonical` to
+/// put clauses of equal expressions in the same order.
+///
+/// [`Expr`]: crate::Expr
 /// [Conjunctive Normal Form]: https://en.wikipedia.org/wiki/Conjunctive_normal_form
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -40,6 +77,227 @@ impl<V> CnfVec<V> where V: Eval + PartialEq {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Evaluates the expression, reporting each variable evaluation and
+    /// conjunctive clause result to the given observer.
+    pub fn eval_observed<O>(&self, data: &V::Context, observer: &mut O) -> bool
+        where O: EvalObserver<V>
+    {
+        eval_clauses_observed(self.0.iter(), true, data, observer)
+    }
+
+    /// Removes every clause containing both a variable and its negation. Such
+    /// clauses are always satisfied, so removing them does not change the
+    /// value of the expression.
+    pub fn remove_tautologies(&mut self) {
+        #[cfg(all(feature = "verify-transforms", debug_assertions))]
+        {
+            let before: Vec<_> = self.0.iter().collect();
+            let after: Vec<_> = before.iter()
+                .copied()
+                .filter(|clause| !is_complementary(clause, true))
+                .collect();
+            crate::truth::verify_removal("tautology removal", &before, &after,
+                true);
+        }
+        self.0.retain(|clause| !is_complementary(clause, true));
+    }
+
+    /// Checks that every clause is a disjunction of literals, returning the
+    /// location of the first malformed node otherwise.
+    pub fn check_clausal(&self) -> Result<(), ClauseError> {
+        check_clauses(self.0.iter(), true)
+    }
+
+    /// Returns the union of the clauses of both expressions, which is their
+    /// conjunction. Clauses of `other` which are already present are skipped.
+    pub fn union(mut self, other: Self) -> Self {
+        for clause in other.0 {
+            if !self.0.contains(&clause) { self.0.push(clause); }
+        }
+        self
+    }
+
+    /// Returns the clauses common to both expressions. The result is implied
+    /// by each of the expressions, and so by their disjunction.
+    pub fn intersection(mut self, other: &Self) -> Self {
+        self.0.retain(|clause| other.0.contains(clause));
+        self
+    }
+
+    /// Returns the clauses of the expression which are not clauses of `other`.
+    /// Conjoining the result with `other` gives their union.
+    pub fn difference(mut self, other: &Self) -> Self {
+        self.0.retain(|clause| !other.0.contains(clause));
+        self
+    }
+
+    /// Returns the set of variables appearing in the expression.
+    pub fn vars(&self) -> HashSet<V> where V: Eq + Hash + Clone {
+        clause_vars(self.0.iter())
+    }
+
+    /// Returns the polarity of each variable appearing in the expression.
+    pub fn classify_vars(&self) -> HashMap<V, Polarity>
+        where V: Eq + Hash + Clone
+    {
+        var_polarities(self.0.iter())
+    }
+
+    /// Removes every clause containing a pure literal, i.e., a variable which
+    /// only appears with a single polarity, repeating until no pure literals
+    /// remain.
+    ///
+    /// This preserves satisfiability, but not equivalence: the result is
+    /// satisfiable if and only if the original expression is, and any model
+    /// of the result is extended to a model of the original by the returned
+    /// assignment of the pure variables of the removed clauses.
+    pub fn eliminate_pure_literals(&mut self) -> HashMap<V, bool>
+        where V: Eq + Hash + Clone
+    {
+        let mut assignment = HashMap::new();
+        loop {
+            let polarities = var_polarities(self.0.iter());
+            let len = self.0.len();
+            self.0.retain(|clause| match pure_literal(clause, &polarities) {
+                Some((var, value)) => {
+                    let _ = assignment.insert(var.clone(), value);
+                    false
+                },
+                None => true,
+            });
+            if self.0.len() == len { break; }
+        }
+        assignment
+    }
+
+    /// Evaluates the expression on each of the given contexts, returning the
+    /// results in order.
+    ///
+    /// Each clause is evaluated across all of the contexts before moving on to
+    /// the next, skipping contexts already falsified by an earlier clause.
+    pub fn eval_many<'a, I>(&self, ctxs: I) -> Vec<bool>
+        where
+            I: IntoIterator<Item=&'a V::Context>,
+            V::Context: 'a,
+    {
+        eval_clauses_many(self.0.iter(), true, ctxs)
+    }
+
+    /// Returns the first clause which is false on the given context, which
+    /// decides that the expression is false, or `None` if the expression is
+    /// true.
+    pub fn first_failing_clause(&self, data: &V::Context) -> Option<&Expr<V>> {
+        self.0.iter().find(|clause| !clause.eval(data))
+    }
+
+    /// Returns the number of clauses which are true on the given context.
+    /// Every clause is evaluated, without short-circuiting.
+    pub fn satisfied_count(&self, data: &V::Context) -> usize {
+        self.0.iter().filter(|clause| clause.eval(data)).count()
+    }
+
+    /// Sorts the literals of each clause by variable, with unnegated literals
+    /// first, and then sorts the clauses by their literals, so that
+    /// expressions with the same clauses have the same order.
+    ///
+    /// Clauses which are not disjunctions of literals are kept after the
+    /// others, in their original order.
+    pub fn sort_canonical(&mut self) where V: Ord + Clone {
+        sort_clauses_canonical(&mut self.0, true);
+    }
+
+    /// Returns true if the expression has the same value as `other` on
+    /// every assignment of their variables, even if their clauses differ.
+    ///
+    /// This may take time exponential in the number of variables.
+    pub fn equivalent(&self, other: &Self) -> bool {
+        let a: Vec<_> = self.0.iter().collect();
+        let b: Vec<_> = other.0.iter().collect();
+        clauses_equivalent(&a, true, &b, true)
+    }
+
+    /// Returns true if the conjunction of the clauses has the same value as the
+    /// given expression on every assignment of their variables.
+    ///
+    /// This may take time exponential in the number of variables.
+    pub fn equivalent_expr(&self, expr: &Expr<V>) -> bool {
+        let clauses: Vec<_> = self.0.iter().collect();
+        clauses_equivalent(&clauses, true, &[expr], true)
+    }
+
+    /// Evaluates every clause on every given context, returning how many
+    /// contexts each clause satisfied and rejected.
+    ///
+    /// Clauses which never rejected a context are candidates for removal from
+    /// the rule set.
+    pub fn coverage<'a, I>(&self, ctxs: I) -> CoverageReport<'_, V>
+        where
+            I: IntoIterator<Item=&'a V::Context>,
+            V::Context: 'a,
+    {
+        clause_coverage(self.0.iter(), ctxs)
+    }
+
+    /// Returns the clauses which are implied by the other clauses, in their
+    /// original order.
+    ///
+    /// Any one of the returned clauses may be removed without changing the
+    /// expression, but removing several of them may: of two identical
+    /// clauses, both are returned. This may take time exponential in the
+    /// number of variables.
+    pub fn redundant_clauses(&self) -> Vec<&Expr<V>> {
+        let clauses: Vec<_> = self.0.iter().collect();
+        redundant_clauses(&clauses)
+    }
+
+    /// Returns the literals which are true in every assignment satisfying the
+    /// expression and the given assumptions, excluding the assumptions
+    /// themselves. Returns no literals if the assumptions are unsatisfiable.
+    ///
+    /// Returns an error if a clause is not a disjunction of literals. See
+    /// [`IncrementalSolver::implied_literals`] to answer repeated queries.
+    ///
+    /// [`IncrementalSolver::implied_literals`]: crate::IncrementalSolver::implied_literals
+    pub fn implied_literals(&self, assumptions: &[Literal<V>])
+        -> Result<Vec<Literal<V>>, ClauseError>
+        where V: Eq + Hash + Clone
+    {
+        Ok(IncrementalSolver::new(self)?.implied_literals(assumptions))
+    }
+
+    /// Returns an evaluation plan for the expression which evaluates each
+    /// variable appearing in more than one place at most once, rather than
+    /// once per clause containing it.
+    ///
+    /// ```rust
+    /// # use simple_predicates::Eval;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::CnfVec;
+    /// use simple_predicates::Expr::Var;
+    ///
+    /// // (1 | 2) & (1 | 3)
+    /// let cnf = CnfVec::from(vec![
+    ///     Var(Contains(1)).or(Var(Contains(2))),
+    ///     Var(Contains(1)).or(Var(Contains(3))),
+    /// ]);
+    /// let factored = cnf.factor_common();
+    ///
+    /// assert_eq!(factored.shared_atoms(), &[Contains(1)]);
+    /// assert_eq!(factored.eval(&vec![2, 3]), cnf.eval(&vec![2, 3]));
+    /// ```
+    pub fn factor_common(&self) -> FactoredCnf<V>
+        where V: Eq + Hash + Clone
+    {
+        factor_common(&self.0)
+    }
 }
 
 impl<V> Eval for CnfVec<V> where V: Eval + PartialEq {
@@ -50,25 +308,9 @@ impl<V> Eval for CnfVec<V> where V: Eval + PartialEq {
     }
 }
 
-impl<V> From<Expr<V>> for CnfVec<V> where V: Eval + PartialEq {
+impl<V> From<Expr<V>> for CnfVec<V> where V: Eval + PartialEq + Clone {
     fn from(expr: Expr<V>) -> Self {
-        use Expr::*;
-        let mut clauses = Vec::new();
-        let mut queue = Vec::with_capacity(2);
-        queue.push(expr.simplify());
-
-        while let Some(expr) = queue.pop() {
-            match expr.pushdown_not().distribute_or() {
-                And(a, b) => {
-                    queue.push(*a);
-                    queue.push(*b);
-                },
-                other => {
-                    let _ = clauses.push(other);
-                }
-            }
-        }
-        CnfVec(clauses)
+        CnfVec(expr.into_normal_form(true))
     }
 }
 
@@ -99,12 +341,31 @@ impl<V> Default for CnfVec<V> where V: Eval + PartialEq {
     }
 }
 
+impl<V> BitAndAssign for CnfVec<V> where V: Eval + PartialEq {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = std::mem::take(self).union(rhs);
+    }
+}
+
+impl<V> BitAndAssign<Expr<V>> for CnfVec<V> where V: Eval + PartialEq + Clone {
+    fn bitand_assign(&mut self, rhs: Expr<V>) {
+        *self &= CnfVec::from(rhs);
+    }
+}
+
 
 ////////////////////////////////////////////////////////////////////////////////
 // DnfVec
 ////////////////////////////////////////////////////////////////////////////////
 /// A boolean expression in [Disjunctive Normal Form], stored as a `Vec`.
 ///
+/// When converted from an [`Expr`], the terms are ordered from left to right
+/// as they occur in the simplified expression, and the terms distributed
+/// from a single `Or` keep the order of its operands. The conversion of a
+/// given expression always produces the same order. Use `sort_canonical` to
+/// put terms of equal expressions in the same order.
+///
+/// [`Expr`]: crate::Expr
 /// [Disjunctive Normal Form]: https://en.wikipedia.org/wiki/Disjunctive_normal_form
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -133,6 +394,175 @@ impl<V> DnfVec<V> where V: Eval + PartialEq{
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Evaluates the expression, reporting each variable evaluation and
+    /// disjunctive clause result to the given observer.
+    pub fn eval_observed<O>(&self, data: &V::Context, observer: &mut O) -> bool
+        where O: EvalObserver<V>
+    {
+        eval_clauses_observed(self.0.iter(), false, data, observer)
+    }
+
+    /// Removes every term which includes all of the conjuncts of another term,
+    /// as such terms are absorbed by the other: `(a & b) | (a & b & c)` is
+    /// equivalent to `a & b`.
+    pub fn absorb(&mut self) {
+        self.0 = absorb_terms(std::mem::take(&mut self.0));
+    }
+
+    /// Checks that every clause is a conjunction of literals, returning the
+    /// location of the first malformed node otherwise.
+    pub fn check_clausal(&self) -> Result<(), ClauseError> {
+        check_clauses(self.0.iter(), false)
+    }
+
+    /// Returns the union of the terms of both expressions, which is their
+    /// disjunction. Terms of `other` which are already present are skipped.
+    pub fn union(mut self, other: Self) -> Self {
+        for term in other.0 {
+            if !self.0.contains(&term) { self.0.push(term); }
+        }
+        self
+    }
+
+    /// Returns the terms common to both expressions. The result implies each
+    /// of the expressions, and so their conjunction.
+    pub fn intersection(mut self, other: &Self) -> Self {
+        self.0.retain(|term| other.0.contains(term));
+        self
+    }
+
+    /// Returns the terms of the expression which are not terms of `other`.
+    /// Disjoining the result with `other` gives their union.
+    pub fn difference(mut self, other: &Self) -> Self {
+        self.0.retain(|term| !other.0.contains(term));
+        self
+    }
+
+    /// Returns the set of variables appearing in the expression.
+    pub fn vars(&self) -> HashSet<V> where V: Eq + Hash + Clone {
+        clause_vars(self.0.iter())
+    }
+
+    /// Returns the first term which is true on the given context, which
+    /// decides that the expression is true, or `None` if the expression is
+    /// false.
+    pub fn first_matching_clause(&self, data: &V::Context) -> Option<&Expr<V>> {
+        self.0.iter().find(|term| term.eval(data))
+    }
+
+    /// Returns the number of terms which are true on the given context.
+    /// Every term is evaluated, without short-circuiting.
+    pub fn satisfied_count(&self, data: &V::Context) -> usize {
+        self.0.iter().filter(|term| term.eval(data)).count()
+    }
+
+    /// Sorts the literals of each term by variable, with unnegated literals
+    /// first, and then sorts the terms by their literals, so that
+    /// expressions with the same terms have the same order.
+    ///
+    /// Terms which are not conjunctions of literals are kept after the
+    /// others, in their original order.
+    pub fn sort_canonical(&mut self) where V: Ord + Clone {
+        sort_clauses_canonical(&mut self.0, false);
+    }
+
+    /// Returns true if the expression has the same value as `other` on
+    /// every assignment of their variables, even if their clauses differ.
+    ///
+    /// This may take time exponential in the number of variables.
+    pub fn equivalent(&self, other: &Self) -> bool {
+        let a: Vec<_> = self.0.iter().collect();
+        let b: Vec<_> = other.0.iter().collect();
+        clauses_equivalent(&a, false, &b, false)
+    }
+
+    /// Returns true if the disjunction of the clauses has the same value as the
+    /// given expression on every assignment of their variables.
+    ///
+    /// This may take time exponential in the number of variables.
+    pub fn equivalent_expr(&self, expr: &Expr<V>) -> bool {
+        let clauses: Vec<_> = self.0.iter().collect();
+        clauses_equivalent(&clauses, false, &[expr], true)
+    }
+
+    /// Returns the terms of the expression as lists of literals.
+    ///
+    /// Returns an error if a term is not a conjunction of literals.
+    ///
+    /// ```rust
+    /// # use simple_predicates::Eval;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::DnfVec;
+    /// use simple_predicates::Expr::Var;
+    /// use simple_predicates::Literal;
+    ///
+    /// // (1 & !2) | 3
+    /// let dnf = DnfVec::from(vec![
+    ///     Var(Contains(1)).and(!Var(Contains(2))),
+    ///     Var(Contains(3)),
+    /// ]);
+    /// let terms = dnf.to_literal_dnf().unwrap();
+    /// let mut terms = terms.terms();
+    ///
+    /// assert_eq!(terms.next(), Some(&[
+    ///     Literal::positive(Contains(1)),
+    ///     Literal::negative(Contains(2)),
+    /// ][..]));
+    /// assert_eq!(terms.next(), Some(&[Literal::positive(Contains(3))][..]));
+    /// assert_eq!(terms.next(), None);
+    /// ```
+    pub fn to_literal_dnf(&self) -> Result<LiteralDnf<V>, ClauseError>
+        where V: Clone
+    {
+        check_clauses(self.0.iter(), false)?;
+        Ok(LiteralDnf::from_terms(self.0.iter().map(term_literals)))
+    }
+
+    /// Returns the variables which must be true for each term to be true, in
+    /// the order of the terms. These are the unnegated variables among the
+    /// top-level conjuncts of each term.
+    ///
+    /// Since the expression can only be true if some term is true, any
+    /// data for which none of these sets is entirely true can be skipped
+    /// without evaluating the expression.
+    ///
+    /// ```rust
+    /// # use simple_predicates::Eval;
+    /// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// # struct Contains(pub u32);
+    /// # impl Eval for Contains {
+    /// #     type Context = Vec<u32>;
+    /// #     fn eval(&self, data: &Self::Context) -> bool {
+    /// #         data.contains(&self.0)
+    /// #     }
+    /// # }
+    /// use simple_predicates::DnfVec;
+    /// use simple_predicates::Expr::Var;
+    ///
+    /// // (1 & !2) | (3 & 4)
+    /// let dnf = DnfVec::from(vec![
+    ///     Var(Contains(1)).and(!Var(Contains(2))),
+    ///     Var(Contains(3)).and(Var(Contains(4))),
+    /// ]);
+    /// let required = dnf.required_vars_per_term();
+    ///
+    /// assert_eq!(required[0].len(), 1);
+    /// assert!(required[0].contains(&Contains(1)));
+    /// assert_eq!(required[1].len(), 2);
+    /// ```
+    pub fn required_vars_per_term(&self) -> Vec<HashSet<V>>
+        where V: Eq + Hash + Clone
+    {
+        self.0.iter().map(required_vars).collect()
+    }
 }
 
 impl<V> Eval for DnfVec<V> where V: Eval + PartialEq {
@@ -143,25 +573,9 @@ impl<V> Eval for DnfVec<V> where V: Eval + PartialEq {
     }
 }
 
-impl<V> From<Expr<V>> for DnfVec<V> where V: Eval + PartialEq {
+impl<V> From<Expr<V>> for DnfVec<V> where V: Eval + PartialEq + Clone {
     fn from(expr: Expr<V>) -> Self {
-        use Expr::*;
-        let mut clauses = Vec::new();
-        let mut queue = Vec::with_capacity(2);
-        queue.push(expr.simplify());
-
-        while let Some(expr) = queue.pop() {
-            match expr.pushdown_not().distribute_and() {
-                Or(a, b) => {
-                    queue.push(*a);
-                    queue.push(*b);
-                },
-                other => {
-                    let _ = clauses.push(other);
-                }
-            }
-        }
-        DnfVec(clauses)
+        DnfVec(expr.into_normal_form(false))
     }
 }
 
@@ -191,3 +605,15 @@ impl<V> Default for DnfVec<V> where V: Eval + PartialEq {
         DnfVec(Vec::new())
     }
 }
+
+impl<V> BitOrAssign for DnfVec<V> where V: Eval + PartialEq {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = std::mem::take(self).union(rhs);
+    }
+}
+
+impl<V> BitOrAssign<Expr<V>> for DnfVec<V> where V: Eval + PartialEq + Clone {
+    fn bitor_assign(&mut self, rhs: Expr<V>) {
+        *self |= DnfVec::from(rhs);
+    }
+}